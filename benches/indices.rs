@@ -0,0 +1,41 @@
+//!
+//! Compares looking items up through the cache's indices with scanning every
+//! item, in a cache of 20,000 items.
+//!
+
+use std::env::{ temp_dir };
+use std::ffi::{ OsString };
+use std::fs::{ remove_file };
+use std::path::{ PathBuf };
+use criterion::{ Criterion, black_box, criterion_group, criterion_main };
+use uuid::{ Uuid };
+
+use tman::cache::{ Cache, Key, Metadata };
+
+///
+/// The number of items in the cache.
+///
+const ITEMS: usize = 20_000;
+
+fn lookups(criterion: &mut Criterion) {
+    let file: PathBuf = temp_dir().join(format!("tman-bench-{}.json", std::process::id()));
+    let mut cache: Cache = Cache::new(&file).unwrap();
+    let names: Vec<String> = (0..ITEMS).map(|index| format!("Bilbo-{}.txt", index)).collect();
+
+    for name in names.iter() {
+        cache.push(OsString::from(name), PathBuf::from("/home/Bilbo").join(name), Metadata::default(), &[]);
+    }
+
+    let name: &str = names[ITEMS / 2].as_str();
+    let uuid: Uuid = *cache.find(name)[0].uuid();
+
+    criterion.bench_function("find by name", |bencher| bencher.iter(|| cache.find(black_box(name)).len()));
+    criterion.bench_function("find by scanning", |bencher| bencher.iter(|| cache.find(|key: &Key| key.is_named(black_box(name))).len()));
+    criterion.bench_function("entry by UUID", |bencher| bencher.iter(|| cache.entry(black_box(&uuid)).is_some()));
+    criterion.bench_function("entry by scanning", |bencher| bencher.iter(|| cache.entries().iter().any(|entry| entry.uuid() == black_box(&uuid))));
+
+    remove_file(&file).unwrap_or_default();
+}
+
+criterion_group!(benches, lookups);
+criterion_main!(benches);
@@ -0,0 +1,121 @@
+//!
+//! The stable interface of the program, for tools built on top of it, which
+//! only changes in major releases.
+//! Everything outside of it may change between any releases.
+//!
+//! Changes to the trash are kept in memory until they are committed, as the
+//! program commits them once it's action is done.
+//! The trash is kept in `TMAN_HOME`, or `~/.tman` when it is unset.
+//!
+//! # Deleting
+//!
+//! ```
+//! # use std::env::{ set_var, temp_dir };
+//! # use std::fs::{ create_dir_all, remove_dir_all, write };
+//! # use std::path::{ PathBuf };
+//! use tman::api::{ TMan, DeleteOptions, Result };
+//!
+//! # fn main() -> Result<()> {
+//! # let root: PathBuf = temp_dir().join(format!("tman-api-delete-{}", std::process::id()));
+//! # create_dir_all(&root)?;
+//! # set_var("TMAN_HOME", root.join("trash"));
+//! # let file: PathBuf = root.join("Bilbo.txt");
+//! # write(&file, "There and back again")?;
+//! let mut app: TMan = TMan::new()?;
+//!
+//! app.delete(&file, &DeleteOptions::default())?;
+//! app.commit()?;
+//!
+//! assert!(!file.exists());
+//! # remove_dir_all(&root)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Querying
+//!
+//! ```
+//! # use std::env::{ set_var, temp_dir };
+//! # use std::fs::{ create_dir_all, remove_dir_all, write };
+//! # use std::path::{ PathBuf };
+//! use regex::{ Regex };
+//! use tman::api::{ TMan, DeleteOptions, ListOptions, Error, Result };
+//!
+//! # fn main() -> Result<()> {
+//! # let root: PathBuf = temp_dir().join(format!("tman-api-query-{}", std::process::id()));
+//! # create_dir_all(&root)?;
+//! # set_var("TMAN_HOME", root.join("trash"));
+//! # let file: PathBuf = root.join("Bilbo.txt");
+//! # write(&file, "There and back again")?;
+//! let mut app: TMan = TMan::new()?;
+//! let matching: ListOptions = ListOptions { quiet_match: true, ..ListOptions::default() };
+//!
+//! app.delete(&file, &DeleteOptions::default())?;
+//!
+//! assert!(app.list(Regex::new("^Bilbo")?, &matching).is_ok());
+//! assert!(matches!(app.list(Regex::new("^Frodo")?, &matching), Err(Error::NoMatch)));
+//!
+//! app.list(Regex::new("^Bilbo")?, &ListOptions { simple: true, ..ListOptions::default() })?;
+//! # remove_dir_all(&root)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Restoring
+//!
+//! ```
+//! # use std::env::{ set_var, temp_dir };
+//! # use std::fs::{ create_dir_all, read_to_string, remove_dir_all, write };
+//! # use std::path::{ PathBuf };
+//! use tman::api::{ TMan, DeleteOptions, RestoreOptions, Result };
+//!
+//! # fn main() -> Result<()> {
+//! # let root: PathBuf = temp_dir().join(format!("tman-api-restore-{}", std::process::id()));
+//! # create_dir_all(&root)?;
+//! # set_var("TMAN_HOME", root.join("trash"));
+//! # let file: PathBuf = root.join("Bilbo.txt");
+//! # write(&file, "There and back again")?;
+//! let mut app: TMan = TMan::new()?;
+//!
+//! app.delete(&file, &DeleteOptions::default())?;
+//! app.restore("Bilbo.txt", &RestoreOptions::default())?;
+//! app.commit()?;
+//!
+//! assert_eq!(read_to_string(&file)?, "There and back again");
+//! # remove_dir_all(&root)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Emptying
+//!
+//! ```
+//! # use std::env::{ set_var, temp_dir };
+//! # use std::fs::{ create_dir_all, remove_dir_all, write };
+//! # use std::path::{ PathBuf };
+//! use tman::api::{ TMan, DeleteOptions, RestoreOptions, TypeFilter, Result };
+//!
+//! # fn main() -> Result<()> {
+//! # let root: PathBuf = temp_dir().join(format!("tman-api-empty-{}", std::process::id()));
+//! # create_dir_all(&root)?;
+//! # set_var("TMAN_HOME", root.join("trash"));
+//! # let file: PathBuf = root.join("Bilbo.txt");
+//! # write(&file, "There and back again")?;
+//! let mut app: TMan = TMan::new()?;
+//!
+//! app.delete(&file, &DeleteOptions::default())?;
+//! app.empty(&TypeFilter::All, &[], true, false, false, false)?;
+//! app.commit()?;
+//!
+//! assert!(app.restore("Bilbo.txt", &RestoreOptions::default()).is_err());
+//! # remove_dir_all(&root)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+
+pub use super::{ TMan, DeleteOptions, RestoreOptions, OnConflict, ListOptions, SortKey };
+#[doc(inline)]
+pub use super::cache::{ TypeFilter };
+#[doc(inline)]
+pub use super::error::{ Error, Result, finish };
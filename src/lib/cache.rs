@@ -1,12 +1,32 @@
-use std::io::{ BufReader, BufWriter, Seek, SeekFrom };
-use std::fs::{ OpenOptions, File };
-use std::path::{ PathBuf };
-use serde::{ Serialize, Deserialize };
-use serde_json::{ from_reader, to_writer };
-use chrono::{ Utc };
+use std::io::{ BufReader, BufWriter, Write };
+use std::fs::{ OpenOptions, File, FileType, rename, remove_file, symlink_metadata };
+use std::path::{ Path, PathBuf };
+use std::ffi::{ OsStr, OsString };
+use serde::{ Serialize, Serializer, Deserialize, Deserializer };
+use serde_json::{ from_reader, from_str, to_writer, to_string };
+use chrono::{ DateTime, Utc, TimeZone, ParseResult, NaiveDate };
+use chrono::format::{ StrftimeItems, Item };
+use std::convert::{ TryFrom };
+use std::collections::{ HashMap };
 use uuid::{ Uuid };
 
 use super::error::{ Result, Error };
+use super::dedup::{ Manifest };
+
+///
+/// The version of the cache format written by this program.
+///
+const CACHE_FORMAT: u32 = 1;
+
+///
+/// The most restores remembered for each entry.
+///
+const MAX_EVENTS: usize = 20;
+
+///
+/// The most emptied entries whose restores are remembered.
+///
+const MAX_RETIRED: usize = 256;
 
 ///
 /// A map of key value pairs representing version controlled files in the trash,
@@ -18,26 +38,102 @@ use super::error::{ Result, Error };
 /// 
 /// # Example
 /// 
-/// ```
+/// ```ignore
 /// let file: PathBuf = PathBuf::from("./cache.json");
 /// let cache: Cache = Cache::new(&file)?;
 /// ```
 /// 
 pub struct Cache {
+    /// The header, as it was loaded or as `end` last wrote it.
+    header: Header,
     /// The entries.
     entries: Vec<Entry>,
-    /// The physical file.
-    file: File
+    /// Emptied entries, without versions, kept for the restores they record.
+    retired: Vec<Entry>,
+    /// The positions in `entries` of the entries with each name, in it's
+    /// lossy form, which is only kept in memory.
+    index: HashMap<String, Vec<usize>>,
+    /// The position in `entries` of the entry with each UUID, which is only
+    /// kept in memory.
+    uuids: HashMap<Uuid, usize>,
+    /// The path of the physical file.
+    path: PathBuf
 }
 
 ///
-/// A unique cache with a physical representation in a file with the name uuid.
+/// A summary of the cache, stored ahead of it's entries and refreshed whenever
+/// the cache is written.
+/// Every field is optional, so that caches written before the field existed
+/// can still be read.
 /// 
 /// # Example
 /// 
+/// ```ignore
+/// let header: Header = cache.header().clone();
 /// ```
-/// let name: String = "Bilbo.txt".to_string();
-/// let origin: String = "/home/Bilbo/Bilbo.txt".to_string();
+///
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Header {
+    /// The version of the cache format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    format: Option<u32>,
+    /// The time at which the cache was created.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Utc>>,
+    /// The time at which the cache was last rewritten.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    compacted_at: Option<DateTime<Utc>>,
+    /// The number of entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entries: Option<usize>,
+    /// The number of versions of all entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    versions: Option<usize>,
+    /// The total recorded size, in bytes, of all versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bytes: Option<u64>,
+    /// The architecture and operating system which last wrote the cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    platform: Option<String>
+}
+
+///
+/// The serialized forms of a cache.
+/// Caches were stored as bare lists of entries before they had a header.
+///
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CacheFormat {
+    /// A bare list of entries.
+    Legacy(Vec<Entry>),
+    /// A header, a list of entries and a list of emptied entries.
+    Current {
+        header: Header,
+        entries: Vec<Entry>,
+        #[serde(default)]
+        retired: Vec<Entry>
+    }
+}
+
+///
+/// The serialized form of a cache, borrowing it's contents.
+///
+#[derive(Serialize)]
+struct CacheContents<'a> {
+    header: &'a Header,
+    entries: &'a Vec<Entry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    retired: &'a Vec<Entry>
+}
+
+///
+/// A unique cache with a physical representation in a file with the name uuid.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let name: OsString = OsString::from("Bilbo.txt");
+/// let origin: PathBuf = PathBuf::from("/home/Bilbo/Bilbo.txt");
 /// let entry: Entry = Entry::new(
 ///     Key::new(name, origin),
 ///     Uuid::new_v4(),
@@ -45,14 +141,44 @@ pub struct Cache {
 /// );
 /// ```
 ///
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Entry {
     /// The unique key.
     key: Key,
     /// The UUID representing the entry's physical directory.
     uuid: Uuid,
     /// The versions of the entry.
-    history: Vec<String>
+    history: Vec<Version>,
+    /// The name shown in place of the key's name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    display_name: Option<String>,
+    /// The most recent restores of the entry's versions, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    events: Vec<Event>,
+    /// The tags given to any of the entry's versions, in the order they were
+    /// first given.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>
+}
+
+///
+/// A record of a version being restored.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let event: Event = Event::new(Utc::now(), *version.timestamp(), PathBuf::from("/home/Bilbo/Bilbo.txt"));
+/// ```
+///
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Event {
+    /// The time at which the version was restored.
+    restored_at: DateTime<Utc>,
+    /// The timestamp of the version restored.
+    version: DateTime<Utc>,
+    /// The path the version was restored to.
+    #[serde(serialize_with = "serialize_os_text", deserialize_with = "deserialize_os_text")]
+    destination: PathBuf
 }
 
 ///
@@ -61,26 +187,168 @@ pub struct Entry {
 /// 
 /// # Example
 /// 
-/// ```
-/// let name: String = "Bilbo.txt".to_string();
-/// let origin: String = "/home/Bilbo/Bilbo.txt".to_string();
+/// ```ignore
+/// let name: OsString = OsString::from("Bilbo.txt");
+/// let origin: PathBuf = PathBuf::from("/home/Bilbo/Bilbo.txt");
 /// let key: Key = Key::new(name, origin);
 /// ```
 ///
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct Key {
     /// The filename.
-    name: String,
+    #[serde(serialize_with = "serialize_os_text", deserialize_with = "deserialize_os_text")]
+    name: OsString,
     /// The original location.
-    origin: String
+    #[serde(serialize_with = "serialize_os_text", deserialize_with = "deserialize_os_text")]
+    origin: PathBuf
 }
 
 ///
-/// A search predicate for versions.
+/// The serialized forms of a name or path, which is a string when it is valid
+/// UTF-8, and otherwise the platform's representation, such as the bytes on
+/// Unix, so that it is kept exactly.
+///
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OsText {
+    /// A name or path which is valid UTF-8.
+    Text(String),
+    /// Any other name or path.
+    Native(OsString)
+}
+
+///
+/// A single version of an entry, identified by the timestamp at which it was
+/// trashed, whose display form is also the name of it's physical file.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let version: Version = Version::new(Utc::now(), Metadata::default());
+/// ```
+///
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(try_from = "VersionFormat")]
+pub struct Version {
+    /// The timestamp.
+    timestamp: DateTime<Utc>,
+    /// The metadata recorded when the version was trashed.
+    #[serde(flatten)]
+    metadata: Metadata
+}
+
+///
+/// The serialized forms of a version.
+/// Versions were stored as bare timestamps before they carried metadata, and
+/// timestamps were stored in their display form before they were stored as
+/// RFC 3339 strings.
+///
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VersionFormat {
+    /// A bare timestamp.
+    Legacy(String),
+    /// A timestamp and it's metadata.
+    Current {
+        timestamp: String,
+        #[serde(flatten)]
+        metadata: Metadata
+    }
+}
+
+///
+/// Information about a version recorded when it was trashed.
+/// Every field is optional, so that versions trashed before the field existed
+/// can still be read.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let mut metadata: Metadata = Metadata::default();
+/// metadata.set_link_target(Some("/home/Bilbo/Bilbo.txt".to_string()));
+/// ```
+///
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Metadata {
+    /// The target of the version, if it is a symbolic link.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    link_target: Option<String>,
+    /// The manifest of the version, if it is a directory stored incrementally.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    manifest: Option<Manifest>,
+    /// The size, in bytes, of the version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    /// The type of the version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kind: Option<Kind>,
+    /// The permission bits of the version when it was trashed (Unix only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mode: Option<u32>,
+    /// The modification time of the version when it was trashed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<DateTime<Utc>>,
+    /// Why the version was trashed, as given with `--reason`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    /// The transaction, shared by every version trashed by the same command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transaction: Option<Uuid>
+}
+
+///
+/// The type of a version's physical file.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let kind: Kind = Kind::Directory;
+/// ```
+///
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// A regular file.
+    File,
+    /// A directory.
+    Directory,
+    /// A symbolic link.
+    Symlink,
+    /// A named pipe (Unix only).
+    Fifo,
+    /// A socket (Unix only).
+    Socket,
+    /// A block or character device (Unix only).
+    Device
+}
+
+///
+/// A filter on the type of an entry's newest version.
+/// Entries whose type was not recorded match either type, unless `strict`.
 /// 
 /// # Example
 /// 
+/// ```ignore
+/// let filter: TypeFilter = TypeFilter::Directories { strict: false };
 /// ```
+///
+#[derive(Clone, Copy, Default)]
+pub enum TypeFilter {
+    /// Match every entry.
+    #[default]
+    All,
+    /// Match entries which are not directories.
+    Files { strict: bool },
+    /// Match entries which are directories.
+    Directories { strict: bool }
+}
+
+///
+/// A search predicate for versions.
+/// 
+/// # Example
+/// 
+/// ```ignore
 /// let predicate: VersionPredicate = VersionPredicate::Latest;
 /// ```
 ///
@@ -89,8 +357,152 @@ pub enum VersionPredicate<'a> {
     All,
     /// Match the latest version.
     Latest,
+    /// Match the oldest version.
+    Oldest,
     /// Match a specific version.
-    Specific(&'a str)
+    Specific(&'a str),
+    /// Match the nth newest version, counting from one.
+    Index(usize),
+    /// Match all versions trashed before a time.
+    OlderThan(DateTime<Utc>),
+    /// Match all versions trashed from a time, inclusive, until another,
+    /// exclusive, where a missing bound is unbounded.
+    Range(Option<DateTime<Utc>>, Option<DateTime<Utc>>),
+    /// Match all versions trashed in a transaction.
+    Transaction(Uuid),
+    /// Match all but the given number of newest versions.
+    AllButNewest(usize)
+}
+
+///
+/// A search predicate for keys, answered by `Cache::find`.
+/// Names, alone or with an origin, are looked up in the cache's index of
+/// names; closures over keys are tested against every item.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let entries: Vec<&Entry> = cache.find(("Bilbo.txt", Some(Path::new("/home/Bilbo/Bilbo.txt"))));
+/// ```
+///
+pub trait KeyQuery {
+    ///
+    /// The name, in it's lossy form, that every matching key has, if there
+    /// is one.
+    ///
+    fn name(&self) -> Option<&str>;
+
+    ///
+    /// Check whether `key` matches.
+    ///
+    fn matches(&self, key: &Key) -> bool;
+}
+
+impl<F> KeyQuery for F
+where
+    F: Fn(&Key) -> bool
+{
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    fn matches(&self, key: &Key) -> bool {
+        self(key)
+    }
+}
+
+impl KeyQuery for &str {
+    fn name(&self) -> Option<&str> {
+        Some(self)
+    }
+
+    fn matches(&self, key: &Key) -> bool {
+        key.is_named(self)
+    }
+}
+
+impl<'a> KeyQuery for (&'a str, Option<&'a Path>) {
+    fn name(&self) -> Option<&str> {
+        Some(self.0)
+    }
+
+    fn matches(&self, key: &Key) -> bool {
+        key.is_named(self.0) && self.1.is_none_or(|origin| key.origin() == origin)
+    }
+}
+
+///
+/// A problem with the trash found by `Cache::validate`.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let issues: Vec<Issue> = cache.validate(Path::new("./data"));
+/// ```
+///
+pub enum Issue {
+    /// An entry has no directory in the data.
+    MissingDirectory { name: String, uuid: Uuid },
+    /// A version has no file in it's entry's directory.
+    MissingVersion { name: String, uuid: Uuid, version: String },
+    /// An entry has the same key as an earlier one.
+    DuplicateKey { name: String, origin: String, uuid: Uuid },
+    /// A stored version is not later than the one stored before it.
+    Unordered { name: String, uuid: Uuid, version: String },
+    /// The cache file can not be read, for the given reason.
+    Unreadable(String),
+    /// The entries are not the same once written and read back.
+    Unstable
+}
+
+impl<'a> TryFrom<Option<&'a str>> for VersionPredicate<'a> {
+    type Error = Error;
+
+    ///
+    /// Parse a version argument, where `all`, `latest` and `oldest` have
+    /// special meanings, numbers are indices counting from the newest version,
+    /// `START..END` is a range of dates or timestamps, either of which may be
+    /// left out, and the absence of a version means the latest version.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let predicate: VersionPredicate = VersionPredicate::try_from(Some("2023-01-01..2023-02-01"))?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when a range has a malformed bound or ends before it starts.
+    ///
+    fn try_from(version: Option<&'a str>) -> Result<Self> {
+        Ok(match version {
+            Some("all") => VersionPredicate::All,
+            Some("latest") | None => VersionPredicate::Latest,
+            Some("oldest") => VersionPredicate::Oldest,
+            Some(version) if version.parse::<usize>().is_ok() => VersionPredicate::Index(version.parse().unwrap()),
+            Some(range) if range.contains("..") => {
+                let split: usize = range.find("..").unwrap();
+                let parse = |bound: &str| if bound.is_empty() {
+                    Ok(None)
+                } else {
+                    parse_bound(bound)
+                        .map(Some)
+                        .map_err(|_| Error::InvalidRange(range.to_string(), format!("'{}' is not a date or timestamp", bound)))
+                };
+                let start: Option<DateTime<Utc>> = parse(&range[..split])?;
+                let end: Option<DateTime<Utc>> = parse(&range[split + 2..])?;
+
+                if let (Some(start), Some(end)) = (start, end) {
+                    if end < start {
+                        Err(Error::InvalidRange(range.to_string(), String::from("it ends before it starts")))?;
+                    }
+                }
+
+                VersionPredicate::Range(start, end)
+            },
+            Some(version) => VersionPredicate::Specific(version)
+        })
+    }
 }
 
 impl Cache {
@@ -99,7 +511,7 @@ impl Cache {
     /// 
     /// # Example
     /// 
-    /// ```
+    /// ```ignore
     /// let path: PathBuf = PathBuf::from("./cache.json");
     /// let cache: Cache = Cache::new(&path)?;
     /// ```
@@ -113,57 +525,164 @@ impl Cache {
             .read(true)
             .write(true)
             .create(true)
+            .truncate(false)
             .open(path)?;
-        let entries: Vec<Entry> = from_reader(BufReader::new(&file)).unwrap_or(vec![]);
+        let (header, mut entries, retired): (Header, Vec<Entry>, Vec<Entry>) = read(&file)?;
+        let path: PathBuf = path.clone();
 
-        Ok(Cache {
+        //
+        // Timestamps stored in their old display form are parsed on load, so
+        // histories can be ordered by time rather than by text.
+        //
+        for entry in entries.iter_mut() {
+            entry.history.sort_by_key(|version| *version.timestamp());
+        }
+
+        let mut cache: Cache = Cache {
+            header,
             entries,
-            file
-        })
+            retired,
+            index: HashMap::new(),
+            uuids: HashMap::new(),
+            path
+        };
+
+        cache.reindex();
+
+        Ok(cache)
+    }
+
+    ///
+    /// Read the entries stored in `path` without opening it for writing,
+    /// holding a shared lock on it while it is read.
+    /// A missing file is read as containing no entries.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let path: PathBuf = PathBuf::from("./cache.json");
+    /// let entries: Vec<Entry> = Cache::snapshot(&path)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails if the file exists but can not be opened or locked, or is not a
+    /// valid cache.
+    ///
+    pub fn snapshot(path: &PathBuf) -> Result<Vec<Entry>> {
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+
+        let file: File = File::open(path)?;
+
+        //
+        // Writes replace the file rather than locking it, so the lock never
+        // holds them up, but it keeps out anything which locks the file to
+        // change it in place.
+        //
+        file.lock_shared()?;
+
+        let entries: Vec<Entry> = read(&file)?.1;
+
+        file.unlock()?;
+
+        Ok(entries)
     }
 
     ///
     /// Push a new version of an item onto the cache.
-    /// The item will be created, if it does not already exist.
+    /// The item will be created, if it does not already exist, taking the
+    /// restores recorded before it was last emptied.
     /// A UUID, representing the directory name of the item, and a timestamp,
     /// representing the version of the item, are returned.
+    /// The timestamp is always later than those of the item's other versions,
+    /// so that it names a version of it's own.
+    /// The item keeps it's tags, gaining any of `tags` it did not have.
     /// 
     /// # Example
     /// 
-    /// ```
-    /// let name: String = "Bilbo.txt".to_string();
-    /// let origin: String = "/home/Bilbo/Bilbo.txt".to_string();
-    /// let (uuid, version): (Uuid, String) = cache.push(name, origin);
+    /// ```ignore
+    /// let name: OsString = OsString::from("Bilbo.txt");
+    /// let origin: PathBuf = PathBuf::from("/home/Bilbo/Bilbo.txt");
+    /// let (uuid, version): (Uuid, String) = cache.push(name, origin, Metadata::default(), &[]);
     /// ```
     ///
-    pub fn push(&mut self, name: String, origin: String) -> (Uuid, String) {
-        let mut done: bool = false;
-        let key: Key = Key::new(name, origin.clone());
+    pub fn push(&mut self, name: OsString, origin: PathBuf, metadata: Metadata, tags: &[String]) -> (Uuid, String) {
+        let key: Key = Key::new(name, origin);
+        let position: Option<usize> = self.position(&key);
+        let mut timestamp: DateTime<Utc> = Utc::now();
+
         //
-        // Here the `uuid` must be optional since have an initial uuid does not
-        // make sense.
+        // Versions are stored under their timestamps, so a version trashed
+        // within the same tick of the clock as the item's newest, or while the
+        // clock is behind it, is moved just past it rather than overwriting it.
         //
-        let mut uuid: Option<Uuid> = None;
-        let version: String = format!("{}", Utc::now());
-        
-        for entry in self.entries.iter_mut() {
-            if entry.key() == &key {
-                entry.push(version.clone());
-                uuid = Some(entry.uuid().clone());
-                done = true;
-                break;
+        if let Some(newest) = position
+            .and_then(|position| self.entries[position].history.iter().map(|version| version.timestamp).max()) {
+            if timestamp <= newest {
+                timestamp = newest + chrono::Duration::nanoseconds(1);
             }
         }
+
+        let version: Version = Version::new(timestamp, metadata);
         
-        //
-        // Create the item if no versions were pushed -- the item does not exist.
-        //
-        if !done {
-            uuid = Some(Uuid::new_v4());
-            self.entries.push(Entry::new(key, uuid.clone().unwrap(), vec![version.clone()]));
-        }
+        let uuid: Uuid = if let Some(position) = position {
+            let entry: &mut Entry = &mut self.entries[position];
+
+            entry.push(version.clone());
+            entry.add_tags(tags);
+
+            *entry.uuid()
+        } else {
+            //
+            // Create the item, as it does not exist.
+            //
+            let mut entry: Entry = Entry::new(key, Uuid::new_v4(), vec![version.clone()]);
+
+            if let Some(index) = self.retired.iter().position(|retired| retired.key() == entry.key()) {
+                entry.events = self.retired.remove(index).events;
+            }
 
-        (uuid.unwrap(), version)
+            entry.add_tags(tags);
+
+            let uuid: Uuid = *entry.uuid();
+
+            self.index.entry(entry.key().name().to_string_lossy().to_string()).or_default().push(self.entries.len());
+            self.uuids.insert(uuid, self.entries.len());
+            self.entries.push(entry);
+
+            uuid
+        };
+
+        (uuid, version.display())
+    }
+
+    ///
+    /// Find the items whose keys satisfy `key_query`, without removing them.
+    /// Queries by name only look at the items with that name, through the
+    /// index, while closures, whose argument must be typed, scan every item.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let named: Vec<&Entry> = cache.find("Bilbo.txt");
+    /// let scanned: Vec<&Entry> = cache.find(|key: &Key| key.origin().starts_with("/home/Bilbo"));
+    /// ```
+    ///
+    pub fn find<KQ>(&self, key_query: KQ) -> Vec<&Entry>
+    where
+        KQ: KeyQuery
+    {
+        match key_query.name() {
+            Some(name) => self.index.get(name)
+                .map(|positions| positions.iter()
+                    .map(|position| &self.entries[*position])
+                    .filter(|entry| key_query.matches(entry.key()))
+                    .collect())
+                .unwrap_or_default(),
+            None => self.entries.iter().filter(|entry| key_query.matches(entry.key())).collect()
+        }
     }
 
     ///
@@ -173,7 +692,7 @@ impl Cache {
     /// 
     /// # Example
     /// 
-    /// ```
+    /// ```ignore
     /// let entries: Vec<(bool, Entry)> = cache.pop(|_| true, VersionPredicate::All)?;
     /// ```
     ///
@@ -185,143 +704,462 @@ impl Cache {
     where
         KP: Fn(&Key) -> bool
     {
-        let mut popped: Vec<(bool, Entry)> = vec![];
-        let mut indices: Vec<usize> = vec![];
-        let mut shift_factor: usize = 0;
-        let mut occurred: bool = false;
-        #[allow(unused_assignments)]
-        let mut empty: bool = false;
-        let mut victim_entry: Entry;
+        self.pop_where(|entry| key_predicate(entry.key()), version_predicate)
+    }
+
+    ///
+    /// Remove items or versions of items from the cache using predicates over
+    /// whole entries, rather than just their keys.
+    /// `entry_predicate` determines which items will be operated on.
+    /// `version_predicate` determines which versions of said items will be operated on.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let entries: Vec<(bool, Entry)> = cache.pop_where(|entry| entry.uuid() == &uuid, VersionPredicate::Latest)?;
+    /// ```
+    ///
+    /// # Errors
+    /// 
+    /// Fails when no entries satisfy the `entry_predicate` and when none of
+    /// their versions satisfy the `version_predicate`.
+    ///
+    pub fn pop_where<EP>(&mut self, entry_predicate: EP, version_predicate: VersionPredicate) -> Result<Vec<(bool, Entry)>>
+    where
+        EP: Fn(&Entry) -> bool
+    {
+        let popped: Vec<(bool, Entry)> = pop_entries(&mut self.entries, entry_predicate, version_predicate)?;
 
         //
-        // Pop required versions from the required entries, marking which
-        // entries are now empty.
+        // Removing emptied entries moves those after them.
         //
-        for (index, entry) in self.entries.iter_mut().enumerate() {
-            if key_predicate(entry.key()) {
-                victim_entry = Entry::new(entry.key().clone(), entry.uuid().clone(), entry.pop(&version_predicate));
-                empty = entry.history().len() == 0;
-
-                popped.push((empty, victim_entry));
-                
-                if empty {
-                    indices.push(index);
-                }
-
-                occurred = true;
-            }
-        }
+        self.reindex();
 
         //
-        // Remove all emptied entries from the cache.
-        // `shift_factor` is required to account for the vector shrinking.
+        // Emptied entries which were restored from are kept without their
+        // versions, so that their restores are still known if they are
+        // trashed again.
         //
-        for index in indices.iter() {
-            self.entries.remove(*index - shift_factor);
-            shift_factor += 1;
+        for (_, entry) in popped.iter().filter(|(empty, entry)| *empty && !entry.events().is_empty()) {
+            self.retired.retain(|retired| retired.key() != entry.key());
+            self.retired.push(Entry {
+                history: vec![],
+                ..entry.clone()
+            });
         }
 
-        if occurred {
-            Ok(popped)
-        } else {
-            Err(Error::MissingTargetPredicate)
+        if self.retired.len() > MAX_RETIRED {
+            self.retired.drain(..self.retired.len() - MAX_RETIRED);
         }
+
+        Ok(popped)
     }
 
     ///
-    /// Gain a reference to the entries.
+    /// Find the items, or versions of items, that `pop_where` would remove,
+    /// without removing them.
     /// 
     /// # Example
     /// 
-    /// ```
-    /// let entries: &Vec<Entry> = cache.entries();
+    /// ```ignore
+    /// let entries: Vec<Entry> = cache.select(|entry| entry.uuid() == &uuid, VersionPredicate::Latest)?;
     /// ```
     ///
-    pub fn entries(&self) -> &Vec<Entry> {
-        &self.entries
+    /// # Errors
+    /// 
+    /// Fails when `pop_where` would.
+    ///
+    pub fn select<EP>(&self, entry_predicate: EP, version_predicate: VersionPredicate) -> Result<Vec<Entry>>
+    where
+        EP: Fn(&Entry) -> bool
+    {
+        let mut entries: Vec<Entry> = self.entries.clone();
+
+        Ok(pop_entries(&mut entries, entry_predicate, version_predicate)?
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect())
     }
 
     ///
-    /// Commit changes to the cache.
+    /// Set the display name of all entries satisfying `entry_predicate`.
+    /// An empty display name removes the display name.
     /// 
     /// # Example
     /// 
+    /// ```ignore
+    /// cache.set_display_name(|entry| entry.key().is_named("REPORT~1.DOC"), "Report.doc".to_string())?;
     /// ```
-    /// cache.end()?;
-    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when no entries satisfy the `entry_predicate`.
     ///
-    pub fn end(&mut self) -> Result<()> {
-        self.file.set_len(0)?;
-        self.file.seek(SeekFrom::Start(0))?;
+    pub fn set_display_name<EP>(&mut self, entry_predicate: EP, display_name: String) -> Result<()>
+    where
+        EP: Fn(&Entry) -> bool
+    {
+        let mut occurred: bool = false;
 
-        to_writer(BufWriter::new(&self.file), &self.entries)?;
+        for entry in self.entries.iter_mut().filter(|entry| entry_predicate(entry)) {
+            entry.set_display_name(if display_name.is_empty() {
+                None
+            } else {
+                Some(display_name.clone())
+            });
 
-        Ok(())
+            occurred = true;
+        }
+
+        if occurred {
+            Ok(())
+        } else {
+            Err(Error::MissingTargetPredicate)
+        }
     }
-}
 
-impl Entry {
     ///
-    /// Create a new entry.
+    /// Record that a version of every entry satisfying `entry_predicate` was
+    /// restored.
     /// 
     /// # Example
     /// 
-    /// ```
-    /// let name: String = "Bilbo.txt".to_striong();
-    /// let origin: String = "/home/Bilbo/Bilbo.txt".to_string();
-    /// let entry: Entry = Entry::new(
-    ///     Key::new(name, origin),
-    ///     Uuid::new_v4(),
-    ///     vec![]
-    /// );
+    /// ```ignore
+    /// cache.record(|entry| entry.uuid() == &uuid, event);
     /// ```
     ///
-    pub fn new(key: Key, uuid: Uuid, history: Vec<String>) -> Entry {
-        Entry {
-            key,
-            uuid,
-            history
+    pub fn record<EP>(&mut self, entry_predicate: EP, event: Event)
+    where
+        EP: Fn(&Entry) -> bool
+    {
+        for entry in self.entries.iter_mut().filter(|entry| entry_predicate(entry)) {
+            entry.record(event.clone());
         }
     }
 
     ///
-    /// Push a new version into an entry.
+    /// Gain a reference to the emptied entries kept for the restores they
+    /// record.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let retired: &Vec<Entry> = cache.retired();
+    /// ```
+    ///
+    pub fn retired(&self) -> &Vec<Entry> {
+        &self.retired
+    }
+
+    ///
+    /// Gain a reference to the entries.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let entries: &Vec<Entry> = cache.entries();
+    /// ```
+    ///
+    pub fn entries(&self) -> &Vec<Entry> {
+        &self.entries
+    }
+
+    ///
+    /// Gain a reference to the entry with the UUID `uuid`, if there is one,
+    /// without scanning every item.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let entry: Option<&Entry> = cache.entry(&uuid);
+    /// ```
+    ///
+    pub fn entry(&self, uuid: &Uuid) -> Option<&Entry> {
+        self.uuids.get(uuid).map(|position| &self.entries[*position])
+    }
+
+    ///
+    /// Check the trash, whose data is kept in `data`, without changing it:
+    /// every entry has a directory and every version a file, no key belongs
+    /// to two entries, the stored versions of each entry are in chronological
+    /// order and the stored entries are the same once written and read back.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let issues: Vec<Issue> = cache.validate(Path::new("./data"));
+    /// ```
+    ///
+    pub fn validate(&self, data: &Path) -> Vec<Issue> {
+        let mut issues: Vec<Issue> = vec![];
+
+        for (position, entry) in self.entries.iter().enumerate() {
+            let name: String = entry.key().name().to_string_lossy().to_string();
+            let directory: PathBuf = data.join(entry.uuid().to_string());
+
+            if !directory.is_dir() {
+                issues.push(Issue::MissingDirectory { name: name.clone(), uuid: *entry.uuid() });
+            } else {
+                for version in entry.history() {
+                    if symlink_metadata(directory.join(version.display())).is_err() {
+                        issues.push(Issue::MissingVersion { name: name.clone(), uuid: *entry.uuid(), version: version.display() });
+                    }
+                }
+            }
+
+            if self.index.get(&name).is_some_and(|positions| positions.iter().any(|other| *other < position && self.entries[*other].key() == entry.key())) {
+                issues.push(Issue::DuplicateKey { name, origin: entry.key().origin().display().to_string(), uuid: *entry.uuid() });
+            }
+        }
+
+        //
+        // Histories are sorted as the cache is loaded, so their stored order
+        // is checked in the file itself.
+        //
+        let stored: Vec<Entry> = match File::open(&self.path).map_err(Error::from).and_then(|file| read(&file)) {
+            Ok((_, entries, _)) => entries,
+            Err(error) => {
+                issues.push(Issue::Unreadable(error.to_string()));
+
+                return issues;
+            }
+        };
+
+        for entry in stored.iter() {
+            for pair in entry.history().windows(2) {
+                if pair[1].timestamp() <= pair[0].timestamp() {
+                    issues.push(Issue::Unordered {
+                        name: entry.key().name().to_string_lossy().to_string(),
+                        uuid: *entry.uuid(),
+                        version: pair[1].display()
+                    });
+                }
+            }
+        }
+
+        let written: Option<String> = to_string(&stored).ok();
+        let rewritten: Option<String> = written.as_ref()
+            .and_then(|written| from_str::<Vec<Entry>>(written).ok())
+            .and_then(|entries| to_string(&entries).ok());
+
+        if written.is_none() || written != rewritten {
+            issues.push(Issue::Unstable);
+        }
+
+        issues
+    }
+
+    ///
+    /// Get the position in `entries` of the entry with the key `key`, if
+    /// there is one.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let position: Option<usize> = cache.position(&key);
+    /// ```
+    ///
+    fn position(&self, key: &Key) -> Option<usize> {
+        self.index.get(key.name().to_string_lossy().as_ref())?
+            .iter()
+            .copied()
+            .find(|position| self.entries[*position].key() == key)
+    }
+
+    ///
+    /// Rebuild the indices of the entries' names and UUIDs, as they are after
+    /// any entry is removed.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// cache.reindex();
+    /// ```
+    ///
+    fn reindex(&mut self) {
+        self.index.clear();
+        self.uuids.clear();
+
+        for (position, entry) in self.entries.iter().enumerate() {
+            self.index.entry(entry.key().name().to_string_lossy().to_string()).or_default().push(position);
+            self.uuids.insert(*entry.uuid(), position);
+        }
+    }
+
+    ///
+    /// Commit changes to the cache.
+    /// The cache is written to a temporary file beside it, which then replaces
+    /// it, so that a write which fails or is interrupted leaves the old cache
+    /// whole.
+    /// The temporary file is named after the process, so that runs ending at
+    /// the same time do not write to, or move, each other's.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// cache.end()?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails if the temporary file can not be written or moved over the cache,
+    /// in which case the cache is left as it was.
+    ///
+    pub fn end(&mut self) -> Result<()> {
+        self.header = Header::describe(&self.entries, &self.header);
+
+        let temporary: PathBuf = self.path.with_extension(format!("json.{}.tmp", std::process::id()));
+        let written: Result<()> = File::create(&temporary)
+            .map_err(Error::from)
+            .and_then(|file| {
+                let mut writer: BufWriter<&File> = BufWriter::new(&file);
+
+                to_writer(&mut writer, &CacheContents {
+                    header: &self.header,
+                    entries: &self.entries,
+                    retired: &self.retired
+                })?;
+                writer.flush()?;
+                drop(writer);
+
+                //
+                // The contents must reach the disk before the rename does, or
+                // a crash could leave the new name pointing at an empty file.
+                //
+                file.sync_all()?;
+
+                Ok(())
+            })
+            .and_then(|()| Ok(rename(&temporary, &self.path)?));
+
+        if written.is_err() {
+            remove_file(&temporary).unwrap_or_default();
+        }
+
+        written
+    }
+
+    ///
+    /// Get a reference to the header, as it was when the cache was loaded or,
+    /// once the cache has been written, as it was written.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let header: &Header = cache.header();
+    /// ```
+    ///
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+}
+
+impl Entry {
+    ///
+    /// Create a new entry.
     /// 
     /// # Example
     /// 
+    /// ```ignore
+    /// let name: String = "Bilbo.txt".to_striong();
+    /// let origin: String = "/home/Bilbo/Bilbo.txt".to_string();
+    /// let entry: Entry = Entry::new(
+    ///     Key::new(name, origin),
+    ///     Uuid::new_v4(),
+    ///     vec![]
+    /// );
     /// ```
-    /// entry.push(format!("{}", Utc::now()));
+    ///
+    pub fn new(key: Key, uuid: Uuid, history: Vec<Version>) -> Entry {
+        Entry {
+            key,
+            uuid,
+            history,
+            display_name: None,
+            events: vec![],
+            tags: vec![]
+        }
+    }
+
+    ///
+    /// Push a new version into an entry.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// entry.push(Version::new(Utc::now(), Metadata::default()));
     /// ```
     ///
-    pub fn push(&mut self, version: String) {
+    pub fn push(&mut self, version: Version) {
         self.history.push(version);
     }
 
+    ///
+    /// Give the entry each of `tags` it does not already have.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// entry.add_tags(&[ "ci-junk".to_string() ]);
+    /// ```
+    ///
+    pub fn add_tags(&mut self, tags: &[String]) {
+        for tag in tags {
+            if !self.tags.contains(tag) {
+                self.tags.push(tag.clone());
+            }
+        }
+    }
+
     ///
     /// Remove all versions that satisfy `predicate` from the history.
     ///
-    pub fn pop(&mut self, predicate: &VersionPredicate) -> Vec<String> {
-        let mut popped: Vec<String> = vec![];
+    pub fn pop(&mut self, predicate: &VersionPredicate) -> Vec<Version> {
+        let count: usize = self.history.len();
 
         match predicate {
-            VersionPredicate::All => {
-                popped = self.history.clone();
-                self.history.truncate(0);
-            },
-            VersionPredicate::Latest => {
-                popped.push(self.history.pop().unwrap());
-            },
-            VersionPredicate::Specific(target_version) => {
-                for (index, version) in self.history.iter().enumerate() {
-                    if &version == target_version {
-                        popped.push(self.history.remove(index));
-                        break;
-                    }
-                }
+            VersionPredicate::All => self.take_where(|_, _| true),
+            VersionPredicate::Latest => self.take_where(|index, _| index + 1 == count),
+            VersionPredicate::Oldest => self.take_where(|index, _| index == 0),
+            VersionPredicate::Specific(target_version) => self.take_where(|_, version| version.matches(target_version)),
+            VersionPredicate::Index(target_index) => self.take_where(|index, _| count - index == *target_index),
+            VersionPredicate::OlderThan(time) => self.take_where(|_, version| version.timestamp() < time),
+            VersionPredicate::Range(start, end) => self.take_where(|_, version| {
+                start.is_none_or(|start| *version.timestamp() >= start) && end.is_none_or(|end| *version.timestamp() < end)
+            }),
+            VersionPredicate::Transaction(transaction) => self.take_where(|_, version| version.metadata().transaction() == Some(transaction)),
+            VersionPredicate::AllButNewest(kept) => self.take_where(|index, _| index + kept < count)
+        }
+    }
+
+    ///
+    /// Remove the versions for which `predicate`, given each version's index
+    /// in the history (oldest first), holds, keeping the rest in order.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let popped: Vec<Version> = entry.take_where(|index, _| index == 0);
+    /// ```
+    ///
+    fn take_where<P>(&mut self, predicate: P) -> Vec<Version>
+    where
+        P: Fn(usize, &Version) -> bool
+    {
+        let mut taken: Vec<Version> = vec![];
+        let mut kept: Vec<Version> = vec![];
+
+        for (index, version) in self.history.drain(..).enumerate() {
+            if predicate(index, &version) {
+                taken.push(version);
+            } else {
+                kept.push(version);
             }
         }
 
-        popped
+        self.history = kept;
+
+        taken
     }
 
     ///
@@ -329,7 +1167,7 @@ impl Entry {
     /// 
     /// # Example
     /// 
-    /// ```
+    /// ```ignore
     /// let key: &Key = entry.key();
     /// ```
     ///
@@ -342,7 +1180,7 @@ impl Entry {
     /// 
     /// # Example
     /// 
-    /// ```
+    /// ```ignore
     /// let key: &Uuid = entry.uuid();
     /// ```
     ///
@@ -355,13 +1193,126 @@ impl Entry {
     /// 
     /// # Example
     /// 
-    /// ```
-    /// let key: &Vec<String> = entry.history();
+    /// ```ignore
+    /// let key: &Vec<Version> = entry.history();
     /// ```
     ///
-    pub fn history(&self) -> &Vec<String> {
+    pub fn history(&self) -> &Vec<Version> {
         &self.history
     }
+
+    ///
+    /// Get a reference to the entry's display name, if it has one.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let display_name: Option<&String> = entry.display_name();
+    /// ```
+    ///
+    pub fn display_name(&self) -> Option<&String> {
+        self.display_name.as_ref()
+    }
+
+    ///
+    /// Get the type of the entry's newest version, if it was recorded.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let kind: Option<Kind> = entry.kind();
+    /// ```
+    ///
+    pub fn kind(&self) -> Option<Kind> {
+        self.history.last().and_then(|version| version.metadata().kind())
+    }
+
+    ///
+    /// Check whether the recorded types of the entry's versions differ.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let mixed: bool = entry.has_mixed_kinds();
+    /// ```
+    ///
+    pub fn has_mixed_kinds(&self) -> bool {
+        let mut kinds = self.history.iter().filter_map(|version| version.metadata().kind());
+
+        match kinds.next() {
+            Some(first) => kinds.any(|kind| kind != first),
+            None => false
+        }
+    }
+
+    ///
+    /// Get a reference to the entry's recorded restores, oldest first.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let events: &Vec<Event> = entry.events();
+    /// ```
+    ///
+    pub fn events(&self) -> &Vec<Event> {
+        &self.events
+    }
+
+    ///
+    /// Get a reference to the entry's tags.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let tags: &Vec<String> = entry.tags();
+    /// ```
+    ///
+    pub fn tags(&self) -> &Vec<String> {
+        &self.tags
+    }
+
+    ///
+    /// Check whether the entry has every one of `tags`.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let tagged: bool = entry.has_tags(&[ "ci-junk".to_string() ]);
+    /// ```
+    ///
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|tag| self.tags.contains(tag))
+    }
+
+    ///
+    /// Record a restore, forgetting the oldest once there are too many.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// entry.record(Event::new(Utc::now(), *version.timestamp(), PathBuf::from("/home/Bilbo/Bilbo.txt")));
+    /// ```
+    ///
+    pub fn record(&mut self, event: Event) {
+        self.events.push(event);
+
+        if self.events.len() > MAX_EVENTS {
+            self.events.drain(..self.events.len() - MAX_EVENTS);
+        }
+    }
+
+    ///
+    /// Set or remove the entry's display name.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// entry.set_display_name(Some("Report.doc".to_string()));
+    /// ```
+    ///
+    pub fn set_display_name(&mut self, display_name: Option<String>) {
+        self.display_name = display_name;
+    }
 }
 
 impl Key {
@@ -370,13 +1321,13 @@ impl Key {
     /// 
     /// # Example
     /// 
-    /// ```
-    /// let name: String = "Bilbo.txt".to_string();
-    /// let origin: String = "/home/Bilbo/Bilbo.txt".to_string();
+    /// ```ignore
+    /// let name: OsString = OsString::from("Bilbo.txt");
+    /// let origin: PathBuf = PathBuf::from("/home/Bilbo/Bilbo.txt");
     /// let key: Key = Key::new(name, origin);
     /// ```
     /// 
-    pub fn new(name: String, origin: String) -> Key {
+    pub fn new(name: OsString, origin: PathBuf) -> Key {
         Key {
             name,
             origin
@@ -388,11 +1339,11 @@ impl Key {
     /// 
     /// # Example
     /// 
-    /// ```
-    /// let name: &String = key.name();
+    /// ```ignore
+    /// let name: &OsStr = key.name();
     /// ```
     ///
-    pub fn name(&self) -> &String {
+    pub fn name(&self) -> &OsStr {
         &self.name
     }
 
@@ -401,11 +1352,1213 @@ impl Key {
     /// 
     /// # Example
     /// 
-    /// ```
-    /// let origin: &String = key.origin();
+    /// ```ignore
+    /// let origin: &Path = key.origin();
     /// ```
     ///
-    pub fn origin(&self) -> &String {
+    pub fn origin(&self) -> &Path {
         &self.origin
     }
-}
\ No newline at end of file
+
+    ///
+    /// Check whether the key's name is `name`, comparing names which are not
+    /// valid UTF-8 by their lossy form, as they would be typed.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let matched: bool = key.is_named("Bilbo.txt");
+    /// ```
+    ///
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name.to_string_lossy() == name
+    }
+}
+
+impl Version {
+    ///
+    /// Create a new version.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let version: Version = Version::new(Utc::now(), Metadata::default());
+    /// ```
+    ///
+    pub fn new(timestamp: DateTime<Utc>, metadata: Metadata) -> Version {
+        Version {
+            timestamp,
+            metadata
+        }
+    }
+
+    ///
+    /// Get a reference to the version's timestamp.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let timestamp: &DateTime<Utc> = version.timestamp();
+    /// ```
+    ///
+    pub fn timestamp(&self) -> &DateTime<Utc> {
+        &self.timestamp
+    }
+
+    ///
+    /// Render the version's timestamp for display, which is also the name of
+    /// it's physical file.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let name: String = version.display();
+    /// ```
+    ///
+    pub fn display(&self) -> String {
+        format!("{}", self.timestamp)
+    }
+
+    ///
+    /// Render the version's timestamp in the chrono `format`, such as
+    /// `%Y-%m-%d %H:%M`, or `None` when the format is invalid.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let time: Option<String> = version.format("%Y-%m-%d %H:%M");
+    /// ```
+    ///
+    pub fn format(&self, format: &str) -> Option<String> {
+        if is_valid_format(format) {
+            Some(self.timestamp.format(format).to_string())
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Render the version's timestamp as a suffix for file names, which sorts
+    /// chronologically and is valid on every platform, such as
+    /// `20240131T154501Z`.
+    /// Fractions of a second are included when `precise` is set.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let suffix: String = version.suffix(false);
+    /// ```
+    ///
+    pub fn suffix(&self, precise: bool) -> String {
+        if precise {
+            self.timestamp.format("%Y%m%dT%H%M%S%.fZ").to_string()
+        } else {
+            self.timestamp.format("%Y%m%dT%H%M%SZ").to_string()
+        }
+    }
+
+    ///
+    /// Check whether `timestamp`, in either it's display or RFC 3339 form,
+    /// refers to the version.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let matches: bool = version.matches("2019-11-30 09:27:04.201347 UTC");
+    /// ```
+    ///
+    pub fn matches(&self, timestamp: &str) -> bool {
+        match parse_timestamp(timestamp) {
+            Ok(timestamp) => timestamp == self.timestamp,
+            Err(_) => false
+        }
+    }
+
+    ///
+    /// Get a reference to the version's metadata.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let metadata: &Metadata = version.metadata();
+    /// ```
+    ///
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+}
+
+impl Event {
+    ///
+    /// Create a new record of the version with the timestamp `version` being
+    /// restored to `destination`.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let event: Event = Event::new(Utc::now(), *version.timestamp(), PathBuf::from("/home/Bilbo/Bilbo.txt"));
+    /// ```
+    ///
+    pub fn new(restored_at: DateTime<Utc>, version: DateTime<Utc>, destination: PathBuf) -> Event {
+        Event {
+            restored_at,
+            version,
+            destination
+        }
+    }
+
+    ///
+    /// Get the time at which the version was restored.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let restored_at: &DateTime<Utc> = event.restored_at();
+    /// ```
+    ///
+    pub fn restored_at(&self) -> &DateTime<Utc> {
+        &self.restored_at
+    }
+
+    ///
+    /// Get the timestamp of the version restored.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let version: &DateTime<Utc> = event.version();
+    /// ```
+    ///
+    pub fn version(&self) -> &DateTime<Utc> {
+        &self.version
+    }
+
+    ///
+    /// Get the path the version was restored to.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let destination: &Path = event.destination();
+    /// ```
+    ///
+    pub fn destination(&self) -> &Path {
+        &self.destination
+    }
+}
+
+impl TryFrom<VersionFormat> for Version {
+    type Error = chrono::ParseError;
+
+    fn try_from(format: VersionFormat) -> ParseResult<Self> {
+        match format {
+            VersionFormat::Legacy(timestamp) => Ok(Version::new(parse_timestamp(&timestamp)?, Metadata::default())),
+            VersionFormat::Current { timestamp, metadata } => Ok(Version::new(parse_timestamp(&timestamp)?, metadata))
+        }
+    }
+}
+
+///
+/// Remove the versions satisfying `version_predicate` of the entries
+/// satisfying `entry_predicate`, as described by `Cache::pop_where`.
+///
+fn pop_entries<EP>(entries: &mut Vec<Entry>, entry_predicate: EP, version_predicate: VersionPredicate) -> Result<Vec<(bool, Entry)>>
+where
+    EP: Fn(&Entry) -> bool
+{
+    let mut popped: Vec<(bool, Entry)> = vec![];
+    let mut emptied: Vec<Uuid> = vec![];
+    let mut occurred: bool = false;
+    #[allow(unused_assignments)]
+    let mut empty: bool = false;
+    let mut victim_entry: Entry;
+    let mut missing: Option<String> = None;
+
+    //
+    // Pop required versions from the required entries, marking which
+    // entries are now empty.
+    // Entries without any matching versions (such as an entry with an empty
+    // history, from a hand-edited cache) are left as they are.
+    //
+    for entry in entries.iter_mut() {
+        if entry_predicate(entry) {
+            victim_entry = Entry::new(entry.key().clone(), *entry.uuid(), entry.pop(&version_predicate));
+            empty = entry.history().is_empty();
+            victim_entry.events = entry.events().clone();
+            victim_entry.tags = entry.tags().clone();
+            occurred = true;
+
+            if victim_entry.history().is_empty() {
+                missing = Some(match version_predicate {
+                    VersionPredicate::Specific(version) => version.to_string(),
+                    VersionPredicate::Index(index) => index.to_string(),
+                    _ => entry.key().name().to_string_lossy().to_string()
+                });
+
+                continue;
+            }
+
+            popped.push((empty, victim_entry));
+            
+            if empty {
+                emptied.push(*entry.uuid());
+            }
+        }
+    }
+
+    //
+    // Remove all emptied entries from the cache, which are identified by
+    // their UUIDs.
+    //
+    entries.retain(|entry| !emptied.contains(entry.uuid()));
+
+    if !occurred {
+        Err(Error::MissingTargetPredicate)
+    } else if popped.is_empty() {
+        Err(Error::MissingTarget(missing.unwrap_or_default()))
+    } else {
+        Ok(popped)
+    }
+}
+
+///
+/// Read the header, entries and emptied entries of a cache file.
+/// An empty file, such as one just created, is read as an empty cache.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let (header, entries, retired): (Header, Vec<Entry>, Vec<Entry>) = read(&file)?;
+/// ```
+/// 
+/// # Errors
+/// 
+/// Fails if the file can not be read, or holds anything but a valid cache,
+/// so that a corrupt cache is never mistaken for an empty one and then
+/// written over.
+///
+fn read(file: &File) -> Result<(Header, Vec<Entry>, Vec<Entry>)> {
+    if file.metadata()?.len() == 0 {
+        return Ok((Header::default(), vec![], vec![]));
+    }
+
+    match from_reader(BufReader::new(file))? {
+        CacheFormat::Legacy(entries) => Ok((Header::default(), entries, vec![])),
+        CacheFormat::Current { header, entries, retired } => Ok((header, entries, retired))
+    }
+}
+
+///
+/// Serialize a name or path in the form described by `OsText`.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// serialize_os_text(&PathBuf::from("/home/Bilbo/Bilbo.txt"), serializer)?;
+/// ```
+///
+pub fn serialize_os_text<T, S>(text: &T, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    T: AsRef<OsStr>,
+    S: Serializer
+{
+    match text.as_ref().to_str() {
+        Some(text) => serializer.serialize_str(text),
+        None => text.as_ref().serialize(serializer)
+    }
+}
+
+///
+/// Deserialize a name or path in any of the forms described by `OsText`.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let origin: PathBuf = deserialize_os_text(deserializer)?;
+/// ```
+///
+pub fn deserialize_os_text<'de, T, D>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    T: From<OsString>,
+    D: Deserializer<'de>
+{
+    Ok(T::from(match OsText::deserialize(deserializer)? {
+        OsText::Text(text) => OsString::from(text),
+        OsText::Native(text) => text
+    }))
+}
+
+///
+/// Check whether `format` is a chrono format which timestamps can be
+/// rendered in, as rendering one with an invalid format panics.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let valid: bool = is_valid_format("%Y-%m-%d %H:%M");
+/// ```
+///
+pub fn is_valid_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| item == Item::Error)
+}
+
+///
+/// Parse a timestamp in either it's display or RFC 3339 form.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let timestamp: DateTime<Utc> = parse_timestamp("2019-11-30T09:27:04.201347Z")?;
+/// ```
+///
+fn parse_timestamp(timestamp: &str) -> ParseResult<DateTime<Utc>> {
+    match DateTime::parse_from_rfc3339(timestamp) {
+        Ok(timestamp) => Ok(timestamp.with_timezone(&Utc)),
+        Err(_) => Utc.datetime_from_str(timestamp, "%Y-%m-%d %H:%M:%S%.f UTC")
+    }
+}
+
+///
+/// Parse a bound of a range of versions, which is a timestamp, as accepted by
+/// `parse_timestamp`, or a date, meaning it's midnight in UTC.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let start: DateTime<Utc> = parse_bound("2023-01-01")?;
+/// ```
+///
+fn parse_bound(bound: &str) -> ParseResult<DateTime<Utc>> {
+    match NaiveDate::parse_from_str(bound, "%Y-%m-%d") {
+        Ok(date) => Ok(Utc.from_utc_datetime(&date.and_hms(0, 0, 0))),
+        Err(_) => parse_timestamp(bound)
+    }
+}
+
+impl Metadata {
+    ///
+    /// Get a reference to the target of the version, if it is a symbolic link.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let link_target: Option<&String> = metadata.link_target();
+    /// ```
+    ///
+    pub fn link_target(&self) -> Option<&String> {
+        self.link_target.as_ref()
+    }
+
+    ///
+    /// Set or remove the target of the version.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// metadata.set_link_target(Some("/home/Bilbo/Bilbo.txt".to_string()));
+    /// ```
+    ///
+    pub fn set_link_target(&mut self, link_target: Option<String>) {
+        self.link_target = link_target;
+    }
+
+    ///
+    /// Get a reference to the manifest of the version, if it is a directory
+    /// stored incrementally.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let manifest: Option<&Manifest> = metadata.manifest();
+    /// ```
+    ///
+    pub fn manifest(&self) -> Option<&Manifest> {
+        self.manifest.as_ref()
+    }
+
+    ///
+    /// Set or remove the manifest of the version.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// metadata.set_manifest(Some(manifest(Path::new("./build"))?));
+    /// ```
+    ///
+    pub fn set_manifest(&mut self, manifest: Option<Manifest>) {
+        self.manifest = manifest;
+    }
+
+    ///
+    /// Get the recorded size of the version, in bytes.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let size: Option<u64> = metadata.size();
+    /// ```
+    ///
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    ///
+    /// Set or remove the recorded size of the version.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// metadata.set_size(Some(1024));
+    /// ```
+    ///
+    pub fn set_size(&mut self, size: Option<u64>) {
+        self.size = size;
+    }
+
+    ///
+    /// Get the recorded type of the version.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let kind: Option<Kind> = metadata.kind();
+    /// ```
+    ///
+    pub fn kind(&self) -> Option<Kind> {
+        self.kind
+    }
+
+    ///
+    /// Set or remove the recorded type of the version.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// metadata.set_kind(Some(Kind::File));
+    /// ```
+    ///
+    pub fn set_kind(&mut self, kind: Option<Kind>) {
+        self.kind = kind;
+    }
+
+    ///
+    /// Get the recorded permission bits of the version.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let mode: Option<u32> = metadata.mode();
+    /// ```
+    ///
+    pub fn mode(&self) -> Option<u32> {
+        self.mode
+    }
+
+    ///
+    /// Set or remove the recorded permission bits of the version.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// metadata.set_mode(Some(0o755));
+    /// ```
+    ///
+    pub fn set_mode(&mut self, mode: Option<u32>) {
+        self.mode = mode;
+    }
+
+    ///
+    /// Get the recorded modification time of the version.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let modified: Option<&DateTime<Utc>> = metadata.modified();
+    /// ```
+    ///
+    pub fn modified(&self) -> Option<&DateTime<Utc>> {
+        self.modified.as_ref()
+    }
+
+    ///
+    /// Set or remove the recorded modification time of the version.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// metadata.set_modified(Some(Utc::now()));
+    /// ```
+    ///
+    pub fn set_modified(&mut self, modified: Option<DateTime<Utc>>) {
+        self.modified = modified;
+    }
+
+    ///
+    /// Get a reference to why the version was trashed, if it was given.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let reason: Option<&String> = metadata.reason();
+    /// ```
+    ///
+    pub fn reason(&self) -> Option<&String> {
+        self.reason.as_ref()
+    }
+
+    ///
+    /// Set or remove why the version was trashed.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// metadata.set_reason(Some("replaced by Bilbo.md".to_string()));
+    /// ```
+    ///
+    pub fn set_reason(&mut self, reason: Option<String>) {
+        self.reason = reason;
+    }
+
+    ///
+    /// Get a reference to the transaction the version was trashed in, if it
+    /// was recorded.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let transaction: Option<&Uuid> = metadata.transaction();
+    /// ```
+    ///
+    pub fn transaction(&self) -> Option<&Uuid> {
+        self.transaction.as_ref()
+    }
+
+    ///
+    /// Set or remove the transaction the version was trashed in.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// metadata.set_transaction(Some(Uuid::new_v4()));
+    /// ```
+    ///
+    pub fn set_transaction(&mut self, transaction: Option<Uuid>) {
+        self.transaction = transaction;
+    }
+}
+
+impl Kind {
+    ///
+    /// Get the name of the type, as it is shown in listings.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let name: &str = Kind::Symlink.name();
+    /// ```
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::File => "file",
+            Kind::Directory => "directory",
+            Kind::Symlink => "symbolic link",
+            Kind::Fifo => "named pipe",
+            Kind::Socket => "socket",
+            Kind::Device => "device"
+        }
+    }
+
+    ///
+    /// Get the type of a file from it's file type, without following
+    /// symbolic links.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let kind: Kind = Kind::of(&symlink_metadata("./Bilbo.txt")?.file_type());
+    /// ```
+    ///
+    pub fn of(file_type: &FileType) -> Kind {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{ FileTypeExt };
+
+            if file_type.is_fifo() {
+                return Kind::Fifo;
+            } else if file_type.is_socket() {
+                return Kind::Socket;
+            } else if file_type.is_block_device() || file_type.is_char_device() {
+                return Kind::Device;
+            }
+        }
+
+        if file_type.is_symlink() {
+            Kind::Symlink
+        } else if file_type.is_dir() {
+            Kind::Directory
+        } else {
+            Kind::File
+        }
+    }
+
+    ///
+    /// Check whether the type is a special file, such as a socket, rather
+    /// than a file, directory or symbolic link.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let special: bool = Kind::Fifo.is_special();
+    /// ```
+    ///
+    pub fn is_special(&self) -> bool {
+        matches!(self, Kind::Fifo | Kind::Socket | Kind::Device)
+    }
+}
+
+impl TypeFilter {
+    ///
+    /// Check whether `entry` passes the filter.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let matches: bool = TypeFilter::Files { strict: true }.matches(&entry);
+    /// ```
+    ///
+    pub fn matches(&self, entry: &Entry) -> bool {
+        match (self, entry.kind()) {
+            (TypeFilter::All, _) => true,
+            (TypeFilter::Files { strict }, None) | (TypeFilter::Directories { strict }, None) => !strict,
+            (TypeFilter::Files { .. }, Some(kind)) => kind != Kind::Directory,
+            (TypeFilter::Directories { .. }, Some(kind)) => kind == Kind::Directory
+        }
+    }
+
+    ///
+    /// Check whether the filter matches every entry.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let all: bool = filter.is_all();
+    /// ```
+    ///
+    pub fn is_all(&self) -> bool {
+        matches!(self, TypeFilter::All)
+    }
+}
+
+impl Issue {
+    ///
+    /// Describe the issue, naming the entry and version it affects.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let description: String = issue.describe();
+    /// ```
+    ///
+    pub fn describe(&self) -> String {
+        match self {
+            Issue::MissingDirectory { name, uuid } => format!("{} ({}) has no directory in the data", name, uuid),
+            Issue::MissingVersion { name, uuid, version } => format!("{} ({}), version {}, has no stored copy", name, uuid, version),
+            Issue::DuplicateKey { name, origin, uuid } => format!("{} ({}) has the same name and origin, '{}', as another item", name, uuid, origin),
+            Issue::Unordered { name, uuid, version } => format!("{} ({}), version {}, is stored after a later version", name, uuid, version),
+            Issue::Unreadable(reason) => format!("the cache can not be read, {}", reason),
+            Issue::Unstable => String::from("the cache's entries change when they are written and read back")
+        }
+    }
+}
+
+impl Header {
+    ///
+    /// Describe `entries`, keeping the creation time of `previous`.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let header: Header = Header::describe(cache.entries(), cache.header());
+    /// ```
+    ///
+    pub fn describe(entries: &[Entry], previous: &Header) -> Header {
+        let now: DateTime<Utc> = Utc::now();
+        let versions = entries.iter().flat_map(|entry| entry.history().iter());
+
+        Header {
+            format: Some(CACHE_FORMAT),
+            created_at: Some(previous.created_at.unwrap_or(now)),
+            compacted_at: Some(now),
+            entries: Some(entries.len()),
+            versions: Some(versions.clone().count()),
+            bytes: Some(versions.filter_map(|version| version.metadata().size()).sum()),
+            platform: Some(format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS))
+        }
+    }
+
+    ///
+    /// Get the version of the cache format.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let format: Option<u32> = header.format();
+    /// ```
+    ///
+    pub fn format(&self) -> Option<u32> {
+        self.format
+    }
+
+    ///
+    /// Get the time at which the cache was created.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let created_at: Option<&DateTime<Utc>> = header.created_at();
+    /// ```
+    ///
+    pub fn created_at(&self) -> Option<&DateTime<Utc>> {
+        self.created_at.as_ref()
+    }
+
+    ///
+    /// Get the time at which the cache was last rewritten.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let compacted_at: Option<&DateTime<Utc>> = header.compacted_at();
+    /// ```
+    ///
+    pub fn compacted_at(&self) -> Option<&DateTime<Utc>> {
+        self.compacted_at.as_ref()
+    }
+
+    ///
+    /// Get the number of entries.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let entries: Option<usize> = header.entries();
+    /// ```
+    ///
+    pub fn entries(&self) -> Option<usize> {
+        self.entries
+    }
+
+    ///
+    /// Get the number of versions of all entries.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let versions: Option<usize> = header.versions();
+    /// ```
+    ///
+    pub fn versions(&self) -> Option<usize> {
+        self.versions
+    }
+
+    ///
+    /// Get the total recorded size, in bytes, of all versions.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let bytes: Option<u64> = header.bytes();
+    /// ```
+    ///
+    pub fn bytes(&self) -> Option<u64> {
+        self.bytes
+    }
+
+    ///
+    /// Get the architecture and operating system which last wrote the cache.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let platform: Option<&String> = header.platform();
+    /// ```
+    ///
+    pub fn platform(&self) -> Option<&String> {
+        self.platform.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::testing::{ Scratch };
+    use super::super::maintenance::{ MaintenancePolicy, OldestFirst };
+    use std::fs::{ create_dir_all, write, read_to_string, remove_file, remove_dir_all };
+    use serde_json::{ Value };
+
+    ///
+    /// Push `count` versions of each of `names`, trashed from `/home/Bilbo`.
+    ///
+    fn filled(scratch: &Scratch, names: &[&str], count: usize) -> Cache {
+        let mut cache: Cache = Cache::new(&scratch.join("cache.json")).unwrap();
+
+        for name in names {
+            for _ in 0..count {
+                cache.push(OsString::from(name), PathBuf::from("/home/Bilbo").join(name), Metadata::default(), &[]);
+            }
+        }
+
+        cache
+    }
+
+    ///
+    /// Give every version in `cache` a file in `data`, as trashing would.
+    ///
+    fn store(cache: &Cache, data: &Path) {
+        for entry in cache.entries() {
+            for version in entry.history() {
+                create_dir_all(data.join(entry.uuid().to_string())).unwrap();
+                write(data.join(entry.uuid().to_string()).join(version.display()), "Bilbo").unwrap();
+            }
+        }
+    }
+
+    ///
+    /// Write `cache` and load it again, after `change` has been made to it's
+    /// file as JSON.
+    ///
+    fn rewritten<C>(mut cache: Cache, change: C) -> Cache
+    where
+        C: Fn(&mut Value)
+    {
+        cache.end().unwrap();
+
+        let mut contents: Value = from_str(&read_to_string(&cache.path).unwrap()).unwrap();
+
+        change(&mut contents);
+        write(&cache.path, to_string(&contents).unwrap()).unwrap();
+
+        Cache::new(&cache.path).unwrap()
+    }
+
+    #[test]
+    fn validate_accepts_pushes_pops_and_evictions() {
+        let scratch: Scratch = Scratch::new();
+        let data: PathBuf = scratch.join("data");
+        let mut cache: Cache = filled(&scratch, &[ "Bilbo.txt", "Frodo.txt" ], 3);
+
+        store(&cache, &data);
+        cache.end().unwrap();
+        assert!(cache.validate(&data).is_empty());
+
+        for (empty, entry) in cache.pop(|key| key.is_named("Bilbo.txt"), VersionPredicate::Latest).unwrap() {
+            assert!(!empty);
+            remove_file(data.join(entry.uuid().to_string()).join(entry.history()[0].display())).unwrap();
+        }
+
+        cache.end().unwrap();
+        assert!(cache.validate(&data).is_empty());
+
+        for entry in cache.entries.iter_mut() {
+            for version in entry.history.iter_mut() {
+                version.metadata.set_size(Some(5));
+            }
+        }
+
+        for eviction in OldestFirst.plan(cache.entries(), 15, None) {
+            cache.pop_where(|entry| entry.uuid() == &eviction.uuid, VersionPredicate::Specific(&eviction.version)).unwrap();
+            remove_file(data.join(eviction.uuid.to_string()).join(&eviction.version)).unwrap();
+        }
+
+        cache.end().unwrap();
+        assert_eq!(cache.entries().iter().map(|entry| entry.history().len()).sum::<usize>(), 2);
+        assert!(cache.validate(&data).is_empty());
+    }
+
+    #[test]
+    fn validate_finds_missing_data() {
+        let scratch: Scratch = Scratch::new();
+        let data: PathBuf = scratch.join("data");
+        let mut cache: Cache = filled(&scratch, &[ "Bilbo.txt", "Frodo.txt" ], 2);
+        let bilbo: Entry = cache.find("Bilbo.txt")[0].clone();
+        let frodo: Entry = cache.find("Frodo.txt")[0].clone();
+
+        store(&cache, &data);
+        cache.end().unwrap();
+        remove_dir_all(data.join(bilbo.uuid().to_string())).unwrap();
+        remove_file(data.join(frodo.uuid().to_string()).join(frodo.history()[1].display())).unwrap();
+
+        let issues: Vec<Issue> = cache.validate(&data);
+
+        assert_eq!(issues.len(), 2);
+        assert!(matches!(&issues[0], Issue::MissingDirectory { name, uuid } if name == "Bilbo.txt" && uuid == bilbo.uuid()));
+        assert!(matches!(&issues[1], Issue::MissingVersion { name, version, .. } if name == "Frodo.txt" && *version == frodo.history()[1].display()));
+    }
+
+    #[test]
+    fn validate_finds_corrupt_entries() {
+        let scratch: Scratch = Scratch::new();
+        let data: PathBuf = scratch.join("data");
+        let cache: Cache = filled(&scratch, &[ "Bilbo.txt", "Frodo.txt" ], 2);
+
+        store(&cache, &data);
+
+        let cache: Cache = rewritten(cache, |contents| {
+            let mut duplicate: Value = contents["entries"][0].clone();
+
+            duplicate["uuid"] = Value::from(Uuid::new_v4().to_string());
+            contents["entries"].as_array_mut().unwrap().push(duplicate);
+            contents["entries"][1]["history"].as_array_mut().unwrap().swap(0, 1);
+        });
+        let issues: Vec<Issue> = cache.validate(&data);
+
+        assert!(issues.iter().any(|issue| matches!(issue, Issue::MissingDirectory { name, .. } if name == "Bilbo.txt")));
+        assert!(issues.iter().any(|issue| matches!(issue, Issue::DuplicateKey { name, .. } if name == "Bilbo.txt")));
+        assert!(issues.iter().any(|issue| matches!(issue, Issue::Unordered { name, .. } if name == "Frodo.txt")));
+        assert_eq!(issues.len(), 3);
+    }
+
+    #[test]
+    fn validate_finds_an_unreadable_cache() {
+        let scratch: Scratch = Scratch::new();
+        let data: PathBuf = scratch.join("data");
+        let mut cache: Cache = filled(&scratch, &[ "Bilbo.txt" ], 1);
+
+        store(&cache, &data);
+        cache.end().unwrap();
+        write(scratch.join("cache.json"), "{ \"entries\": [").unwrap();
+
+        let issues: Vec<Issue> = cache.validate(&data);
+
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(&issues[0], Issue::Unreadable(_)));
+    }
+
+    #[test]
+    fn an_empty_cache_file_is_an_empty_cache() {
+        let scratch: Scratch = Scratch::new();
+
+        write(scratch.join("cache.json"), "").unwrap();
+
+        assert!(Cache::new(&scratch.join("cache.json")).unwrap().entries().is_empty());
+        assert!(Cache::snapshot(&scratch.join("cache.json")).unwrap().is_empty());
+        assert!(Cache::snapshot(&scratch.join("missing.json")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn a_written_cache_is_read_back() {
+        let scratch: Scratch = Scratch::new();
+        let mut cache: Cache = filled(&scratch, &[ "Bilbo.txt", "Frodo.txt" ], 2);
+
+        cache.end().unwrap();
+
+        let read: Cache = Cache::new(&scratch.join("cache.json")).unwrap();
+
+        assert_eq!(read.entries().len(), 2);
+        assert_eq!(read.header().entries(), Some(2));
+        assert_eq!(read.header().versions(), Some(4));
+        assert_eq!(read.find("Frodo.txt")[0].history().len(), 2);
+        assert_eq!(read.find("Frodo.txt")[0].uuid(), cache.find("Frodo.txt")[0].uuid());
+    }
+
+    #[test]
+    fn a_malformed_cache_is_refused() {
+        let scratch: Scratch = Scratch::new();
+
+        for contents in &[ "{ \"entries\": [", "[ 1, 2 ]", "{ \"header\": {}, \"entries\": 3 }", "Bilbo" ] {
+            write(scratch.join("cache.json"), contents).unwrap();
+
+            assert!(matches!(Cache::new(&scratch.join("cache.json")), Err(Error::InvalidJSON(_, _))), "{} was read", contents);
+            assert!(matches!(Cache::snapshot(&scratch.join("cache.json")), Err(Error::InvalidJSON(_, _))), "{} was read", contents);
+            assert_eq!(read_to_string(scratch.join("cache.json")).unwrap(), *contents);
+        }
+    }
+
+    #[test]
+    fn a_failed_write_leaves_the_old_cache() {
+        let scratch: Scratch = Scratch::new();
+        let mut cache: Cache = filled(&scratch, &[ "Bilbo.txt" ], 1);
+
+        cache.end().unwrap();
+
+        let written: String = read_to_string(scratch.join("cache.json")).unwrap();
+
+        cache.push(OsString::from("Frodo.txt"), PathBuf::from("/home/Bilbo/Frodo.txt"), Metadata::default(), &[]);
+        create_dir_all(scratch.join(format!("cache.json.{}.tmp", std::process::id()))).unwrap();
+
+        assert!(cache.end().is_err());
+        assert_eq!(read_to_string(scratch.join("cache.json")).unwrap(), written);
+        assert_eq!(Cache::new(&scratch.join("cache.json")).unwrap().entries().len(), 1);
+    }
+
+    #[test]
+    fn rapid_pushes_make_distinct_ordered_versions() {
+        let scratch: Scratch = Scratch::new();
+        let mut cache: Cache = Cache::new(&scratch.join("cache.json")).unwrap();
+        let pushed: Vec<String> = (0..50)
+            .map(|_| cache.push(OsString::from("Bilbo.txt"), PathBuf::from("/home/Bilbo/Bilbo.txt"), Metadata::default(), &[]).1)
+            .collect();
+        let history: &Vec<Version> = cache.find("Bilbo.txt")[0].history();
+
+        assert_eq!(cache.entries().len(), 1);
+        assert_eq!(history.iter().map(|version| version.display()).collect::<Vec<String>>(), pushed);
+        assert!(history.windows(2).all(|pair| pair[0].timestamp() < pair[1].timestamp()));
+    }
+
+    #[test]
+    fn pop_all_empties_every_entry() {
+        let scratch: Scratch = Scratch::new();
+        let mut cache: Cache = filled(&scratch, &[ "Bilbo.txt", "Frodo.txt", "Sam.txt" ], 2);
+
+        let popped: Vec<(bool, Entry)> = cache.pop_where(|_| true, VersionPredicate::All).unwrap();
+
+        assert_eq!(popped.len(), 3);
+        assert!(popped.iter().all(|(empty, entry)| *empty && entry.history().len() == 2));
+        assert!(cache.entries().is_empty());
+        assert!(cache.find("Bilbo.txt").is_empty());
+        assert!(matches!(cache.pop_where(|_| true, VersionPredicate::All), Err(Error::MissingTargetPredicate)));
+    }
+
+    #[test]
+    fn indexed_lookups_agree_with_scanning() {
+        let scratch: Scratch = Scratch::new();
+        let mut cache: Cache = filled(&scratch, &[ "Bilbo.txt", "Frodo.txt", "Sam.txt" ], 2);
+
+        for directory in &[ "/home/Frodo", "/home/Sam" ] {
+            cache.push(OsString::from("Frodo.txt"), PathBuf::from(directory).join("Frodo.txt"), Metadata::default(), &[]);
+        }
+
+        cache.pop(|key| key.is_named("Sam.txt"), VersionPredicate::All).unwrap();
+
+        for name in &[ "Bilbo.txt", "Frodo.txt", "Sam.txt", "Gandalf.txt", "bilbo.txt" ] {
+            let indexed: Vec<&Uuid> = cache.find(*name).iter().map(|entry| entry.uuid()).collect();
+            let scanned: Vec<&Uuid> = cache.find(|key: &Key| key.is_named(name)).iter().map(|entry| entry.uuid()).collect();
+
+            assert_eq!(indexed, scanned, "{}", name);
+
+            for uuid in scanned {
+                assert_eq!(cache.entry(uuid).map(Entry::uuid), Some(uuid));
+            }
+        }
+    }
+
+    #[test]
+    fn indices_follow_popped_entries() {
+        let scratch: Scratch = Scratch::new();
+        let mut cache: Cache = filled(&scratch, &[ "Bilbo.txt", "Frodo.txt", "Sam.txt" ], 1);
+        let frodo: Uuid = *cache.find("Frodo.txt")[0].uuid();
+        let sam: Uuid = *cache.find("Sam.txt")[0].uuid();
+
+        cache.pop(|key| key.is_named("Bilbo.txt"), VersionPredicate::All).unwrap();
+
+        assert!(cache.find("Bilbo.txt").is_empty());
+        assert!(cache.entry(&frodo).unwrap().key().is_named("Frodo.txt"));
+        assert!(cache.entry(&sam).unwrap().key().is_named("Sam.txt"));
+
+        let (uuid, _): (Uuid, String) = cache.push(OsString::from("Bilbo.txt"), PathBuf::from("/home/Bilbo/Bilbo.txt"), Metadata::default(), &[]);
+
+        assert!(cache.entry(&uuid).unwrap().key().is_named("Bilbo.txt"));
+        assert_eq!(cache.find("Bilbo.txt").len(), 1);
+    }
+
+    #[test]
+    fn popping_an_empty_history_is_an_error() {
+        let scratch: Scratch = Scratch::new();
+        let cache: Cache = filled(&scratch, &[ "Bilbo.txt", "Frodo.txt" ], 1);
+        let mut cache: Cache = rewritten(cache, |contents| {
+            contents["entries"][0]["history"] = Value::Array(vec![]);
+        });
+
+        for predicate in [ VersionPredicate::Latest, VersionPredicate::Oldest, VersionPredicate::All ] {
+            assert!(matches!(cache.pop(|key| key.is_named("Bilbo.txt"), predicate), Err(Error::MissingTarget(name)) if name == "Bilbo.txt"));
+        }
+
+        assert!(matches!(cache.pop(|key| key.is_named("Bilbo.txt"), VersionPredicate::Specific("1999-01-01 00:00:00 UTC")), Err(Error::MissingTarget(version)) if version == "1999-01-01 00:00:00 UTC"));
+        assert_eq!(cache.find("Bilbo.txt").len(), 1);
+        assert_eq!(cache.pop(|key| key.is_named("Frodo.txt"), VersionPredicate::Latest).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn find_answers_every_kind_of_query() {
+        let scratch: Scratch = Scratch::new();
+        let mut cache: Cache = filled(&scratch, &[ "Bilbo.txt", "Frodo.txt" ], 2);
+
+        for directory in &[ "/home/Frodo", "/home/Sam" ] {
+            cache.push(OsString::from("Frodo.txt"), PathBuf::from(directory).join("Frodo.txt"), Metadata::default(), &[]);
+        }
+
+        let origins = |entries: Vec<&Entry>| -> Vec<PathBuf> {
+            entries.iter().map(|entry| entry.key().origin().to_path_buf()).collect()
+        };
+        let frodos: Vec<PathBuf> = vec![ PathBuf::from("/home/Bilbo/Frodo.txt"), PathBuf::from("/home/Frodo/Frodo.txt"), PathBuf::from("/home/Sam/Frodo.txt") ];
+
+        assert!(cache.find("Gandalf.txt").is_empty());
+        assert!(cache.find("bilbo.txt").is_empty());
+        assert!(cache.find(("Frodo.txt", Some(Path::new("/home/Gandalf/Frodo.txt")))).is_empty());
+        assert!(cache.find(|key: &Key| key.origin().starts_with("/home/Gandalf")).is_empty());
+
+        assert_eq!(origins(cache.find("Bilbo.txt")), vec![ PathBuf::from("/home/Bilbo/Bilbo.txt") ]);
+        assert_eq!(origins(cache.find(("Frodo.txt", Some(Path::new("/home/Sam/Frodo.txt"))))), vec![ PathBuf::from("/home/Sam/Frodo.txt") ]);
+        assert_eq!(origins(cache.find(|key: &Key| key.origin().starts_with("/home/Frodo"))), vec![ PathBuf::from("/home/Frodo/Frodo.txt") ]);
+        assert_eq!(cache.find("Bilbo.txt")[0].history().len(), 2);
+
+        assert_eq!(origins(cache.find("Frodo.txt")), frodos);
+        assert_eq!(origins(cache.find(("Frodo.txt", None))), frodos);
+        assert_eq!(origins(cache.find(|key: &Key| key.is_named("Frodo.txt"))), frodos);
+        assert_eq!(cache.find(|key: &Key| key.origin().starts_with("/home/Bilbo")).len(), 2);
+    }
+
+    #[test]
+    fn type_filters_follow_the_newest_version() {
+        let scratch: Scratch = Scratch::new();
+        let mut cache: Cache = Cache::new(&scratch.join("cache.json")).unwrap();
+
+        for (name, kinds) in [ ("Bilbo.txt", vec![ Some(Kind::File) ]), ("Shire", vec![ Some(Kind::Directory) ]), ("Old.txt", vec![ None ]), ("Mixed", vec![ Some(Kind::File), Some(Kind::Directory) ]), ("Link", vec![ Some(Kind::Symlink) ]) ] {
+            for kind in kinds {
+                let mut metadata: Metadata = Metadata::default();
+
+                metadata.set_kind(kind);
+                cache.push(OsString::from(name), PathBuf::from("/home/Bilbo").join(name), metadata, &[]);
+            }
+        }
+
+        let filtered = |filter: TypeFilter| -> Vec<String> {
+            cache.entries().iter()
+                .filter(|entry| filter.matches(entry))
+                .map(|entry| entry.key().name().to_string_lossy().to_string())
+                .collect()
+        };
+
+        assert_eq!(filtered(TypeFilter::All), vec![ "Bilbo.txt", "Shire", "Old.txt", "Mixed", "Link" ]);
+        assert_eq!(filtered(TypeFilter::Files { strict: false }), vec![ "Bilbo.txt", "Old.txt", "Link" ]);
+        assert_eq!(filtered(TypeFilter::Files { strict: true }), vec![ "Bilbo.txt", "Link" ]);
+        assert_eq!(filtered(TypeFilter::Directories { strict: false }), vec![ "Shire", "Old.txt", "Mixed" ]);
+        assert_eq!(filtered(TypeFilter::Directories { strict: true }), vec![ "Shire", "Mixed" ]);
+    }
+
+    #[test]
+    fn display_names_are_kept_and_cleared() {
+        let scratch: Scratch = Scratch::new();
+        let mut cache: Cache = filled(&scratch, &[ "REPORT~1.DOC", "Bilbo.txt" ], 1);
+
+        cache.set_display_name(|entry| entry.key().is_named("REPORT~1.DOC"), String::from("Quarterly report.doc")).unwrap();
+
+        assert!(matches!(cache.set_display_name(|entry| entry.key().is_named("Gandalf.txt"), String::from("Gandalf")), Err(Error::MissingTargetPredicate)));
+
+        cache.end().unwrap();
+
+        let contents: Value = from_str(&read_to_string(&cache.path).unwrap()).unwrap();
+
+        assert_eq!(contents["entries"][0]["display_name"], "Quarterly report.doc");
+        assert!(contents["entries"][1].get("display_name").is_none());
+
+        let mut cache: Cache = Cache::new(&cache.path).unwrap();
+
+        assert_eq!(cache.find("REPORT~1.DOC")[0].display_name().map(String::as_str), Some("Quarterly report.doc"));
+        assert_eq!(cache.find("Bilbo.txt")[0].display_name(), None);
+
+        cache.set_display_name(|entry| entry.key().is_named("REPORT~1.DOC"), String::new()).unwrap();
+
+        assert_eq!(cache.find("REPORT~1.DOC")[0].display_name(), None);
+    }
+}
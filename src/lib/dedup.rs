@@ -0,0 +1,351 @@
+use std::collections::{ BTreeMap };
+use std::io::{ Read, BufReader };
+use std::fs::{ File, read_dir, symlink_metadata, hard_link, rename, remove_file, copy };
+use std::path::{ Path, PathBuf };
+use std::time::{ SystemTime };
+
+use super::error::{ Result };
+use super::permissions::{ set_modified };
+
+///
+/// A map of the relative paths of the regular files in a directory tree to
+/// hashes of their contents.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let manifest: Manifest = manifest(Path::new("./build"))?;
+/// ```
+///
+pub type Manifest = BTreeMap<String, u64>;
+
+///
+/// Hash the contents of a file with 64-bit FNV-1a, which unlike the standard
+/// library's hasher is stable across releases and so may be stored.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let hash: u64 = hash_file(Path::new("./Bilbo.txt"))?;
+/// ```
+///
+pub fn hash_file(path: &Path) -> Result<u64> {
+    let mut reader: BufReader<File> = BufReader::new(File::open(path)?);
+    let mut buffer: [u8; 8192] = [0; 8192];
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+    loop {
+        let read: usize = reader.read(&mut buffer)?;
+
+        if read == 0 {
+            break;
+        }
+
+        for byte in buffer[..read].iter() {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    Ok(hash)
+}
+
+///
+/// Build the manifest of the directory tree `root`.
+/// Symbolic links are not followed.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let manifest: Manifest = manifest(Path::new("./build"))?;
+/// ```
+///
+pub fn manifest(root: &Path) -> Result<Manifest> {
+    let mut manifest: Manifest = Manifest::new();
+
+    for file in files(root)? {
+        if let Ok(relative) = file.strip_prefix(root) {
+            manifest.insert(relative.to_string_lossy().to_string(), hash_file(&file)?);
+        }
+    }
+
+    Ok(manifest)
+}
+
+///
+/// Replace each file in the tree `current` which is unchanged from the same
+/// file in the tree `previous` with a hard link to the latter.
+/// Files are compared byte for byte before linking, so a hash collision can
+/// never lose data, and are left as they are where linking is unsupported.
+/// The number of bytes saved is returned.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let saved: u64 = link_unchanged(&previous, &previous_manifest, &current, &current_manifest);
+/// ```
+///
+pub fn link_unchanged(previous: &Path, previous_manifest: &Manifest, current: &Path, current_manifest: &Manifest) -> u64 {
+    let mut saved: u64 = 0;
+
+    for (relative, hash) in current_manifest.iter() {
+        if previous_manifest.get(relative) == Some(hash) {
+            saved += link_identical(&previous.join(relative), &current.join(relative));
+        }
+    }
+
+    saved
+}
+
+///
+/// Replace the file `duplicate` with a hard link to the file `original`, if
+/// their contents are identical and they are not already linked.
+/// Files are compared byte for byte, and left as they are where linking is
+/// unsupported.
+/// A linked duplicate shares the original's permissions and modification
+/// time, as they belong to the file rather than the link.
+/// The number of bytes saved is returned.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let saved: u64 = link_identical(Path::new("./old/Bilbo.txt"), Path::new("./new/Bilbo.txt"));
+/// ```
+///
+pub fn link_identical(original: &Path, duplicate: &Path) -> u64 {
+    let temporary: PathBuf = PathBuf::from(format!("{}.tman-link", duplicate.display()));
+
+    if same_file(original, duplicate).unwrap_or(true) || !same_contents(original, duplicate).unwrap_or(false) {
+        return 0;
+    }
+
+    //
+    // Link beside the duplicate first, so that the duplicate is only ever
+    // replaced by a complete link.
+    //
+    if hard_link(original, &temporary).is_ok() {
+        if rename(&temporary, duplicate).is_ok() {
+            return symlink_metadata(duplicate).map(|metadata| metadata.len()).unwrap_or(0);
+        }
+
+        remove_file(&temporary).unwrap_or_default();
+    }
+
+    0
+}
+
+///
+/// Give every file in the tree `root` that shares it's data with another
+/// version it's own copy, so that changing a restored file can never change a
+/// version left in the trash.
+/// The copies keep the modification times of the files they replace, as do
+/// the directories inside `root` they are replaced in.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// separate(Path::new("./build"))?;
+/// ```
+///
+pub fn separate(root: &Path) -> Result<()> {
+    for file in files(root)? {
+        if links(&file)? > 1 {
+            let temporary: PathBuf = PathBuf::from(format!("{}.tman-copy", file.display()));
+            let modified: Option<SystemTime> = symlink_metadata(&file)?.modified().ok();
+            let parent: Option<(&Path, SystemTime)> = file.parent()
+                .filter(|_| file != root)
+                .and_then(|parent| Some((parent, symlink_metadata(parent).ok()?.modified().ok()?)));
+
+            copy(&file, &temporary)?;
+
+            if let Some(modified) = modified {
+                set_modified(&temporary, modified)?;
+            }
+
+            rename(&temporary, &file)?;
+
+            //
+            // Replacing the file touches it's directory, whose time is put
+            // back.
+            //
+            if let Some((parent, modified)) = parent {
+                set_modified(parent, modified)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+///
+/// Collect the regular files in the tree `root`, which may itself be a
+/// regular file, without following symbolic links.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let files: Vec<PathBuf> = files(Path::new("./build"))?;
+/// ```
+///
+pub fn files(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = vec![];
+    let mut directories: Vec<PathBuf> = vec![root.to_path_buf()];
+
+    if symlink_metadata(root)?.is_file() {
+        return Ok(vec![ root.to_path_buf() ]);
+    }
+
+    while let Some(directory) = directories.pop() {
+        for child in read_dir(&directory)? {
+            let path: PathBuf = child?.path();
+            let file_type = symlink_metadata(&path)?.file_type();
+
+            if file_type.is_dir() {
+                directories.push(path);
+            } else if file_type.is_file() {
+                files.push(path);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+///
+/// Compare two files byte for byte.
+///
+fn same_contents(left: &Path, right: &Path) -> Result<bool> {
+    if symlink_metadata(left)?.len() != symlink_metadata(right)?.len() {
+        return Ok(false);
+    }
+
+    let mut left_reader: BufReader<File> = BufReader::new(File::open(left)?);
+    let mut right_reader: BufReader<File> = BufReader::new(File::open(right)?);
+    let mut left_buffer: [u8; 8192] = [0; 8192];
+    let mut right_buffer: [u8; 8192] = [0; 8192];
+
+    loop {
+        let read: usize = left_reader.read(&mut left_buffer)?;
+
+        if read == 0 {
+            return Ok(true);
+        }
+
+        right_reader.read_exact(&mut right_buffer[..read])?;
+
+        if left_buffer[..read] != right_buffer[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+///
+/// Check whether two paths are hard links to the same file.
+/// Platforms without file identities never report the same file.
+///
+#[cfg(unix)]
+fn same_file(left: &Path, right: &Path) -> Result<bool> {
+    use std::os::unix::fs::{ MetadataExt };
+
+    let (left, right) = (symlink_metadata(left)?, symlink_metadata(right)?);
+
+    Ok(left.dev() == right.dev() && left.ino() == right.ino())
+}
+
+#[cfg(not(unix))]
+fn same_file(_: &Path, _: &Path) -> Result<bool> {
+    Ok(false)
+}
+
+///
+/// Count the hard links to a file.
+/// Platforms without hard link counts report a single link.
+///
+#[cfg(unix)]
+pub fn links(path: &Path) -> Result<u64> {
+    use std::os::unix::fs::{ MetadataExt };
+
+    Ok(symlink_metadata(path)?.nlink())
+}
+
+#[cfg(not(unix))]
+pub fn links(_: &Path) -> Result<u64> {
+    Ok(1)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use super::super::testing::{ Scratch };
+    use std::fs::{ create_dir_all, write, read_to_string };
+    use std::time::{ Duration, UNIX_EPOCH };
+
+    ///
+    /// Write the files of a build, keyed by their relative paths, into
+    /// `root`.
+    ///
+    fn build(root: &Path, files: &[(&str, &str)]) -> Manifest {
+        for (relative, contents) in files {
+            create_dir_all(root.join(relative).parent().unwrap()).unwrap();
+            write(root.join(relative), contents).unwrap();
+        }
+
+        manifest(root).unwrap()
+    }
+
+    #[test]
+    fn link_identical_links_only_identical_files() {
+        let scratch: Scratch = Scratch::new();
+
+        write(scratch.join("Bilbo.txt"), "Bilbo").unwrap();
+        write(scratch.join("copy.txt"), "Bilbo").unwrap();
+        write(scratch.join("Frodo.txt"), "Frodo").unwrap();
+
+        assert_eq!(link_identical(&scratch.join("Bilbo.txt"), &scratch.join("copy.txt")), 5);
+        assert!(same_file(&scratch.join("Bilbo.txt"), &scratch.join("copy.txt")).unwrap());
+        assert_eq!(link_identical(&scratch.join("Bilbo.txt"), &scratch.join("copy.txt")), 0);
+        assert_eq!(link_identical(&scratch.join("Bilbo.txt"), &scratch.join("Frodo.txt")), 0);
+        assert!(!same_file(&scratch.join("Bilbo.txt"), &scratch.join("Frodo.txt")).unwrap());
+        assert_eq!(link_identical(&scratch.join("Bilbo.txt"), &scratch.join("missing.txt")), 0);
+        assert!(!scratch.join("copy.txt.tman-link").exists());
+    }
+
+    #[test]
+    fn link_unchanged_links_only_unchanged_files() {
+        let scratch: Scratch = Scratch::new();
+        let previous: Manifest = build(&scratch.join("previous"), &[ ("Bilbo.txt", "Bilbo"), ("src/main.rs", "fn main() {}"), ("src/lib.rs", "old") ]);
+        let current: Manifest = build(&scratch.join("current"), &[ ("Bilbo.txt", "Bilbo"), ("src/main.rs", "fn main() {}"), ("src/lib.rs", "new"), ("Frodo.txt", "Frodo") ]);
+
+        assert_eq!(link_unchanged(&scratch.join("previous"), &previous, &scratch.join("current"), &current), 17);
+
+        for (relative, linked) in &[ ("Bilbo.txt", true), ("src/main.rs", true), ("src/lib.rs", false) ] {
+            assert_eq!(same_file(&scratch.join("previous").join(relative), &scratch.join("current").join(relative)).unwrap(), *linked, "{}", relative);
+        }
+
+        assert_eq!(read_to_string(scratch.join("current/src/lib.rs")).unwrap(), "new");
+    }
+
+    #[test]
+    fn separate_copies_linked_files_keeping_their_times() {
+        let scratch: Scratch = Scratch::new();
+        let previous: Manifest = build(&scratch.join("previous"), &[ ("Bilbo.txt", "Bilbo"), ("src/main.rs", "fn main() {}") ]);
+        let current: Manifest = build(&scratch.join("current"), &[ ("Bilbo.txt", "Bilbo"), ("src/main.rs", "fn main() {}") ]);
+        let file_time: SystemTime = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let directory_time: SystemTime = UNIX_EPOCH + Duration::from_secs(1_100_000_000);
+
+        set_modified(&scratch.join("previous/src/main.rs"), file_time).unwrap();
+        link_unchanged(&scratch.join("previous"), &previous, &scratch.join("current"), &current);
+        set_modified(&scratch.join("current/src"), directory_time).unwrap();
+
+        separate(&scratch.join("current")).unwrap();
+
+        for relative in &[ "Bilbo.txt", "src/main.rs" ] {
+            assert!(!same_file(&scratch.join("previous").join(relative), &scratch.join("current").join(relative)).unwrap());
+            assert_eq!(links(&scratch.join("current").join(relative)).unwrap(), 1);
+        }
+
+        assert_eq!(symlink_metadata(scratch.join("current/src/main.rs")).unwrap().modified().unwrap(), file_time);
+        assert_eq!(symlink_metadata(scratch.join("current/src")).unwrap().modified().unwrap(), directory_time);
+        assert_eq!(read_to_string(scratch.join("current/src/main.rs")).unwrap(), "fn main() {}");
+    }
+}
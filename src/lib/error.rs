@@ -1,3 +1,5 @@
+use std::path::{ Path };
+
 ///
 /// A custom result type with specialized errors.
 ///
@@ -6,6 +8,7 @@ pub type Result<T> = std::result::Result<T, Error>;
 ///
 /// Custom errors that may occur.
 ///
+#[derive(Debug)]
 pub enum Error {
     /// Program was called with invalid arguments.
     InvalidArguments,
@@ -17,22 +20,108 @@ pub enum Error {
     MissingTarget(String),
     /// Could not locate a target file or entry satisfying a predicate.
     MissingTargetPredicate,
+    /// Could not access a target file.
+    PermissionDenied(String),
+    /// Could not locate several target files or entries.
+    MissingTargets(Vec<String>),
+    /// An origin was given for more than one target.
+    AmbiguousOrigin,
+    /// A name was trashed from several origins, listing them, and none was
+    /// chosen.
+    AmbiguousName(String, Vec<String>),
+    /// A destination directory could not be created.
+    InvalidDestination(String),
+    /// A path that must be a directory is something else.
+    NotADirectory(String),
+    /// A question was asked without a terminal to answer it.
+    NotATerminal,
+    /// An invalid UUID was passed as an argument.
+    InvalidUuid(String),
+    /// An argument other than a file name is not valid UTF-8.
+    InvalidUnicode(String),
+    /// An invalid duration was passed as an argument.
+    InvalidDuration(String),
+    /// A setting which does not exist, or can not be set, was named.
+    InvalidSetting(String),
+    /// A setting was given a value of the wrong type, as the setting and the
+    /// value.
+    InvalidSettingValue(String, String),
+    /// An invalid range of versions was passed as an argument, and why.
+    InvalidRange(String, String),
+    /// The counts recorded in the cache's header do not match it's entries.
+    InconsistentCache(Vec<String>),
+    /// Checking the trash found this many issues.
+    FailedCheck(usize),
+    /// A restore would move a version onto itself or into the trash.
+    UnsafeDestination(String),
+    /// An empty pattern, matching every item, was given without `--all`.
+    EmptyPattern,
+    /// A line of a batch file could not be parsed.
+    InvalidCommand(String),
+    /// Some lines of a batch file failed, counting the failures and lines.
+    BatchFailed(usize, usize),
+    /// Some targets of a delete failed, counting the failures and targets.
+    DeleteFailed(usize, usize),
+    /// Some items of a restore failed, counting the failures and items.
+    RestoreFailed(usize, usize),
+    /// A configured location lies inside another, naming both.
+    InvalidConfiguration(String, String),
+    /// A target to delete contains the trash or lies inside it.
+    TrashTarget(String),
+    /// A target to delete resolves to a path without a name, such as `/`.
+    UnnamedTarget(String, String),
+    /// The trash would be moved to a directory already holding a trash.
+    ExistingTrash(String),
+    /// A target to delete is, or contains, a protected path.
+    ProtectedPath(String),
+    /// A target to delete is a special file, such as a socket, naming it's
+    /// type.
+    SpecialFile(String, &'static str),
+    /// A special file would have to be copied to another filesystem, which
+    /// can not be done faithfully, naming it's type.
+    UncopyableFile(String, &'static str),
+    /// A directory would be deleted without a terminal to confirm it, and
+    /// without `--yes`.
+    UnconfirmedDirectory(String),
+    /// Many paths matching a pattern would be deleted without a terminal to
+    /// confirm them, and without `--yes`, counting them.
+    UnconfirmedMatches(usize, String),
+    /// Items would be permanently deleted without a terminal to confirm it,
+    /// and without `--yes`, counting them.
+    UnconfirmedEmpty(usize),
+    /// A copy made across filesystems differs from it's original.
+    CopyMismatch(String),
+    /// A path inside an item is absolute or leaves the item.
+    InvalidInnerPath(String),
+    /// No items matched a listing which only reports whether any did.
+    NoMatch,
+    /// Trashing a target would grow the trash past it's quota, in bytes, and
+    /// eviction was not allowed.
+    QuotaExceeded(String, u64),
+    /// There is no delete or restore left in the log to undo.
+    NothingToUndo,
+    /// A line of the log, given by the log and the line's number, is not a
+    /// record.
+    InvalidRecord(String, usize),
+    /// A filesystem operation failed, with the kind of failure and what was
+    /// being done, such as `moving '/x/y'`, if it is known.
+    Io(std::io::ErrorKind, String),
     /// A unknown error.
     Unknown,
 }
 
 impl Error {
     ///
-    /// Display the error as a `String`.
+    /// Describe the error, without any prefix or punctuation.
     /// 
     /// # Example
     /// 
-    /// ```
-    /// Error::InvalidArguments.pring();
+    /// ```ignore
+    /// let message: String = Error::InvalidArguments.message();
     /// ```
     ///
-    fn print(&self) -> String {
-        format!("trash: error: {}!", match self {
+    pub fn message(&self) -> String {
+        match self {
             Error::InvalidArguments => String::from("invalid arguments"),
             Error::InvalidJSON(line, column) => format!("syntax error on line {}, column {}, of settings.json or cache.json", line, column),
             Error::InvalidRegex(regex_error) => {
@@ -46,8 +135,146 @@ impl Error {
             },
             Error::MissingTarget(target) => format!("could not locate '{}'", target),
             Error::MissingTargetPredicate => String::from("could not locate any target satisfying given conditions"),
+            Error::PermissionDenied(target) => format!("permission denied for '{}'", target),
+            Error::MissingTargets(targets) => format!("could not locate '{}'", targets.join("', '")),
+            Error::AmbiguousOrigin => String::from("an origin can only be given when restoring a single file"),
+            Error::AmbiguousName(name, origins) => format!("'{}' was trashed from several origins, '{}', use --origin to choose one or --all-origins to restore them all", name, origins.join("', '")),
+            Error::InvalidDestination(destination) => format!("could not create destination directory '{}'", destination),
+            Error::NotADirectory(path) => format!("'{}' is not a directory", path),
+            Error::NotATerminal => String::from("can not ask questions without a terminal"),
+            Error::InvalidUuid(uuid) => format!("'{}' is not a valid UUID", uuid),
+            Error::InvalidUnicode(argument) => format!("the value of --{} is not valid UTF-8", argument),
+            Error::InvalidDuration(duration) => format!("'{}' is not a valid duration", duration),
+            Error::InvalidSetting(key) => format!("there is no setting '{}'", key),
+            Error::InvalidSettingValue(key, value) => format!("'{}' is not a valid value for the setting '{}'", value, key),
+            Error::InvalidRange(range, reason) => format!("'{}' is not a valid version range, {}", range, reason),
+            Error::InconsistentCache(counts) => format!("the cache's {} have drifted from it's header", counts.join(", ")),
+            Error::FailedCheck(count) => format!("found {} issues with the trash", count),
+            Error::UnsafeDestination(destination) => format!("refusing to restore onto '{}', which is stored in the trash (use --to to restore elsewhere)", destination),
+            Error::EmptyPattern => String::from("an empty pattern matches every item, add --all to restore them all"),
+            Error::InvalidCommand(reason) => format!("invalid command, {}", reason),
+            Error::BatchFailed(failed, total) => format!("{} of {} commands failed", failed, total),
+            Error::DeleteFailed(failed, total) => format!("{} of {} deletes failed", failed, total),
+            Error::RestoreFailed(failed, total) => format!("{} of {} restores failed", failed, total),
+            Error::InvalidConfiguration(inner, outer) => format!("invalid configuration, '{}' can not be inside '{}'", inner, outer),
+            Error::TrashTarget(target) => format!("refusing to delete '{}', which contains or is part of the trash", target),
+            Error::UnnamedTarget(target, origin) => format!("'{}' resolves to '{}', which has no name to trash it under", target, origin),
+            Error::ExistingTrash(directory) => format!("refusing to move the trash to '{}', which already holds one", directory),
+            Error::ProtectedPath(path) => format!("refusing to delete '{}', which is protected, use --allow-protected to delete it anyway", path),
+            Error::UnconfirmedDirectory(path) => format!("refusing to delete the directory '{}' without confirmation, use --yes to delete it anyway", path),
+            Error::SpecialFile(path, kind) => format!("refusing to delete '{}', which is a {}, use --allow-special to delete it anyway", path, kind),
+            Error::UncopyableFile(path, kind) => format!("refusing to copy '{}', which is a {}, to another filesystem", path, kind),
+            Error::UnconfirmedMatches(count, pattern) => format!("refusing to delete the {} paths matching '{}' without confirmation, use --yes to delete them anyway", count, pattern),
+            Error::UnconfirmedEmpty(count) => format!("refusing to permanently delete {} items without confirmation, use --yes to delete them anyway", count),
+            Error::CopyMismatch(path) => format!("the copy of '{}' does not match the original, which was kept", path),
+            Error::InvalidInnerPath(path) => format!("'{}' is not a relative path inside the item", path),
+            Error::NoMatch => String::from("no items matched"),
+            Error::QuotaExceeded(target, limit) => format!("refusing to trash '{}', which would grow the trash past it's quota of {} bytes, leave out --no-evict to evict old versions instead", target, limit),
+            Error::NothingToUndo => String::from("there is nothing to undo"),
+            Error::InvalidRecord(log, line) => format!("line {} of '{}' is not a valid record, remove it to undo earlier changes", line, log),
+            Error::Io(kind, context) if context.is_empty() => kind.to_string(),
+            Error::Io(kind, context) => format!("{} while {}", kind, context),
             Error::Unknown => String::from("unknown")
-        })
+        }
+    }
+
+    ///
+    /// Describe a failure to locate `target`, which is missing or can not be
+    /// accessed, unless something else went wrong.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let origin: PathBuf = canonicalize(path).map_err(|error| Error::locating("Bilbo.txt", error))?;
+    /// ```
+    ///
+    pub fn locating(target: &str, io_error: std::io::Error) -> Error {
+        match io_error.kind() {
+            std::io::ErrorKind::NotFound => Error::MissingTarget(target.to_string()),
+            std::io::ErrorKind::PermissionDenied => Error::PermissionDenied(target.to_string()),
+            _ => Error::from(io_error)
+        }
+    }
+
+    ///
+    /// Get the status the program exits with after the error, which is 1 when
+    /// nothing matched, like `grep`, and otherwise tells the category of the
+    /// error, as documented by `--help`, so that scripts can branch on it.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// std::process::exit(Error::NoMatch.exit_code());
+    /// ```
+    ///
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::NoMatch => 1,
+            Error::InvalidArguments
+            | Error::InvalidRegex(_)
+            | Error::AmbiguousOrigin
+            | Error::AmbiguousName(_, _)
+            | Error::InvalidUuid(_)
+            | Error::InvalidUnicode(_)
+            | Error::InvalidDuration(_)
+            | Error::InvalidSetting(_)
+            | Error::InvalidSettingValue(_, _)
+            | Error::InvalidRange(_, _)
+            | Error::EmptyPattern
+            | Error::InvalidCommand(_)
+            | Error::InvalidInnerPath(_) => 2,
+            Error::MissingTarget(_)
+            | Error::MissingTargetPredicate
+            | Error::MissingTargets(_)
+            | Error::NothingToUndo => 3,
+            Error::Io(_, _)
+            | Error::PermissionDenied(_)
+            | Error::InvalidDestination(_)
+            | Error::NotADirectory(_)
+            | Error::CopyMismatch(_) => 4,
+            Error::InvalidJSON(_, _)
+            | Error::InconsistentCache(_)
+            | Error::FailedCheck(_)
+            | Error::InvalidConfiguration(_, _)
+            | Error::InvalidRecord(_, _) => 5,
+            Error::NotATerminal
+            | Error::UnsafeDestination(_)
+            | Error::TrashTarget(_)
+            | Error::UnnamedTarget(_, _)
+            | Error::ExistingTrash(_)
+            | Error::ProtectedPath(_)
+            | Error::SpecialFile(_, _)
+            | Error::UncopyableFile(_, _)
+            | Error::UnconfirmedDirectory(_)
+            | Error::UnconfirmedMatches(_, _)
+            | Error::UnconfirmedEmpty(_)
+            | Error::QuotaExceeded(_, _) => 6,
+            Error::BatchFailed(_, _)
+            | Error::DeleteFailed(_, _)
+            | Error::RestoreFailed(_, _) => 7,
+            Error::Unknown => 8
+        }
+    }
+}
+
+///
+/// Errors display as their message, such as `invalid arguments`.
+///
+impl std::fmt::Display for Error {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "{}", self.message())
+    }
+}
+
+///
+/// Errors are standard errors, caused by the regex error they wrap, if any.
+///
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidRegex(regex_error) => Some(regex_error),
+            _ => None
+        }
     }
 }
 
@@ -55,8 +282,40 @@ impl Error {
 /// Conversions from IO errors to custom errors.
 ///
 impl From<std::io::Error> for Error {
-    fn from(_: std::io::Error) -> Self {
-        Error::Unknown
+    fn from(io_error: std::io::Error) -> Self {
+        Error::Io(io_error.kind(), String::new())
+    }
+}
+
+///
+/// Attaching what was being done, and to which path, to filesystem errors.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// move_path(&from, &to).context("moving", &from)?;
+/// ```
+///
+pub trait Context<T> {
+    ///
+    /// Describe a failure as happening while doing `action` to `path`,
+    /// unless it is already described.
+    ///
+    fn context(self, action: &str, path: &Path) -> Result<T>;
+}
+
+impl<T> Context<T> for std::io::Result<T> {
+    fn context(self, action: &str, path: &Path) -> Result<T> {
+        self.map_err(Error::from).context(action, path)
+    }
+}
+
+impl<T> Context<T> for Result<T> {
+    fn context(self, action: &str, path: &Path) -> Result<T> {
+        self.map_err(|error| match error {
+            Error::Io(kind, context) if context.is_empty() => Error::Io(kind, format!("{} '{}'", action, path.display())),
+            error => error
+        })
     }
 }
 
@@ -65,7 +324,7 @@ impl From<std::io::Error> for Error {
 ///
 impl From<serde_json::Error> for Error {
     fn from(json_error: serde_json::Error) -> Self {
-        if json_error.is_syntax() || json_error.is_data() {
+        if json_error.is_syntax() || json_error.is_data() || json_error.is_eof() {
             Error::InvalidJSON(json_error.line(), json_error.column())
         } else {
             Error::Unknown
@@ -83,18 +342,87 @@ impl From<regex::Error> for Error {
 }
 
 ///
-/// Finish a `Result` computating, writing to stdout on error and doing nothing
-/// on success.
+/// Finish a `Result` computating, writing to stderr on error and doing nothing
+/// on success, or when nothing matched a quiet listing.
 /// 
 /// # Example
 /// 
-/// ```
+/// ```ignore
 /// finish(Err(Error::InvalidArguments));
 /// ```
 ///
 pub fn finish<T>(result: Result<T>) {
     match result {
-        Ok(_) => (),
-        Err(error) => println!("{}", error.print()),
+        Ok(_) | Err(Error::NoMatch) => (),
+        Err(error) => eprintln!("trash: error: {}!", error),
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_exit_with_their_category() {
+        let regex_error: regex::Error = regex::Error::Syntax(String::new());
+        let errors: Vec<(Error, i32)> = vec![
+            (Error::NoMatch, 1),
+            (Error::InvalidArguments, 2),
+            (Error::InvalidRegex(regex_error), 2),
+            (Error::AmbiguousOrigin, 2),
+            (Error::AmbiguousName(String::from("Bilbo.txt"), vec![]), 2),
+            (Error::InvalidUuid(String::from("Bilbo")), 2),
+            (Error::InvalidUnicode(String::from("to")), 2),
+            (Error::InvalidDuration(String::from("1x")), 2),
+            (Error::InvalidSetting(String::from("Bilbo")), 2),
+            (Error::InvalidSettingValue(String::from("quota"), String::from("Bilbo")), 2),
+            (Error::InvalidRange(String::from("3..1"), String::new()), 2),
+            (Error::EmptyPattern, 2),
+            (Error::InvalidCommand(String::new()), 2),
+            (Error::InvalidInnerPath(String::from("..")), 2),
+            (Error::MissingTarget(String::from("Bilbo.txt")), 3),
+            (Error::MissingTargetPredicate, 3),
+            (Error::MissingTargets(vec![]), 3),
+            (Error::NothingToUndo, 3),
+            (Error::Io(std::io::ErrorKind::Other, String::new()), 4),
+            (Error::PermissionDenied(String::from("Bilbo.txt")), 4),
+            (Error::InvalidDestination(String::from("Shire")), 4),
+            (Error::NotADirectory(String::from("Shire")), 4),
+            (Error::CopyMismatch(String::from("Bilbo.txt")), 4),
+            (Error::InvalidJSON(1, 1), 5),
+            (Error::InconsistentCache(vec![]), 5),
+            (Error::FailedCheck(1), 5),
+            (Error::InvalidConfiguration(String::from("Shire"), String::from("Shire")), 5),
+            (Error::InvalidRecord(String::from("log.json"), 1), 5),
+            (Error::NotATerminal, 6),
+            (Error::UnsafeDestination(String::from("Bilbo.txt")), 6),
+            (Error::TrashTarget(String::from("Shire")), 6),
+            (Error::UnnamedTarget(String::from("/"), String::from("/")), 6),
+            (Error::ExistingTrash(String::from("Shire")), 6),
+            (Error::ProtectedPath(String::from("Shire")), 6),
+            (Error::SpecialFile(String::from("Bilbo"), "socket"), 6),
+            (Error::UncopyableFile(String::from("Bilbo"), "socket"), 6),
+            (Error::UnconfirmedDirectory(String::from("Shire")), 6),
+            (Error::UnconfirmedMatches(2, String::from("*")), 6),
+            (Error::UnconfirmedEmpty(2), 6),
+            (Error::QuotaExceeded(String::from("Bilbo.txt"), 1), 6),
+            (Error::BatchFailed(1, 2), 7),
+            (Error::DeleteFailed(1, 2), 7),
+            (Error::RestoreFailed(1, 2), 7),
+            (Error::Unknown, 8)
+        ];
+
+        for (error, code) in errors {
+            assert_eq!(error.exit_code(), code, "exit code of {:?}", error);
+        }
+    }
+
+    #[test]
+    fn missing_and_inaccessible_targets_are_told_apart() {
+        let locating = |kind: std::io::ErrorKind| Error::locating("Bilbo.txt", std::io::Error::from(kind));
+
+        assert!(matches!(locating(std::io::ErrorKind::NotFound), Error::MissingTarget(target) if target == "Bilbo.txt"));
+        assert!(matches!(locating(std::io::ErrorKind::PermissionDenied), Error::PermissionDenied(target) if target == "Bilbo.txt"));
+        assert!(matches!(locating(std::io::ErrorKind::InvalidInput), Error::Io(std::io::ErrorKind::InvalidInput, _)));
+    }
+}
@@ -0,0 +1,221 @@
+use std::fs::{ read_dir, symlink_metadata };
+use std::path::{ Path, PathBuf, Component };
+use std::ffi::{ OsString };
+use std::borrow::{ Cow };
+use regex::{ Regex, escape };
+
+use super::error::{ Result };
+
+///
+/// Check whether `pattern` contains any of the glob metacharacters `*`, `?`
+/// and `[`.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let glob: bool = is_glob("*.log");
+/// ```
+///
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+///
+/// Find the paths matching the shell-style glob `pattern`, relative to the
+/// current directory unless it is absolute, in sorted order.
+/// Like a shell, wildcards only match names starting with a dot when the
+/// pattern's name does too, and never match across a `/`.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let paths: Vec<PathBuf> = expand("build/*.log")?;
+/// ```
+/// 
+/// # Errors
+/// 
+/// Fails if a directory can not be read.
+///
+pub fn expand(pattern: &str) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = vec![ PathBuf::new() ];
+
+    for component in Path::new(pattern).components() {
+        let mut next: Vec<PathBuf> = vec![];
+
+        match component {
+            Component::Normal(name) if is_glob(&name.to_string_lossy()) => {
+                let name: String = name.to_string_lossy().to_string();
+                let matcher: Regex = Regex::new(&translate(&name))?;
+
+                for path in paths {
+                    let directory: &Path = if path.as_os_str().is_empty() { Path::new(".") } else { &path };
+
+                    if !directory.is_dir() {
+                        continue;
+                    }
+
+                    //
+                    // Names which are not valid UTF-8 are matched by their
+                    // lossy form, but kept exactly.
+                    //
+                    for child in read_dir(directory)? {
+                        let child_name: OsString = child?.file_name();
+                        let lossy_name: Cow<str> = child_name.to_string_lossy();
+
+                        if matcher.is_match(&lossy_name) && (!lossy_name.starts_with('.') || name.starts_with('.')) {
+                            next.push(path.join(&child_name));
+                        }
+                    }
+                }
+            },
+            _ => {
+                for path in paths {
+                    let candidate: PathBuf = path.join(component.as_os_str());
+
+                    if symlink_metadata(&candidate).is_ok() {
+                        next.push(candidate);
+                    }
+                }
+            }
+        }
+
+        paths = next;
+    }
+
+    paths.sort();
+
+    Ok(paths)
+}
+
+///
+/// Translate a glob matching a single name into an anchored regular
+/// expression, where `*` matches any run of characters, `?` matches one
+/// character and `[...]` (or `[!...]`) matches one character from (or not
+/// from) a set.
+/// An unclosed `[` matches itself.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let expression: String = translate("*.log");
+/// ```
+///
+fn translate(glob: &str) -> String {
+    let characters: Vec<char> = glob.chars().collect();
+    let mut expression: String = String::from("(?s)^");
+    let mut index: usize = 0;
+
+    while index < characters.len() {
+        match characters[index] {
+            '*' => expression.push_str(".*"),
+            '?' => expression.push('.'),
+            '[' => {
+                //
+                // A `]` straight after the opening bracket, or it's negation,
+                // is part of the set rather than it's end.
+                //
+                let mut end: usize = index + 1;
+
+                if end < characters.len() && characters[end] == '!' {
+                    end += 1;
+                }
+
+                if end < characters.len() && characters[end] == ']' {
+                    end += 1;
+                }
+
+                while end < characters.len() && characters[end] != ']' {
+                    end += 1;
+                }
+
+                if end >= characters.len() {
+                    expression.push_str(&escape("["));
+                } else {
+                    let mut set: String = characters[index + 1..end].iter().collect();
+
+                    if set.starts_with('!') {
+                        set.replace_range(..1, "^");
+                    }
+
+                    expression.push('[');
+                    expression.push_str(&set.replace('\\', "\\\\").replace('[', "\\["));
+                    expression.push(']');
+                    index = end;
+                }
+            },
+            character => expression.push_str(&escape(&character.to_string()))
+        }
+
+        index += 1;
+    }
+
+    expression.push('$');
+
+    expression
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::testing::{ Scratch };
+    use std::fs::{ create_dir_all, write };
+
+    ///
+    /// Check whether the glob `pattern` matches the whole of `name`.
+    ///
+    fn matches(pattern: &str, name: &str) -> bool {
+        Regex::new(&translate(pattern)).unwrap().is_match(name)
+    }
+
+    #[test]
+    fn wildcards_match_runs_and_single_characters() {
+        assert!(matches("*.log", "build.log"));
+        assert!(!matches("*.log", "build.log.gz"));
+        assert!(matches("?.txt", "a.txt"));
+        assert!(!matches("?.txt", "ab.txt"));
+        assert!(matches("a*b", "a\nb"));
+    }
+
+    #[test]
+    fn sets_match_one_character() {
+        assert!(matches("[ab].txt", "a.txt"));
+        assert!(!matches("[ab].txt", "c.txt"));
+        assert!(matches("[!ab].txt", "c.txt"));
+        assert!(!matches("[!ab].txt", "a.txt"));
+        assert!(matches("[]a].txt", "].txt"));
+        assert!(matches("[]a].txt", "a.txt"));
+        assert!(!matches("[!]a].txt", "].txt"));
+        assert!(matches("[!]a].txt", "b.txt"));
+        assert!(matches("[a-c].txt", "b.txt"));
+        assert!(matches("[\\[].txt", "\\.txt"));
+        assert!(matches("[[].txt", "[.txt"));
+    }
+
+    #[test]
+    fn other_characters_match_themselves() {
+        assert!(matches("a+b(c).txt", "a+b(c).txt"));
+        assert!(matches("[unclosed", "[unclosed"));
+        assert!(!matches("[unclosed", "u"));
+        assert!(matches("$^.|", "$^.|"));
+    }
+
+    #[test]
+    fn expansion_skips_hidden_files_unless_named() {
+        let scratch: Scratch = Scratch::new();
+
+        for name in &[ "build.log", ".hidden.log", "logs/today.log", "logs/.old.log", "notes.txt" ] {
+            create_dir_all(scratch.join(name).parent().unwrap()).unwrap();
+            write(scratch.join(name), "").unwrap();
+        }
+
+        let expanded = |pattern: &str| expand(&scratch.join(pattern).to_string_lossy()).unwrap();
+
+        assert_eq!(expanded("*.log"), vec![ scratch.join("build.log") ]);
+        assert_eq!(expanded(".*.log"), vec![ scratch.join(".hidden.log") ]);
+        assert_eq!(expanded("*/*.log"), vec![ scratch.join("logs/today.log") ]);
+        assert_eq!(expanded("*"), vec![ scratch.join("build.log"), scratch.join("logs"), scratch.join("notes.txt") ]);
+        assert!(expanded("*.md").is_empty());
+        assert!(expanded("missing/*.log").is_empty());
+        assert!(expanded("notes.txt/*").is_empty());
+    }
+}
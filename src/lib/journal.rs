@@ -0,0 +1,212 @@
+use serde::{ Serialize, Deserialize };
+use serde_json::{ to_string, from_str };
+use std::io::{ ErrorKind, Write };
+use std::fs::{ OpenOptions, read_to_string };
+use std::path::{ Path, PathBuf };
+use chrono::{ DateTime, Utc };
+use uuid::{ Uuid };
+
+use super::error::{ Result, Error, Context };
+use super::cache::{ serialize_os_text, deserialize_os_text };
+
+///
+/// A change made by a run, with what is needed to reverse it.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let step: Step = Step::Restore { uuid, version: version.display(), destination: PathBuf::from("/home/Bilbo/Bilbo.txt") };
+/// ```
+///
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum Step {
+    /// A version of the item with the UUID was trashed from it's origin.
+    Delete {
+        uuid: Uuid,
+        version: String,
+        #[serde(serialize_with = "serialize_os_text", deserialize_with = "deserialize_os_text")]
+        origin: PathBuf
+    },
+    /// A version of the item with the UUID was restored to a destination.
+    Restore {
+        uuid: Uuid,
+        version: String,
+        #[serde(serialize_with = "serialize_os_text", deserialize_with = "deserialize_os_text")]
+        destination: PathBuf
+    }
+}
+
+///
+/// The changes made by a single run, in the order they were made.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let record: Record = Record::new(steps);
+/// ```
+///
+#[derive(Serialize, Deserialize)]
+pub struct Record {
+    /// The time at which the run finished.
+    at: DateTime<Utc>,
+    /// The changes made.
+    steps: Vec<Step>
+}
+
+impl Record {
+    ///
+    /// Create a record of `steps`, made now.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let record: Record = Record::new(vec![]);
+    /// ```
+    ///
+    pub fn new(steps: Vec<Step>) -> Record {
+        Record {
+            at: Utc::now(),
+            steps
+        }
+    }
+
+    ///
+    /// Get the time at which the run finished.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let at: &DateTime<Utc> = record.at();
+    /// ```
+    ///
+    pub fn at(&self) -> &DateTime<Utc> {
+        &self.at
+    }
+
+    ///
+    /// Get the changes made, in the order they were made.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let steps: &[Step] = record.steps();
+    /// ```
+    ///
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+///
+/// Append `record` to the log at `path`, which holds a record per line.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// append(Path::new("./log.json"), &Record::new(steps))?;
+/// ```
+/// 
+/// # Errors
+/// 
+/// Fails if the log can not be opened or written.
+///
+pub fn append(path: &Path, record: &Record) -> Result<()> {
+    let line: String = to_string(record)?;
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .context("writing", path)
+}
+
+///
+/// Read the last record of the log at `path`, if there is one.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let record: Option<Record> = last(Path::new("./log.json"))?;
+/// ```
+/// 
+/// # Errors
+/// 
+/// Fails if the log exists but can not be read, or it's last line is not a
+/// record, naming the line.
+///
+pub fn last(path: &Path) -> Result<Option<Record>> {
+    let contents: String = match read_to_string(path) {
+        Err(ref error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+        result => result.context("reading", path)?
+    };
+
+    match contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()).last() {
+        Some((index, line)) => Ok(Some(from_str(line).map_err(|_| Error::InvalidRecord(path.display().to_string(), index + 1))?)),
+        None => Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::testing::{ Scratch };
+    use std::fs::{ write };
+
+    ///
+    /// A record of restoring a version of Bilbo.txt.
+    ///
+    fn record() -> Record {
+        Record::new(vec![ Step::Restore {
+            uuid: Uuid::new_v4(),
+            version: String::from("2019-11-30 09:27:04.201347 UTC"),
+            destination: PathBuf::from("/home/Bilbo/Bilbo.txt")
+        } ])
+    }
+
+    #[test]
+    fn last_reads_nothing_from_a_missing_or_empty_log() {
+        let scratch: Scratch = Scratch::new();
+
+        assert!(last(&scratch.join("log.json")).unwrap().is_none());
+
+        write(scratch.join("log.json"), "").unwrap();
+        assert!(last(&scratch.join("log.json")).unwrap().is_none());
+
+        write(scratch.join("log.json"), "\n  \n").unwrap();
+        assert!(last(&scratch.join("log.json")).unwrap().is_none());
+    }
+
+    #[test]
+    fn last_reads_the_last_record() {
+        let scratch: Scratch = Scratch::new();
+        let log: PathBuf = scratch.join("log.json");
+
+        append(&log, &Record::new(vec![])).unwrap();
+        append(&log, &record()).unwrap();
+
+        let last: Record = last(&log).unwrap().unwrap();
+
+        assert_eq!(last.steps().len(), 1);
+        assert!(matches!(&last.steps()[0], Step::Restore { destination, .. } if destination == Path::new("/home/Bilbo/Bilbo.txt")));
+    }
+
+    #[test]
+    fn last_names_a_malformed_last_line() {
+        let scratch: Scratch = Scratch::new();
+        let log: PathBuf = scratch.join("log.json");
+
+        append(&log, &record()).unwrap();
+        append(&log, &record()).unwrap();
+        OpenOptions::new().append(true).open(&log).unwrap().write_all(b"{\"at\": \"2019-11-30T09:27:04Z\", \"steps\": [\n\n").unwrap();
+
+        match last(&log) {
+            Err(Error::InvalidRecord(path, line)) => {
+                assert_eq!(path, log.display().to_string());
+                assert_eq!(line, 3);
+            },
+            _ => panic!("the malformed line was read")
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use serde::{ Serialize, Deserialize };
+use chrono::{ DateTime, Utc };
+use uuid::{ Uuid };
+
+use super::cache::{ Entry, Version };
+
+///
+/// The policies which choose what to evict when the trash outgrows it's quota.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let policy: QuotaPolicy = QuotaPolicy::OldestVersionsFirst;
+/// ```
+///
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+#[allow(clippy::enum_variant_names)]
+pub enum QuotaPolicy {
+    /// Evict versions across all entries, oldest first.
+    #[default]
+    OldestFirst,
+    /// Evict whole entries, least recently trashed first.
+    OldestEntriesFirst,
+    /// Evict old versions across all entries before any entry's last version.
+    OldestVersionsFirst
+}
+
+///
+/// A version chosen for eviction, and the reason it was chosen.
+///
+pub struct Eviction {
+    /// The UUID of the version's entry.
+    pub uuid: Uuid,
+    /// The name of the version's entry.
+    pub name: String,
+    /// The display form of the version's timestamp.
+    pub version: String,
+    /// The recorded size, in bytes, of the version.
+    pub size: u64,
+    /// Why the version was chosen.
+    pub reason: &'static str
+}
+
+///
+/// The interface shared by the maintenance policies, which plan what to evict
+/// without evicting anything.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let plan: Vec<Eviction> = OldestVersionsFirst.plan(cache.entries(), 1024, None);
+/// ```
+///
+pub trait MaintenancePolicy {
+    ///
+    /// Choose the versions of `entries` to evict, in order, so that at least
+    /// `excess` bytes are freed, never choosing the newest version of the
+    /// entry with the UUID `protected`.
+    /// Versions without a recorded size free nothing, so are never chosen.
+    ///
+    fn plan(&self, entries: &[Entry], excess: u64, protected: Option<&Uuid>) -> Vec<Eviction>;
+}
+
+///
+/// Evict versions in chronological order across all entries.
+///
+pub struct OldestFirst;
+
+///
+/// Evict whole entries, starting with the one trashed least recently.
+///
+pub struct OldestEntriesFirst;
+
+///
+/// Evict versions in chronological order across all entries, keeping the
+/// last version of every entry until no entry has more than one left.
+///
+pub struct OldestVersionsFirst;
+
+impl QuotaPolicy {
+    ///
+    /// Get the implementation of the policy.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let plan: Vec<Eviction> = settings.quota_policy().planner().plan(cache.entries(), 1024, None);
+    /// ```
+    ///
+    pub fn planner(&self) -> Box<dyn MaintenancePolicy> {
+        match self {
+            QuotaPolicy::OldestFirst => Box::new(OldestFirst),
+            QuotaPolicy::OldestEntriesFirst => Box::new(OldestEntriesFirst),
+            QuotaPolicy::OldestVersionsFirst => Box::new(OldestVersionsFirst)
+        }
+    }
+}
+
+impl MaintenancePolicy for OldestFirst {
+    fn plan(&self, entries: &[Entry], excess: u64, protected: Option<&Uuid>) -> Vec<Eviction> {
+        let mut plan: Vec<Eviction> = vec![];
+        let mut freed: u64 = 0;
+        let mut versions: Vec<(&DateTime<Utc>, &Uuid, &Entry, &Version, u64)> = vec![];
+
+        for entry in entries {
+            let last: usize = entry.history().len().saturating_sub(1);
+
+            for (index, version) in entry.history().iter().enumerate() {
+                if index_is_protected(entry, index, last, protected) {
+                    continue;
+                }
+
+                if let Some(size) = version.metadata().size() {
+                    versions.push((version.timestamp(), entry.uuid(), entry, version, size));
+                }
+            }
+        }
+
+        //
+        // Ties are broken by UUID so that the plan is always the same.
+        //
+        versions.sort_by_key(|(timestamp, uuid, _, _, _)| (*timestamp, *uuid));
+
+        for (_, uuid, entry, version, size) in versions {
+            if freed >= excess {
+                break;
+            }
+
+            freed += size;
+            plan.push(Eviction {
+                uuid: *uuid,
+                name: entry.key().name().to_string_lossy().to_string(),
+                version: version.display(),
+                size,
+                reason: "it is the oldest version in the trash"
+            });
+        }
+
+        plan
+    }
+}
+
+impl MaintenancePolicy for OldestEntriesFirst {
+    fn plan(&self, entries: &[Entry], excess: u64, protected: Option<&Uuid>) -> Vec<Eviction> {
+        let mut plan: Vec<Eviction> = vec![];
+        let mut freed: u64 = 0;
+        let mut order: Vec<&Entry> = entries.iter().collect();
+
+        //
+        // An entry was last trashed at the time of it's newest version, and
+        // ties are broken by UUID so that the plan is always the same.
+        //
+        order.sort_by_key(|entry| (entry.history().last().map(|version| *version.timestamp()), *entry.uuid()));
+
+        for entry in order {
+            if freed >= excess {
+                break;
+            }
+
+            let last: usize = entry.history().len().saturating_sub(1);
+
+            for (index, version) in entry.history().iter().enumerate() {
+                if index_is_protected(entry, index, last, protected) {
+                    continue;
+                }
+
+                if let Some(size) = version.metadata().size() {
+                    freed += size;
+                    plan.push(Eviction {
+                        uuid: *entry.uuid(),
+                        name: entry.key().name().to_string_lossy().to_string(),
+                        version: version.display(),
+                        size,
+                        reason: "it belongs to the least recently trashed item"
+                    });
+                }
+            }
+        }
+
+        plan
+    }
+}
+
+impl MaintenancePolicy for OldestVersionsFirst {
+    fn plan(&self, entries: &[Entry], excess: u64, protected: Option<&Uuid>) -> Vec<Eviction> {
+        let mut plan: Vec<Eviction> = vec![];
+        let mut freed: u64 = 0;
+        let mut remaining: Vec<usize> = entries.iter().map(|entry| entry.history().len()).collect();
+        let mut versions: Vec<(DateTime<Utc>, Uuid, usize, usize, u64)> = vec![];
+
+        for (entry_index, entry) in entries.iter().enumerate() {
+            let last: usize = entry.history().len().saturating_sub(1);
+
+            for (version_index, version) in entry.history().iter().enumerate() {
+                if index_is_protected(entry, version_index, last, protected) {
+                    continue;
+                }
+
+                if let Some(size) = version.metadata().size() {
+                    versions.push((*version.timestamp(), *entry.uuid(), entry_index, version_index, size));
+                }
+            }
+        }
+
+        versions.sort();
+
+        //
+        // The first pass only takes versions which leave another behind, and
+        // the second takes what is left, which are the last versions of their
+        // entries, so whole entries are only evicted when the first pass could
+        // not free enough.
+        //
+        let mut evicted: Vec<bool> = vec![false; versions.len()];
+
+        for (reason, sole) in &[ ("newer versions of the item remain", false), ("no item had an older version left to evict", true) ] {
+            for (index, (_, uuid, entry_index, version_index, size)) in versions.iter().enumerate() {
+                if freed >= excess {
+                    return plan;
+                }
+
+                if evicted[index] || (!*sole && remaining[*entry_index] <= 1) {
+                    continue;
+                }
+
+                let entry: &Entry = &entries[*entry_index];
+
+                evicted[index] = true;
+                remaining[*entry_index] -= 1;
+                freed += size;
+                plan.push(Eviction {
+                    uuid: *uuid,
+                    name: entry.key().name().to_string_lossy().to_string(),
+                    version: entry.history()[*version_index].display(),
+                    size: *size,
+                    reason
+                });
+            }
+        }
+
+        plan
+    }
+}
+
+///
+/// Check whether the version at `index` of `entry`, whose last index is
+/// `last`, is the newest version of the protected entry.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let skip: bool = index_is_protected(&entry, 0, 0, Some(&uuid));
+/// ```
+///
+fn index_is_protected(entry: &Entry, index: usize, last: usize, protected: Option<&Uuid>) -> bool {
+    index == last && protected == Some(entry.uuid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::cache::{ Key, Metadata, Version };
+    use chrono::{ TimeZone };
+    use std::ffi::{ OsString };
+    use std::path::{ PathBuf };
+
+    ///
+    /// Make an entry named `name` whose versions were trashed the given
+    /// number of seconds after the epoch, with the given recorded sizes.
+    ///
+    fn entry(name: &str, versions: &[(i64, Option<u64>)]) -> Entry {
+        let history: Vec<Version> = versions.iter()
+            .map(|(seconds, size)| {
+                let mut metadata: Metadata = Metadata::default();
+
+                metadata.set_size(*size);
+
+                Version::new(Utc.timestamp(*seconds, 0), metadata)
+            })
+            .collect();
+
+        Entry::new(Key::new(OsString::from(name), PathBuf::from("/home/Bilbo").join(name)), Uuid::new_v4(), history)
+    }
+
+    ///
+    /// Describe a plan as the name of each version's entry and the second it
+    /// was trashed at.
+    ///
+    fn describe(plan: &[Eviction], entries: &[Entry]) -> Vec<(String, i64)> {
+        plan.iter()
+            .map(|eviction| {
+                let entry: &Entry = entries.iter().find(|entry| entry.uuid() == &eviction.uuid).unwrap();
+                let version: &Version = entry.history().iter().find(|version| version.display() == eviction.version).unwrap();
+
+                (eviction.name.clone(), version.timestamp().timestamp())
+            })
+            .collect()
+    }
+
+    ///
+    /// Two entries with two versions each, trashed in turn, with Frodo's
+    /// newest version trashed before Bilbo's.
+    ///
+    fn entries() -> Vec<Entry> {
+        vec![
+            entry("Bilbo.txt", &[ (1, Some(10)), (4, Some(10)) ]),
+            entry("Frodo.txt", &[ (2, Some(10)), (3, Some(10)) ])
+        ]
+    }
+
+    ///
+    /// Write out a described plan, as versions named by their entry's name
+    /// and the second they were trashed at.
+    ///
+    fn expected(versions: &[(&str, i64)]) -> Vec<(String, i64)> {
+        versions.iter().map(|(name, second)| (name.to_string(), *second)).collect()
+    }
+
+    #[test]
+    fn oldest_first_evicts_in_chronological_order() {
+        let entries: Vec<Entry> = entries();
+
+        assert_eq!(describe(&OldestFirst.plan(&entries, 25, None), &entries), expected(&[ ("Bilbo.txt", 1), ("Frodo.txt", 2), ("Frodo.txt", 3) ]));
+        assert_eq!(describe(&OldestFirst.plan(&entries, 40, None), &entries), expected(&[ ("Bilbo.txt", 1), ("Frodo.txt", 2), ("Frodo.txt", 3), ("Bilbo.txt", 4) ]));
+        assert!(OldestFirst.plan(&entries, 0, None).is_empty());
+    }
+
+    #[test]
+    fn oldest_entries_first_evicts_whole_entries() {
+        let entries: Vec<Entry> = entries();
+
+        assert_eq!(describe(&OldestEntriesFirst.plan(&entries, 15, None), &entries), expected(&[ ("Frodo.txt", 2), ("Frodo.txt", 3) ]));
+        assert_eq!(describe(&OldestEntriesFirst.plan(&entries, 25, None), &entries), expected(&[ ("Frodo.txt", 2), ("Frodo.txt", 3), ("Bilbo.txt", 1), ("Bilbo.txt", 4) ]));
+    }
+
+    #[test]
+    fn oldest_versions_first_keeps_last_versions_until_it_must_not() {
+        let entries: Vec<Entry> = entries();
+
+        assert_eq!(describe(&OldestVersionsFirst.plan(&entries, 20, None), &entries), expected(&[ ("Bilbo.txt", 1), ("Frodo.txt", 2) ]));
+        assert_eq!(describe(&OldestVersionsFirst.plan(&entries, 25, None), &entries), expected(&[ ("Bilbo.txt", 1), ("Frodo.txt", 2), ("Frodo.txt", 3) ]));
+        assert_eq!(OldestVersionsFirst.plan(&entries, 25, None)[2].reason, "no item had an older version left to evict");
+    }
+
+    #[test]
+    fn oldest_versions_first_evicts_a_sole_version_last() {
+        let entries: Vec<Entry> = vec![
+            entry("Sam.txt", &[ (0, Some(10)) ]),
+            entry("Bilbo.txt", &[ (1, Some(10)), (4, Some(10)) ])
+        ];
+
+        assert_eq!(describe(&OldestVersionsFirst.plan(&entries, 10, None), &entries), expected(&[ ("Bilbo.txt", 1) ]));
+        assert_eq!(describe(&OldestVersionsFirst.plan(&entries, 30, None), &entries), expected(&[ ("Bilbo.txt", 1), ("Sam.txt", 0), ("Bilbo.txt", 4) ]));
+        assert_eq!(describe(&OldestFirst.plan(&entries, 10, None), &entries), expected(&[ ("Sam.txt", 0) ]));
+    }
+
+    #[test]
+    fn versions_without_sizes_are_never_chosen() {
+        let entries: Vec<Entry> = vec![
+            entry("Bilbo.txt", &[ (1, None), (4, Some(10)) ]),
+            entry("Frodo.txt", &[ (2, None), (3, None) ])
+        ];
+
+        for policy in &[ QuotaPolicy::OldestFirst, QuotaPolicy::OldestEntriesFirst, QuotaPolicy::OldestVersionsFirst ] {
+            assert_eq!(describe(&policy.planner().plan(&entries, 100, None), &entries), expected(&[ ("Bilbo.txt", 4) ]));
+        }
+    }
+
+    #[test]
+    fn the_newest_version_of_the_protected_entry_is_never_chosen() {
+        let entries: Vec<Entry> = entries();
+        let protected: Uuid = *entries[1].uuid();
+
+        for policy in &[ QuotaPolicy::OldestFirst, QuotaPolicy::OldestEntriesFirst, QuotaPolicy::OldestVersionsFirst ] {
+            let plan: Vec<(String, i64)> = describe(&policy.planner().plan(&entries, 100, Some(&protected)), &entries);
+
+            assert_eq!(plan.len(), 3);
+            assert!(!plan.contains(&("Frodo.txt".to_string(), 3)));
+        }
+    }
+}
@@ -7,28 +7,92 @@ extern crate failure;
 extern crate chrono;
 extern crate console;
 
+pub mod api;
+
+//
+// The modules behind the interface are public, so that the benchmarks can
+// reach them, but hidden, as they may change in any release.
+//
+#[doc(hidden)]
 pub mod cache;
+#[doc(hidden)]
+pub mod dedup;
+#[doc(hidden)]
 pub mod error;
+#[doc(hidden)]
+pub mod glob;
+#[doc(hidden)]
+pub mod journal;
+#[doc(hidden)]
+pub mod maintenance;
+#[doc(hidden)]
+pub mod numbers;
+#[doc(hidden)]
+pub mod paths;
+#[doc(hidden)]
+pub mod permissions;
+#[doc(hidden)]
 pub mod settings;
+#[doc(hidden)]
+pub mod shred;
+#[doc(hidden)]
+pub mod transfer;
+#[doc(hidden)]
+pub mod usage;
+#[doc(hidden)]
+pub mod walk;
+
+#[cfg(test)]
+mod testing;
 
-use std::fs::{ rename, create_dir, canonicalize, remove_dir_all };
-use std::path::{ PathBuf };
+use std::fs::{ read_dir, read_to_string, create_dir, create_dir_all, canonicalize, remove_dir, remove_dir_all, metadata, symlink_metadata, read_link };
+use std::path::{ Component, Path, PathBuf };
+use std::io::{ BufRead, ErrorKind, Read, stdin };
+use std::sync::mpsc::{ channel, Sender, Receiver, RecvTimeoutError };
+use std::thread::{ spawn };
+use std::time::{ Duration, SystemTime };
+use std::convert::{ TryFrom };
+use std::borrow::{ Cow };
+use std::env::{ var_os };
+use std::process::{ exit };
+use std::ffi::{ OsStr, OsString };
+use std::cmp::{ Reverse };
 use dirs::{ home_dir };
-use clap::{ App, AppSettings, ArgMatches, Arg };
+use clap::{ App, AppSettings, ArgMatches, Arg, ArgGroup, Shell };
 use regex::{ Regex };
 use console::{ Term, Style, StyledObject };
 use uuid::{ Uuid };
+use serde::{ Serialize };
+use serde_json::{ to_string };
+use chrono::{ DateTime, Utc };
 
-use cache::{ Cache, VersionPredicate };
-use error::{ Result, Error };
+use cache::{ Cache, Entry, Event, Header, Issue, Kind, Metadata, TypeFilter, Version, VersionPredicate, is_valid_format };
+use dedup::{ Manifest, manifest, link_unchanged, link_identical, separate, files };
+use error::{ Result, Error, Context, finish };
+use glob::{ is_glob, expand };
+use journal::{ Record, Step, append, last };
+use maintenance::{ Eviction };
+use numbers::{ Formatter };
+use paths::{ Paths };
+use permissions::{ executables, strip_exec, mode, set_mode, set_modified };
 use settings::{ Settings };
+use transfer::{ copy_path, move_path, remove_path, resolve, same_file, same_device, linked_ancestor };
+use usage::{ size, measure };
+use walk::{ walk };
+use shred::{ shred };
+
+///
+/// The number of paths matching a pattern which may be deleted without
+/// confirmation.
+///
+const CONFIRM_MATCHES: usize = 10;
 
 ///
 /// The application and all of it's resources.
 /// 
 /// # Example
 /// 
-/// ```
+/// ```ignore
 /// let app: TMan = TMan::new()?;
 /// app.main()?;
 /// ```
@@ -40,47 +104,295 @@ pub struct TMan {
     stdout: Term,
     /// Settings.
     settings: Settings,
-    /// Location of file store.
-    data_path: PathBuf
+    /// Locations of the cache, settings and file store.
+    paths: Paths,
+    /// Symbolic links to other filesystems, which have been warned about.
+    warned_links: Vec<PathBuf>,
+    /// Print only errors and requested data, such as listings.
+    quiet: bool,
+    /// Print a record of each trashed path, and nothing else, on stdout.
+    porcelain: bool,
+    /// Whether to style output, as forced with `--color`, or `None` to decide
+    /// from the environment, settings and console.
+    colors: Option<bool>,
+    /// The transaction recorded with every version this run trashes.
+    transaction: Uuid,
+    /// The deletes and restores made by this run, for `--undo`.
+    journal: Vec<Step>
+}
+
+///
+/// The options controlling how items are deleted.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let options: DeleteOptions = DeleteOptions {
+///     verbose: true,
+///     allow_protected: false,
+///     keep_going: true,
+///     yes: false,
+///     reason: Some("replaced by Bilbo.md"),
+///     tags: vec![ "drafts".to_string() ],
+///     copy: false,
+///     allow_special: false,
+///     modified_before: None,
+///     no_evict: false
+/// };
+/// ```
+///
+#[derive(Default, Clone)]
+pub struct DeleteOptions<'a> {
+    /// Explain why each version evicted to fit the quota was chosen.
+    pub verbose: bool,
+    /// Delete protected paths, such as the home directory.
+    pub allow_protected: bool,
+    /// Attempt every target, rather than stopping at the first failure, and
+    /// summarise the results.
+    pub keep_going: bool,
+    /// Trash directories without asking for confirmation.
+    pub yes: bool,
+    /// Why the targets are trashed, which is recorded with their versions.
+    pub reason: Option<&'a str>,
+    /// The tags given to the targets' items.
+    pub tags: Vec<String>,
+    /// Store copies of the targets, leaving them in place.
+    pub copy: bool,
+    /// Delete special files, such as sockets and named pipes.
+    pub allow_special: bool,
+    /// Skip targets modified at or after this time.
+    pub modified_before: Option<DateTime<Utc>>,
+    /// Refuse targets which would grow the trash past it's quota, rather
+    /// than evicting old versions to fit them.
+    pub no_evict: bool
+}
+
+///
+/// The options controlling which items are restored and where to.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let options: RestoreOptions = RestoreOptions {
+///     version: Some("all"),
+///     ..RestoreOptions::default()
+/// };
+/// ```
+///
+#[derive(Default, Clone)]
+pub struct RestoreOptions<'a> {
+    /// The origin of the items.
+    pub origin: Option<&'a str>,
+    /// Restore the items of every origin when no origin is given.
+    pub all_origins: bool,
+    /// Restore only the newest version among the items of every origin,
+    /// whatever `version` and `transaction` are.
+    pub newest_only: bool,
+    /// The version of the items.
+    pub version: Option<&'a str>,
+    /// The directory to restore the items into.
+    pub destination: Option<&'a str>,
+    /// Confirm each item before restoring it.
+    pub interactive: bool,
+    /// Clear the execute bits of the restored files.
+    pub strip_exec: bool,
+    /// What to do when something already exists where an item is restored.
+    pub on_conflict: OnConflict,
+    /// Restore the versions trashed in this transaction, rather than those
+    /// chosen by `version`.
+    pub transaction: Option<Uuid>
+}
+
+impl<'a> RestoreOptions<'a> {
+    ///
+    /// Get the predicate choosing which versions of the items to restore.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let predicate: VersionPredicate = options.version_predicate()?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails if `version` is malformed.
+    ///
+    fn version_predicate(&self) -> Result<VersionPredicate<'a>> {
+        match self.transaction {
+            Some(transaction) => Ok(VersionPredicate::Transaction(transaction)),
+            None => VersionPredicate::try_from(self.version)
+        }
+    }
+}
+
+///
+/// The ways of restoring an item onto a path which already exists.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let on_conflict: OnConflict = OnConflict::Skip;
+/// ```
+///
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum OnConflict {
+    /// Leave the existing path and the item's version in the trash.
+    Skip,
+    /// Replace the existing path.
+    Overwrite,
+    /// Restore beside the existing path, with a numeric suffix.
+    #[default]
+    Rename
+}
+
+///
+/// The orders in which items can be listed.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let sort: SortKey = SortKey::Date;
+/// ```
+///
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortKey {
+    /// By name.
+    Name,
+    /// By original location.
+    Origin,
+    /// By the time the newest version was trashed.
+    Date,
+    /// By the size of all versions on disk, largest first.
+    Size
+}
+
+///
+/// The options controlling how items are listed.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let options: ListOptions = ListOptions {
+///     simple: true,
+///     ..ListOptions::default()
+/// };
+/// ```
+///
+#[derive(Default)]
+pub struct ListOptions {
+    /// Only list the names of items.
+    pub simple: bool,
+    /// End the names listed by `simple` with NUL characters rather than
+    /// newlines.
+    pub null: bool,
+    /// Match the display names of items as well as their names.
+    pub match_display: bool,
+    /// List the UUIDs of items.
+    pub verbose: bool,
+    /// Re-list the items whenever they change.
+    pub watch: bool,
+    /// Only list items with symbolic links to missing targets.
+    pub missing: bool,
+    /// Number the versions of items, newest first.
+    pub indices: bool,
+    /// Only list items of a type.
+    pub types: TypeFilter,
+    /// List the items as JSON.
+    pub json: bool,
+    /// List nothing, only failing when no items are listed.
+    pub quiet_match: bool,
+    /// Match the reasons items were trashed for as well as their names.
+    pub search_reasons: bool,
+    /// Only list items with every one of these tags.
+    pub tags: Vec<String>,
+    /// The order to list items in, rather than the order they were first
+    /// trashed in.
+    pub sort: Option<SortKey>,
+    /// List items in the opposite order.
+    pub reverse: bool,
+    /// Show the size of each item on disk, and the total.
+    pub size: bool,
+    /// Show sizes as numbers of bytes.
+    pub bytes: bool
+}
+
+///
+/// The representation of an entry written by `--list --json`.
+///
+#[derive(Serialize)]
+struct JsonEntry<'a> {
+    name: Cow<'a, str>,
+    origin: Cow<'a, str>,
+    uuid: &'a Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_name: Option<&'a String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: &'a Vec<String>,
+    history: &'a Vec<Version>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    events: Option<&'a Vec<Event>>
+}
+
+///
+/// The line written by `--delete --porcelain` for each trashed path, whose
+/// fields are stable.
+///
+#[derive(Serialize)]
+struct PorcelainRecord<'a> {
+    origin: Cow<'a, str>,
+    uuid: &'a Uuid,
+    version: &'a str
 }
 
 impl TMan {
     ///
     /// Create a new application, loading it's settings and cache whilst
     /// creating all missing directories.
+    /// The trash is kept in `TMAN_HOME`, or `~/.tman` when it is unset.
     /// 
     /// # Example
     /// 
-    /// ```
+    /// ```ignore
     /// let app: TMan = TMan::new();
     /// ```
     /// 
     /// # Errors
     /// 
-    /// Fails on failed initialisation of cache and on failed initialisation
-    /// of settings.
+    /// Fails on failed initialisation of cache, on failed initialisation
+    /// of settings, when the trash was moved somewhere which no longer
+    /// exists and when the locations of either lie inside one another.
     ///
     pub fn new() -> Result<TMan> {
-        let mut directory: PathBuf = home_dir().unwrap_or_default();
+        let directory: PathBuf = home_directory();
 
-        directory.push(".tman");
+        create_dir_all(&directory).unwrap_or_default();
 
-        let mut cache_path: PathBuf = directory.clone();
-        let mut settings_path: PathBuf = directory.clone();
-        let mut data_path: PathBuf = directory.clone();
+        //
+        // The locations are resolved so that they can be compared with the
+        // resolved targets of deletes.
+        // A trash moved with `--move-trash` leaves only it's location in the
+        // settings of the home directory.
+        //
+        let home: PathBuf = resolve(&directory)?;
+        let paths: Paths = match Settings::new(&directory.join("settings.json"))?.location() {
+            Some(location) => Paths::new(canonicalize(location).context("locating the trash at", location)?),
+            None => Paths::new(home.clone())
+        };
 
-        cache_path.push("cache.json");
-        settings_path.push("settings.json");
-        data_path.push("data");
-
-        create_dir(&directory).unwrap_or_default();
-        create_dir(&data_path).unwrap_or_default();
+        paths.check(&home)?;
+        create_dir(&paths.data).unwrap_or_default();
 
         Ok(TMan {
-            cache: Cache::new(&cache_path)?,
+            cache: Cache::new(&paths.cache)?,
             stdout: Term::stdout(),
-            settings: Settings::new(&settings_path)?,
-            data_path: data_path
+            settings: Settings::new(&paths.settings)?,
+            paths,
+            warned_links: vec![],
+            quiet: false,
+            porcelain: false,
+            colors: None,
+            transaction: Uuid::new_v4(),
+            journal: vec![]
         })
     }
 
@@ -89,299 +401,4029 @@ impl TMan {
     /// 
     /// # Example
     /// 
-    /// ```
+    /// ```ignore
     /// app.main()?;
     /// ```
     /// 
     pub fn main(&mut self) -> Result<()> {
-        let max_argument_values: u64 = std::u64::MAX;
-
-        let matches: ArgMatches<'static> = App::new("TMan")
-            .name("tman")
-            .version("1.0.0")
-            .author("Kove Salter <kove.w.o.salter@gmail.com>")
-            .about("Safely manage your trash")
-            .setting(AppSettings::ArgRequiredElseHelp)
-            .help(
-r#"USAGE:
-    tman <ACTION>
+        //
+        // Usage errors exit like `Error::InvalidArguments`, rather than with
+        // clap's own status of 1, which means that nothing matched.
+        //
+        let matches: ArgMatches<'static> = app().get_matches_safe().unwrap_or_else(|error| match error.kind {
+            clap::ErrorKind::HelpDisplayed | clap::ErrorKind::VersionDisplayed => error.exit(),
+            _ => {
+                eprintln!("{}", error.message);
+                exit(Error::InvalidArguments.exit_code())
+            }
+        });
 
-ACTIONS:
-    --delete             -D    <FILE_1>...    Delete specified files
-    --restore            -R    <FILE>         Restore specified file
-        --origin         -o    <PATH>         Set the origin
-        --version        -v                   Set the revision
-            <VERSION>                         Use a specific version
-            latest                            Use the newest version (default)
-            all                               Use all versions
-    --list               -L                   List items in the trash
-        --pattern        -p    <REGEX>        Set the search pattern
-        --simple         -p                   Set the simple mode
-    --empty              -E                   Permenantly delete trash content"#
-            )
-            .arg(Arg::with_name("delete")
-                .long("delete")
-                .short("D")
-                .help("Delete an item, storing it in the trash")
-                .takes_value(true)
-                .value_name("FILES")
-                .max_values(max_argument_values)
-                .conflicts_with_all(&[ "restore", "origin", "version", "list", "pattern", "simple", "empty" ]))
-            .arg(Arg::with_name("restore")
-                .long("restore")
-                .short("R")
-                .help("Restore files from the trash")
-                .takes_value(true)
-                .value_name("FILES")
-                .max_values(max_argument_values)
-                .conflicts_with_all(&[ "delete", "list", "pattern", "simple", "empty" ]))
-            .arg(Arg::with_name("origin")
-                .long("origin")
-                .short("o")
-                .help("Set the origin for restore")
-                .takes_value(true)
-                .value_name("PATH")
-                .requires("restore")
-                .conflicts_with_all(&[ "delete", "list", "pattern", "simple", "empty" ]))
-            .arg(Arg::with_name("version")
-                .long("version")
-                .short("v")
-                .help("Set the version for restore")
-                .takes_value(true)
-                .value_name("VERSION")
-                .requires("restore")
-                .conflicts_with_all(&[ "delete", "list", "pattern", "simple", "empty" ]))
-            .arg(Arg::with_name("list")
-                .long("list")
-                .short("L")
-                .help("List items in the trash")
-                .conflicts_with_all(&[ "delete", "restore", "origin", "version", "empty" ]))
-            .arg(Arg::with_name("pattern")
-                .long("pattern")
-                .short("p")
-                .help("Set a pattern for --list")
-                .takes_value(true)
-                .value_name("PATTERN")
-                .requires("list")
-                .conflicts_with_all(&[ "delete", "restore", "origin", "version", "empty" ]))
-            .arg(Arg::with_name("simple")
-                .long("simple")
-                .short("s")
-                .help("Use simple list format for --list")
-                .requires("list")
-                .conflicts_with_all(&[ "delete", "restore", "origin", "version", "empty" ]))
-            .arg(Arg::with_name("empty")
-                .long("empty")
-                .short("E")
-                .help("Permenantly delete all trash items")
-                .takes_value(false)
-                .conflicts_with_all(&[ "delete", "restore", "origin", "version", "list", "pattern", "simple" ]))
-            .get_matches();
-
-        if let Some(mut files) = matches.values_of("delete") {
-            files.try_for_each(|file| self.delete(String::from(file)))?;
-        } else if let Some(file) = matches.value_of("restore") {
-            self.restore(file, matches.value_of("origin"), matches.value_of("version"))?;
-        } else if matches.is_present("list") {
-            self.list(Regex::new(matches.value_of("pattern").unwrap_or(""))?, matches.is_present("simple"))?;
-        } else if matches.is_present("empty") {
-            self.empty()?;
+        self.quiet = matches.is_present("quiet");
+        self.porcelain = matches.is_present("porcelain");
+        self.colors = match matches.value_of("color") {
+            Some("always") => Some(true),
+            Some("never") => Some(false),
+            _ => None
+        };
+
+        //
+        // Old versions are purged before the action, so that it sees the
+        // trash as it is left, but never by a dry run or a check, which change
+        // nothing.
+        //
+        let retained: Result<()> = if matches.is_present("dry-run") || matches.is_present("check") {
+            Ok(())
         } else {
-            Err(Error::InvalidArguments)?;
-        }
+            self.retain()
+        };
+        let result: Result<()> = retained.and_then(|()| self.run(&matches));
 
-        self.cache.end()?;
+        //
+        // The cache is committed even when the action fails, so that the
+        // targets which were processed before the failure are not orphaned,
+        // unless nothing was done: a watched listing outlives the cache it
+        // loaded, and committing it would undo any changes made whilst
+        // watching, while a dry run, a check or a batch in which every command
+        // failed must leave the cache as it was.
+        //
+        let commit: bool = match result {
+            Err(Error::BatchFailed(failed, total)) => failed < total,
+            _ => true
+        };
 
-        Ok(())
+        if commit && !matches.is_present("watch") && !matches.is_present("dry-run") && !matches.is_present("check") {
+            self.commit()?;
+        }
+
+        result
     }
 
     ///
-    /// Move a target file to the trash.
+    /// Write the changes made to the trash, and log the actions which made
+    /// them, as `main` does once it's action is done.
+    /// Until then, the stored files and the cache disagree.
     /// 
     /// # Example
     /// 
+    /// ```ignore
+    /// app.delete(Path::new("./Bilbo.txt"), &DeleteOptions::default())?;
+    /// app.commit()?;
     /// ```
-    /// app.delete(String::from("./Bilbo.txt"))?;
-    /// ```
     /// 
-    pub fn delete(&mut self, target: String) -> Result<()> {
-        let origin: PathBuf = canonicalize(&target)?;
-        let name: String = origin.file_name().unwrap().to_str().unwrap().to_string();
-        let mut destination: PathBuf = self.data_path.clone();
-        let (uuid, version): (Uuid, String) = self.cache.push(name, origin.to_str().unwrap().to_string());
+    /// # Errors
+    /// 
+    /// Fails when the cache or the log can not be written.
+    ///
+    pub fn commit(&mut self) -> Result<()> {
+        self.cache.end()?;
 
-        destination.push(uuid.to_string());
-        create_dir(&destination).unwrap_or_default();
-        destination.push(&version);
-        rename(origin, destination)?;
+        if !self.journal.is_empty() {
+            append(&self.paths.log, &Record::new(self.journal.drain(..).collect()))?;
+        }
 
         Ok(())
     }
 
     ///
-    /// Restore a target files version to it's original location.
+    /// Perform the action given by parsed command line arguments, without
+    /// committing the cache.
     /// 
     /// # Example
     /// 
-    /// ```
-    /// app.restore(String::from("Bilbo.txt"), None, None);
+    /// ```ignore
+    /// app.run(&app().get_matches())?;
     /// ```
     ///
-    pub fn restore(&mut self, target_name: &str, target_origin: Option<&str>, target_version: Option<&str>) -> Result<()> {
-        let mut location: PathBuf = PathBuf::default();
-        #[allow(unused_assignments)]
-        let mut destination: PathBuf = PathBuf::default();
-        let entries = self.cache.pop(
-            |key| {
-                if let Some(target_origin) = target_origin {
-                    key.name() == &target_name && key.origin() == &target_origin
-                } else {
-                    key.name() == &target_name
-                }
+    fn run(&mut self, matches: &ArgMatches) -> Result<()> {
+        //
+        // Files to delete, the directory to delete under and the directory to
+        // move the trash to are read exactly and files to restore are matched
+        // by their lossy names, but any other argument must be valid UTF-8.
+        //
+        for name in &[ "origin", "version", "to", "on-conflict", "match", "inner", "uuid", "pattern", "set-display-name", "show", "purge", "older-than", "batch", "files-from", "reason", "max-depth", "if-older-than", "transaction", "config", "color", "empty", "keep-latest", "sort" ] {
+            if matches.values_of_os(name).is_some_and(|mut values| values.any(|value| value.to_str().is_none())) {
+                Err(Error::InvalidUnicode(name.to_string()))?;
+            }
+        }
+
+        let restore_options: RestoreOptions = RestoreOptions {
+            origin: matches.value_of("origin"),
+            all_origins: matches.is_present("all-origins"),
+            newest_only: matches.is_present("newest-only"),
+            version: matches.value_of("version"),
+            destination: matches.value_of("to"),
+            interactive: matches.is_present("interactive"),
+            strip_exec: matches.is_present("no-exec") || self.settings.restore_strip_exec(),
+            on_conflict: match matches.value_of("on-conflict") {
+                Some("skip") => OnConflict::Skip,
+                Some("overwrite") => OnConflict::Overwrite,
+                _ => OnConflict::Rename
             },
-            match target_version {
-                Some("all") => VersionPredicate::All,
-                Some("latest") | None => VersionPredicate::Latest,
-                Some(target_version) => VersionPredicate::Specific(&target_version)
+            transaction: None
+        };
+        let strict: bool = matches.is_present("strict-types");
+        let types: TypeFilter = if matches.is_present("only-dirs") {
+            TypeFilter::Directories { strict }
+        } else if matches.is_present("only-files") {
+            TypeFilter::Files { strict }
+        } else {
+            TypeFilter::All
+        };
+        let tags: Vec<String> = matches.values_of_lossy("tag").unwrap_or_default();
+        let shred: bool = matches.is_present("shred");
+
+        //
+        // Overwriting a file in place can not reach copies of it's data which
+        // the filesystem or the drive keeps elsewhere.
+        //
+        if shred {
+            self.inform("warning: shredding overwrites files in place, which SSDs and journaling or copy-on-write filesystems may not honour, so their contents may still be recoverable")?;
+        }
+
+        if let Some(files) = matches.values_of_os("delete") {
+            let delete_options: DeleteOptions = DeleteOptions {
+                verbose: matches.is_present("verbose"),
+                allow_protected: matches.is_present("allow-protected"),
+                keep_going: matches.is_present("keep-going"),
+                yes: matches.is_present("yes"),
+                reason: matches.value_of("reason"),
+                tags: tags.clone(),
+                copy: matches.is_present("copy"),
+                allow_special: matches.is_present("allow-special"),
+                modified_before: match matches.value_of("if-older-than") {
+                    Some(duration) => Some(Utc::now()
+                        .checked_sub_signed(parse_duration(duration)?)
+                        .ok_or_else(|| Error::InvalidDuration(duration.to_string()))?),
+                    None => None
+                },
+                no_evict: matches.is_present("no-evict")
+            };
+
+            let max_depth: Option<usize> = matches.value_of("max-depth")
+                .map(|depth| depth.parse::<usize>().map_err(|_| Error::InvalidArguments))
+                .transpose()?;
+
+            match (matches.value_of("files-from"), matches.value_of("pattern")) {
+                (Some(source), None) if files.len() == 0 => self.delete_from(source, matches.is_present("null"), &delete_options),
+                (None, Some(pattern)) if files.len() == 0 => Regex::new(pattern)
+                    .map_err(Error::from)
+                    .and_then(|pattern| self.delete_under(
+                        &pattern,
+                        Path::new(matches.value_of_os("under").unwrap_or_else(|| OsStr::new("."))),
+                        max_depth,
+                        matches.is_present("include-dirs"),
+                        &delete_options
+                    )),
+                (None, None) if files.len() > 0 => self.delete_each(files.collect(), &delete_options),
+                _ => Err(Error::InvalidArguments)
+            }
+        } else if matches.is_present("restore") && !matches.is_present("match") && !matches.is_present("pattern") && !types.is_all() {
+            //
+            // Named items are restored whatever their types, so filtering
+            // them by type can only be a mistake.
+            //
+            Err(Error::InvalidArguments)
+        } else if matches.is_present("last") || matches.is_present("transaction") {
+            match matches.values_of_os("restore") {
+                Some(files) if files.len() == 0 => self.restore_transaction(matches.value_of("transaction"), &restore_options),
+                _ => Err(Error::InvalidArguments)
+            }
+        } else if let Some(pattern) = matches.value_of("match").or_else(|| matches.value_of("pattern").filter(|_| matches.is_present("restore"))) {
+            Regex::new(pattern)
+                .map_err(Error::from)
+                .and_then(|pattern| self.restore_matching(pattern, &types, matches.is_present("all"), &restore_options))
+        } else if let (Some(files), Some(inner)) = (matches.values_of_lossy("restore"), matches.value_of("inner")) {
+            match files.as_slice() {
+                [ file ] => self.restore_inner(file, inner, &restore_options),
+                _ => Err(Error::InvalidArguments)
             }
-        )?;
+        } else if let Some(files) = matches.values_of_lossy("restore") {
+            self.restore_each(files.iter().map(String::as_str).collect(), &restore_options)
+        } else if let Some(uuid) = matches.value_of("uuid") {
+            self.restore_uuid(uuid, &restore_options)
+        } else if matches.is_present("restore-all") {
+            self.restore_all(&restore_options)
+        } else if let Some(values) = matches.values_of("config") {
+            match (values.collect::<Vec<&str>>().as_slice(), matches.is_present("list")) {
+                ([ key, value ], false) => self.configure(key, value),
+                ([], true) => self.write_settings(),
+                _ => Err(Error::InvalidArguments)
+            }
+        } else if matches.is_present("transactions") {
+            self.transactions()
+        } else if matches.is_present("list") && matches.is_present("null") && !matches.is_present("simple") {
+            Err(Error::InvalidArguments)
+        } else if matches.is_present("list") {
+            Regex::new(matches.value_of("pattern").unwrap_or(""))
+                .map_err(Error::from)
+                .and_then(|pattern| self.list(pattern, &ListOptions {
+                    simple: matches.is_present("simple"),
+                    null: matches.is_present("null"),
+                    match_display: matches.is_present("match-display"),
+                    verbose: matches.is_present("verbose"),
+                    watch: matches.is_present("watch"),
+                    missing: matches.is_present("missing"),
+                    indices: matches.is_present("indices"),
+                    types,
+                    json: matches.is_present("json"),
+                    quiet_match: matches.is_present("quiet-match"),
+                    search_reasons: matches.is_present("search-reasons"),
+                    tags: tags.clone(),
+                    sort: match matches.value_of("sort") {
+                        Some("name") => Some(SortKey::Name),
+                        Some("origin") => Some(SortKey::Origin),
+                        Some("date") => Some(SortKey::Date),
+                        Some("size") => Some(SortKey::Size),
+                        _ => None
+                    },
+                    reverse: matches.is_present("reverse"),
+                    size: matches.is_present("size"),
+                    bytes: matches.is_present("bytes")
+                }))
+        } else if let Some(values) = matches.values_of("set-display-name") {
+            match values.collect::<Vec<&str>>().as_slice() {
+                [ target, display_name ] => self.set_display_name(target, display_name),
+                _ => Err(Error::InvalidArguments)
+            }
+        } else if let Some(target) = matches.value_of("show") {
+            self.show(target, matches.is_present("json"))
+        } else if let Some(mut files) = matches.values_of("purge") {
+            files.try_for_each(|file| self.purge(file, matches.value_of("version"), shred, matches.is_present("verbose")))
+        } else if matches.is_present("empty") {
+            let targets: Vec<&str> = matches.values_of("empty").map(|targets| targets.collect()).unwrap_or_default();
+            let pattern: Option<Regex> = matches.value_of("pattern").map(Regex::new).transpose()?;
+            let targeted: bool = !targets.is_empty() || pattern.is_some();
+            let force: bool = matches.is_present("force") || matches.is_present("yes");
+            let kept: Option<usize> = matches.value_of("keep-latest")
+                .map(|kept| kept.parse::<usize>().map_err(|_| Error::InvalidArguments))
+                .transpose()?;
+            let matched = |entry: &Entry| types.matches(entry) && entry.has_tags(&tags) && (
+                !targeted
+                    || targets.iter().any(|target| entry.key().is_named(target))
+                    || pattern.as_ref().is_some_and(|pattern| pattern.is_match(&entry.key().name().to_string_lossy()))
+            );
 
-        for (empty, entry) in entries {
-            for version in entry.history() {
-                location = self.data_path.clone();
-                // Ensure unique names by appending the verssion timestamp to
-                // the destination file name, when more than one versions are
-                // being restored.
-                destination = if entry.history().len() > 1 {
-                    PathBuf::from(format!("{}_{}", entry.key().origin(), version))
-                } else {
-                    PathBuf::from(entry.key().origin())
-                };
+            let dry_run: bool = matches.is_present("dry-run");
 
-                location.push(entry.uuid().to_string());
-                location.push(version);
+            match (matches.value_of("older-than"), kept) {
+                (None, Some(kept)) if !dry_run => self.prune(matched, kept, shred, matches.is_present("verbose")),
+                (Some(duration), None) if !targeted && !dry_run => self.expire(duration, &types, &tags, shred, matches.is_present("verbose")),
+                (None, None) if !targeted => self.empty(&types, &tags, force, shred, matches.is_present("verbose"), dry_run),
+                (None, None) if !dry_run => self.empty_matching(matched, matches.value_of("version"), force, shred, matches.is_present("verbose")),
+                _ => Err(Error::InvalidArguments)
+            }
+        } else if matches.is_present("status") {
+            self.status()
+        } else if matches.is_present("doctor") {
+            self.doctor()
+        } else if matches.is_present("check") {
+            self.check()
+        } else if matches.is_present("stats") {
+            self.stats()
+        } else if matches.is_present("gc") {
+            self.gc(matches.is_present("yes"), matches.is_present("dry-run"))
+        } else if matches.is_present("dedup") {
+            self.dedup()
+        } else if matches.is_present("undo") {
+            self.undo()
+        } else if let Some(shell) = matches.value_of("completions") {
+            shell.parse::<Shell>()
+                .map_err(|_| Error::InvalidArguments)
+                .map(|shell| app().gen_completions_to("tman", shell, &mut std::io::stdout()))
+        } else if let Some(directory) = matches.value_of_os("move-trash") {
+            self.move_trash(Path::new(directory))
+        } else if let Some(path) = matches.value_of("batch") {
+            self.batch(path, matches.is_present("stop-on-error"), matches.is_present("dry-run"))
+        } else {
+            Err(Error::InvalidArguments)
+        }
+    }
 
-                if location.exists() {
-                    rename(location.clone(), destination)?;
-                } else {
-                    Err(Error::MissingTarget(version.clone()))?;
-                }
+    ///
+    /// Run the commands in the batch file `path`, one per line, written as
+    /// the command line arguments of `tman` with the dashes of the action
+    /// optional (such as `restore Bilbo.txt --version latest`).
+    /// Blank lines and lines starting with `#` are ignored.
+    /// The outcome of each command is reported, and the commands are only
+    /// parsed when `dry_run` is set.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.batch("./ops.tman", false, false)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when any command fails, after running the rest unless
+    /// `stop_on_error` is set.
+    ///
+    pub fn batch(&mut self, path: &str, stop_on_error: bool, dry_run: bool) -> Result<()> {
+        let contents: String = read_to_string(path)
+            .map_err(|_| Error::MissingTarget(path.to_string()))?;
+        let mut total: usize = 0;
+        let mut failed: usize = 0;
+
+        for (index, line) in contents.lines().enumerate() {
+            let line: &str = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
             }
 
-            // Remove the directory if all entries are restored.
-            if empty {
-                location.pop();
-                remove_dir_all(&location)?;
+            total += 1;
+
+            let result: Result<()> = split_command(line)
+                .and_then(|mut arguments| {
+                    if !arguments[0].starts_with('-') {
+                        arguments[0] = format!("--{}", arguments[0]);
+                    }
+
+                    arguments.insert(0, String::from("tman"));
+
+                    app().setting(AppSettings::ColorNever).get_matches_from_safe(arguments)
+                        .map_err(|error| Error::InvalidCommand(error.message.lines().next().unwrap_or_default().trim_start_matches("error: ").to_string()))
+                })
+                .and_then(|matches| {
+                    if matches.is_present("batch") {
+                        Err(Error::InvalidCommand(String::from("batches can not be nested")))
+                    } else if dry_run {
+                        Ok(())
+                    } else {
+                        self.run(&matches)
+                    }
+                });
+
+            match result {
+                Ok(()) => self.inform(format!("line {}: ok", index + 1).as_str())?,
+                Err(error) => {
+                    failed += 1;
+                    self.stdout.write_line(format!("line {}: {}", index + 1, error.message()).as_str())?;
+
+                    if stop_on_error {
+                        break;
+                    }
+                }
             }
         }
 
-        Ok(())
+        if failed > 0 {
+            Err(Error::BatchFailed(failed, total))
+        } else {
+            Ok(())
+        }
     }
 
     ///
-    /// List the contents of the trash.
+    /// Move a target file to the trash, or copy it there when `copy` is set,
+    /// then evict old versions if the trash has outgrown it's quota, unless
+    /// `no_evict` is set, when a target which would outgrow it is refused.
     /// 
     /// # Example
     /// 
+    /// ```ignore
+    /// app.delete(Path::new("./Bilbo.txt"), &DeleteOptions::default())?;
     /// ```
-    /// app.list(Regex::from_str("")?, false)?;
-    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when the target can not be located or accessed, when it resolves
+    /// to the root of a filesystem, which has no name to store it under, when
+    /// it contains the trash or is part of it, and when it is protected,
+    /// unless `allow_protected` is set.
     ///
-    pub fn list(&self, pattern: Regex, simple: bool) -> Result<()> {
-        let mut empty: bool = true;
-        let show_all: bool = pattern.as_str().is_empty();
-        let name_style = Style::new().bold();
-        let origin_style = Style::new().dim().italic();
-        let version_style = Style::new();
+    pub fn delete(&mut self, path: &Path, options: &DeleteOptions) -> Result<()> {
+        let target: String = path.display().to_string();
+        //
+        // A trailing slash would have a symbolic link to a directory resolved,
+        // so `link/` is read as `link`, like any other `dir/` as `dir`.
+        //
+        let path: &Path = &path.components().collect::<PathBuf>();
+        let mut metadata: Metadata = Metadata::default();
 
-        if !simple {
-            if show_all {
-                self.stdout.write_line("Showing results in trash.")?;
-            } else {
-                self.stdout.write_line(format!("Showing results for '{}' in trash.", pattern.as_str()).as_str())?;
+        metadata.set_reason(options.reason.map(String::from));
+        metadata.set_transaction(Some(self.transaction));
+
+        //
+        // Symbolic links are trashed themselves, so only their parent may be
+        // resolved.
+        //
+        let locate = |error: std::io::Error| Error::locating(&target, error);
+        let origin: PathBuf = if symlink_metadata(path).map_err(locate)?.file_type().is_symlink() {
+            metadata.set_link_target(Some(read_link(path).context("reading the link", path)?.to_string_lossy().to_string()));
+
+            match (path.parent(), path.file_name()) {
+                (Some(parent), Some(file_name)) if parent != Path::new("") => canonicalize(parent).map_err(locate)?.join(file_name),
+                (_, Some(file_name)) => canonicalize(".").map_err(locate)?.join(file_name),
+                _ => canonicalize(path).map_err(locate)?
             }
+        } else {
+            canonicalize(path).map_err(locate)?
+        };
+        //
+        // Targets such as `.` and `dir/..` are named by the directory they
+        // resolve to, which only the root of a filesystem is not.
+        //
+        let name: OsString = match origin.file_name() {
+            Some(name) => name.to_os_string(),
+            None => Err(Error::UnnamedTarget(target.clone(), origin.display().to_string()))?
+        };
+
+        //
+        // The trash itself is never deleted, even when protected paths are
+        // allowed.
+        //
+        if !options.allow_protected {
+            self.check_protected(&origin)?;
         }
 
-        for entry in self.cache.entries().iter() {
-            if pattern.is_match(entry.key().name()) {
-                if simple {
-                    self.stdout.write_line(format!("{}", entry.key().name()).as_str())?;
-                } else {
-                    self.stdout.write_line(format!("  {} {} {} {}", self.unicode("\u{2022}", "*"), self.color(entry.key().name(), &name_style), self.unicode("\u{2190}", "<-"), self.color(entry.key().origin(), &origin_style)).as_str())?;
-                    for version in entry.history().iter().rev() {
-                        self.stdout.write_line(format!("    {} {}", self.unicode("\u{2192}", "->"), self.color(version, &version_style)).as_str())?;
-                    }
+        self.paths.check_target(&origin)?;
+
+        //
+        // Special files can rarely be restored meaningfully, so they are only
+        // deleted when asked to.
+        //
+        let kind: Kind = if metadata.link_target().is_some() {
+            Kind::Symlink
+        } else {
+            Kind::of(&symlink_metadata(&origin).context("reading", &origin)?.file_type())
+        };
+
+        if kind.is_special() && !options.allow_special {
+            Err(Error::SpecialFile(origin.display().to_string(), kind.name()))?;
+        }
+
+        //
+        // A directory is confirmed with it's contents in view, since trashing
+        // the wrong one moves far more than a file, unless it is only copied.
+        // Without a terminal to ask, `--yes` is required instead.
+        //
+        if self.settings.confirm_directories() && !options.yes && !options.copy && metadata.link_target().is_none() && origin.is_dir() {
+            if !self.stdout.is_term() {
+                Err(Error::UnconfirmedDirectory(origin.display().to_string()))?;
+            }
+
+            let count: usize = files(&origin).context("reading", &origin)?.len();
+            let bytes: u64 = size(&origin).context("measuring", &origin)?;
+            let question: String = format!(
+                "'{}' holds {} files, {} in total, trash it?",
+                origin.display(),
+                self.formatter().number(count as u64),
+                self.formatter().size(bytes)
+            );
 
-                    empty = false;
+            if !self.confirm(&question)? {
+                self.inform(&format!("kept '{}'", origin.display()))?;
+
+                return Ok(());
+            }
+        }
+
+        let mut destination: PathBuf = self.paths.data.clone();
+        let mut previous: Option<(PathBuf, Manifest)> = None;
+
+        //
+        // Directories are stored incrementally by linking their unchanged
+        // files to the previous version, so both versions need a manifest.
+        //
+        if self.settings.dedup_dirs() && cfg!(unix) && metadata.link_target().is_none() && origin.is_dir() {
+            metadata.set_manifest(Some(manifest(&origin).context("reading", &origin)?));
+
+            if let Some(entry) = self.cache.entries().iter().find(|entry| entry.key().origin() == origin) {
+                if let Some(version) = entry.history().last() {
+                    if let Some(previous_manifest) = version.metadata().manifest() {
+                        previous = Some((self.paths.data.join(entry.uuid().to_string()).join(version.display()), previous_manifest.clone()));
+                    }
                 }
             }
         }
 
-        if !simple {
-            if empty && show_all {
-                self.stdout.write_line("Your trash is empty!")?;
-            } else if empty {
-                self.stdout.write_line(format!("No results for '{}'.", pattern.as_str()).as_str())?;
+        metadata.set_size(Some(size(&origin).context("measuring", &origin)?));
+
+        if let (true, Some(limit)) = (options.no_evict, self.settings.max_trash_size()) {
+            if size(&self.paths.data).context("measuring", &self.paths.data)? + metadata.size().unwrap_or_default() > limit {
+                Err(Error::QuotaExceeded(target.clone(), limit))?;
             }
         }
+        metadata.set_kind(Some(kind));
 
-        Ok(())
+        //
+        // The permissions of a symbolic link are meaningless and setting them
+        // would change it's target, so they are not recorded.
+        //
+        if metadata.link_target().is_none() {
+            metadata.set_mode(mode(&origin).context("reading the permissions of", &origin)?);
+            metadata.set_modified(symlink_metadata(&origin).context("reading", &origin)?.modified().ok().map(DateTime::<Utc>::from));
+        }
+
+        let current_manifest: Option<Manifest> = metadata.manifest().cloned();
+        let (uuid, version): (Uuid, String) = self.cache.push(name, origin.clone(), metadata, &options.tags);
+
+        destination.push(uuid.to_string());
+
+        let created: Result<()> = match create_dir(&destination) {
+            Err(ref error) if error.kind() == ErrorKind::AlreadyExists => Ok(()),
+            result => result.context("creating", &destination)
+        };
+
+        destination.push(&version);
+
+        //
+        // The version is taken back out of the cache, along with it's entry if
+        // it was the first, when the target could not be stored, so that the
+        // cache never refers to a version which was not stored.
+        // Failing to do so must not hide why the target was not stored.
+        //
+        let stored: Result<()> = created.and_then(|_| if options.copy {
+            copy_path(&origin, &destination).context("copying", &origin)
+        } else {
+            move_path(&origin, &destination).context("moving", &origin)
+        });
+
+        if let Err(error) = stored {
+            //
+            // A failed move removes it's own partial copy, but a failed copy
+            // leaves it behind.
+            //
+            if options.copy && symlink_metadata(&destination).is_ok() {
+                remove_path(&destination).unwrap_or_default();
+            }
+
+            let emptied: bool = self.cache.pop_where(|entry| entry.uuid() == &uuid, VersionPredicate::Specific(&version))
+                .unwrap_or_default()
+                .iter()
+                .any(|(empty, _)| *empty);
+
+            if emptied {
+                destination.pop();
+                remove_dir_all(&destination).unwrap_or_default();
+            }
+
+            //
+            // The target may have vanished since it was located.
+            //
+            Err(if symlink_metadata(&origin).is_err() {
+                Error::MissingTarget(target.clone())
+            } else {
+                error
+            })?;
+        }
+
+        self.journal.push(Step::Delete {
+            uuid,
+            version: version.clone(),
+            origin: origin.clone()
+        });
+
+        if let (Some((previous_path, previous_manifest)), Some(current_manifest)) = (previous, current_manifest) {
+            link_unchanged(&previous_path, &previous_manifest, &destination, &current_manifest);
+        }
+
+        if self.porcelain {
+            self.stdout.write_line(to_string(&PorcelainRecord {
+                origin: origin.to_string_lossy(),
+                uuid: &uuid,
+                version: &version
+            })?.as_str())?;
+        }
+
+        //
+        // The version just trashed is always kept.
+        //
+        if let Some(kept) = self.settings.max_versions_per_entry() {
+            self.prune(|entry| entry.uuid() == &uuid, kept.max(1), false, options.verbose)?;
+        }
+
+        if options.no_evict {
+            return Ok(());
+        }
+
+        self.evict(&uuid, options.verbose)
     }
 
     ///
-    /// Delete everything in the trash.
+    /// Move each of `files` to the trash, expanding those which are
+    /// shell-style globs, unless a file by that name exists.
+    /// When `keep_going` is set, every target is attempted and a summary of
+    /// the results is printed, naming the failures.
+    /// When `modified_before` is set, targets modified since are skipped and
+    /// a summary is printed, counting them.
     /// 
     /// # Example
     /// 
+    /// ```ignore
+    /// app.delete_each(vec![ OsStr::new("./Bilbo.txt"), OsStr::new("*.log") ], &DeleteOptions::default())?;
     /// ```
-    /// app.empty()?;
-    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails at the first target which can not be deleted, or after all of
+    /// them when `keep_going` is set, counting the failures.
     ///
-    pub fn empty(&mut self) -> Result<()> {
-        let mut location: PathBuf;
+    pub fn delete_each(&mut self, files: Vec<&OsStr>, options: &DeleteOptions) -> Result<()> {
+        //
+        // Names which are not valid UTF-8 are never treated as globs.
+        //
+        let pattern = |file: &OsStr| file.to_str().filter(|pattern| is_glob(pattern) && symlink_metadata(file).is_err()).map(String::from);
 
-        for (_, entry) in self.cache.pop(|_| { true }, VersionPredicate::All)? {
-            location = PathBuf::from(&self.data_path);
-            location.push(entry.uuid().to_string());
+        let mut trashed: usize = 0;
+        let mut skipped: usize = 0;
+        let mut failures: Vec<(String, Error)> = vec![];
+
+        for file in files {
+            let targets: Result<Vec<PathBuf>> = match pattern(file) {
+                Some(pattern) => expand(&pattern).and_then(|paths| if paths.is_empty() {
+                    Err(Error::MissingTarget(pattern))
+                } else {
+                    Ok(paths)
+                }),
+                None => Ok(vec![ PathBuf::from(file) ])
+            };
+
+            match targets {
+                Ok(targets) => for target in targets {
+                    match self.delete_unless_modified(&target, options) {
+                        Ok(true) => trashed += 1,
+                        Ok(false) => skipped += 1,
+                        Err(error) if options.keep_going => failures.push((target.display().to_string(), error)),
+                        Err(error) => Err(error)?
+                    }
+                },
+                Err(error) if options.keep_going => failures.push((Path::new(file).display().to_string(), error)),
+                Err(error) => Err(error)?
+            }
+        }
 
-            remove_dir_all(&location)?;
+        if !options.keep_going && options.modified_before.is_none() {
+            return Ok(());
         }
 
-        Ok(())
+        self.summarise_deletes(None, trashed, skipped, failures)
     }
 
     ///
-    /// Insert a unicode character if `use_unicode` is enabled, else use a
-    /// default ASCII character.
+    /// Move each path listed in the file `source`, or standard input if it is
+    /// `-`, to the trash, as with `keep_going`, where paths are separated by
+    /// new lines, or NUL characters if `null` is set, and empty ones are
+    /// skipped.
+    /// Paths are never treated as globs.
     /// 
     /// # Example
     /// 
+    /// ```ignore
+    /// app.delete_from("-", true, &DeleteOptions::default())?;
     /// ```
-    /// let output: &'static str = app.unicode("\u{2022}", "*");
-    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when `source` can not be read, and after all of the paths when
+    /// any of them can not be deleted, counting the failures.
     ///
-    pub fn unicode<'a>(&self, unicode: &'a str, ascii: &'a str) -> &'a str {
-        if self.settings.use_unicode() {
-            unicode
+    pub fn delete_from(&mut self, source: &str, null: bool, options: &DeleteOptions) -> Result<()> {
+        let contents: Vec<u8> = if source == "-" {
+            let mut contents: Vec<u8> = vec![];
+
+            std::io::stdin().read_to_end(&mut contents).context("reading", Path::new("standard input"))?;
+
+            contents
         } else {
-            ascii
+            std::fs::read(source).map_err(|_| Error::MissingTarget(source.to_string()))?
+        };
+        let paths: Vec<PathBuf> = contents.split(|byte| *byte == if null { b'\0' } else { b'\n' })
+            .filter(|path| !path.is_empty())
+            .map(|path| PathBuf::from(os_string_from_bytes(path)))
+            .collect();
+        let mut trashed: usize = 0;
+        let mut skipped: usize = 0;
+        let mut failures: Vec<(String, Error)> = vec![];
+
+        for path in paths.iter() {
+            match self.delete_unless_modified(path, options) {
+                Ok(true) => trashed += 1,
+                Ok(false) => skipped += 1,
+                Err(error) => failures.push((path.display().to_string(), error))
+            }
         }
+
+        self.summarise_deletes(Some(paths.len()), trashed, skipped, failures)
     }
 
     ///
-    /// Format text with ANSI styles if the `use_colors` setting is enabled.
+    /// Move a target file to the trash as `delete` does, unless it was
+    /// modified at or after `modified_before`, in which case it is skipped,
+    /// with a note when `verbose` is set.
+    /// Whether the target was trashed is returned.
     /// 
     /// # Example
     /// 
+    /// ```ignore
+    /// let trashed: bool = app.delete_unless_modified(Path::new("./Bilbo.txt"), &DeleteOptions::default())?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when the target is not skipped and can not be deleted.
+    ///
+    fn delete_unless_modified(&mut self, path: &Path, options: &DeleteOptions) -> Result<bool> {
+        //
+        // Targets whose modification time can not be read are left for
+        // `delete` to report.
+        //
+        let modified: Option<DateTime<Utc>> = symlink_metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .map(DateTime::<Utc>::from);
+
+        match (options.modified_before, modified) {
+            (Some(cutoff), Some(modified)) if modified >= cutoff => {
+                if options.verbose {
+                    self.inform(format!("skipped '{}', which was modified at {}", path.display(), modified.format("%Y-%m-%d %H:%M:%S UTC")).as_str())?;
+                }
+
+                Ok(false)
+            },
+            _ => self.delete(path, options).map(|()| true)
+        }
+    }
+
+    ///
+    /// Print the summary of deleting several targets, naming the `failures`
+    /// with why they failed, how many were `skipped` as modified too recently,
+    /// and how many paths were `read`, if they were read from a file.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.summarise_deletes(None, 2, 0, vec![])?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when there are any failures, counting them.
+    ///
+    fn summarise_deletes(&self, read: Option<usize>, trashed: usize, skipped: usize, failures: Vec<(String, Error)>) -> Result<()> {
+        let formatter: Formatter = self.formatter();
+        let mut summary: String = format!("{} trashed", formatter.number(trashed as u64));
+
+        if let Some(read) = read {
+            summary = format!("read {} paths, {}", formatter.number(read as u64), summary);
+        }
+
+        if skipped > 0 {
+            summary.push_str(format!(", {} skipped as too new", formatter.number(skipped as u64)).as_str());
+        }
+
+        if !failures.is_empty() {
+            let reasons: Vec<String> = failures.iter()
+                .map(|(target, error)| format!("{} ({})", target, error.message()))
+                .collect();
+
+            summary.push_str(format!(", {} failed: {}", formatter.number(failures.len() as u64), reasons.join(", ")).as_str());
+        }
+
+        //
+        // The summary names the failures, so it is kept even when quiet.
+        //
+        if failures.is_empty() {
+            self.inform(summary.as_str())?;
+        } else {
+            self.chatter().write_line(summary.as_str())?;
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::DeleteFailed(failures.len(), trashed + skipped + failures.len()))
+        }
+    }
+
+    ///
+    /// Move every path under the directory `under`, down to `max_depth`,
+    /// whose name matches `pattern` to the trash, as with `keep_going`.
+    /// Directories are only matched when `include_dirs` is set, and then
+    /// nothing inside a matched directory is matched again.
+    /// More than `CONFIRM_MATCHES` matches are confirmed unless `yes` is set.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.delete_under(&Regex::new(r"\.tmp$")?, Path::new("./build"), None, false, &DeleteOptions::default())?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when `under` can not be searched, when nothing matches, when the
+    /// matches can not be confirmed, and after all of the matches when any of
+    /// them can not be deleted, counting the failures.
+    ///
+    pub fn delete_under(&mut self, pattern: &Regex, under: &Path, max_depth: Option<usize>, include_dirs: bool, options: &DeleteOptions) -> Result<()> {
+        let root: PathBuf = canonicalize(under).map_err(|_| Error::MissingTarget(under.display().to_string()))?;
+        let mut targets: Vec<PathBuf> = vec![];
+        let mut directories: Vec<PathBuf> = vec![];
+
+        //
+        // The trash is never searched, since nothing inside it can be
+        // deleted.
+        //
+        for path in walk(&root, max_depth)? {
+            if path.starts_with(&self.paths.root) || directories.iter().any(|directory| path.starts_with(directory)) {
+                continue;
+            }
+
+            let is_dir: bool = symlink_metadata(&path).is_ok_and(|metadata| metadata.is_dir());
+
+            if (include_dirs || !is_dir) && path.file_name().is_some_and(|name| pattern.is_match(&name.to_string_lossy())) {
+                if is_dir {
+                    directories.push(path.clone());
+                }
+
+                targets.push(path);
+            }
+        }
+
+        if targets.is_empty() {
+            Err(Error::MissingTarget(pattern.as_str().to_string()))?;
+        }
+
+        //
+        // Once the matches are confirmed together, the directories among them
+        // are not confirmed again.
+        //
+        let mut options: DeleteOptions = options.clone();
+
+        if targets.len() > CONFIRM_MATCHES && !options.yes {
+            if !self.stdout.is_term() {
+                Err(Error::UnconfirmedMatches(targets.len(), pattern.as_str().to_string()))?;
+            }
+
+            if !self.confirm(format!("{} paths under '{}' match '{}', trash them?", self.formatter().number(targets.len() as u64), root.display(), pattern.as_str()).as_str())? {
+                return Ok(());
+            }
+
+            options.yes = true;
+        }
+
+        let mut trashed: usize = 0;
+        let mut skipped: usize = 0;
+        let mut failures: Vec<(String, Error)> = vec![];
+
+        for target in targets {
+            match self.delete_unless_modified(&target, &options) {
+                Ok(true) => trashed += 1,
+                Ok(false) => skipped += 1,
+                Err(error) => failures.push((target.display().to_string(), error))
+            }
+        }
+
+        self.summarise_deletes(None, trashed, skipped, failures)
+    }
+
+    ///
+    /// Check that the resolved path `target` is not the filesystem root, the
+    /// home directory or a directory containing it, and that it neither
+    /// contains nor lies inside any of the `protected_paths` setting.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.check_protected(Path::new("/home/Bilbo/Bilbo.txt"))?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when `target` is protected.
+    ///
+    fn check_protected(&self, target: &Path) -> Result<()> {
+        let home: Option<PathBuf> = home_dir().map(|home| resolve(&home).unwrap_or(home));
+        let mut protected: bool = target.parent().is_none() || home.is_some_and(|home| home.starts_with(target));
+
+        for path in self.settings.protected_paths() {
+            let path: PathBuf = resolve(Path::new(path)).unwrap_or_else(|_| PathBuf::from(path));
+
+            protected |= path.starts_with(target) || target.starts_with(&path);
+        }
+
+        if protected {
+            Err(Error::ProtectedPath(target.display().to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Permanently delete the versions chosen by the `quota_policy` setting,
+    /// oldest first, until the size of the data directory fits
+    /// `max_trash_size`, keeping the newest version of the item with the UUID
+    /// `protected`.
+    /// What was evicted is reported, along with why when `verbose` is set.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.evict(&uuid, false)?;
+    /// ```
+    ///
+    fn evict(&mut self, protected: &Uuid, verbose: bool) -> Result<()> {
+        let limit: u64 = match self.settings.max_trash_size() {
+            Some(limit) => limit,
+            None => return Ok(())
+        };
+        let total: u64 = size(&self.paths.data)?;
+
+        if total <= limit {
+            return Ok(());
+        }
+
+        //
+        // Versions trashed before their sizes were recorded are measured in
+        // the store, so that the policies can weigh them too.
+        //
+        let mut entries: Vec<Entry> = vec![];
+
+        for entry in self.cache.entries() {
+            let mut history: Vec<Version> = vec![];
+
+            for version in entry.history() {
+                let mut metadata: Metadata = version.metadata().clone();
+
+                if metadata.size().is_none() {
+                    metadata.set_size(size(&self.paths.data.join(entry.uuid().to_string()).join(version.display())).ok());
+                }
+
+                history.push(Version::new(*version.timestamp(), metadata));
+            }
+
+            entries.push(Entry::new(entry.key().clone(), *entry.uuid(), history));
+        }
+
+        let plan: Vec<Eviction> = self.settings.quota_policy().planner().plan(&entries, total - limit, Some(protected));
+        let formatter: Formatter = self.formatter();
+        let mut freed: u64 = 0;
+
+        for eviction in &plan {
+            let location: PathBuf = self.paths.data.join(eviction.uuid.to_string());
+
+            for (empty, _) in self.cache.pop_where(|entry| *entry.uuid() == eviction.uuid, VersionPredicate::Specific(&eviction.version))? {
+                if empty {
+                    remove_dir_all(&location)?;
+                } else {
+                    remove_path(&location.join(&eviction.version))?;
+                }
+            }
+
+            freed += eviction.size;
+
+            if verbose {
+                self.inform(format!("evicted '{}' ({}), {}, because {}", eviction.name, eviction.version, formatter.size(eviction.size), eviction.reason).as_str())?;
+            }
+        }
+
+        self.inform(format!("evicted {} versions, freeing {}, to fit the quota of {}", formatter.number(plan.len() as u64), formatter.size(freed), formatter.size(limit)).as_str())?;
+
+        Ok(())
+    }
+
+    ///
+    /// Restore several target files, applying the same options to each of
+    /// them.
+    /// Targets that can not be located do not stop the remaining targets from
+    /// being restored, they are reported together once all have been attempted.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.restore_each(vec!["Bilbo.txt", "Frodo.txt"], &RestoreOptions::default())?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when an origin is given alongside more than one target, and when
+    /// any of the targets could not be located.
+    ///
+    pub fn restore_each(&mut self, target_names: Vec<&str>, options: &RestoreOptions) -> Result<()> {
+        let mut missing: Vec<String> = vec![];
+
+        if target_names.is_empty() {
+            Err(Error::InvalidArguments)?;
+        }
+
+        if target_names.len() > 1 && options.origin.is_some() {
+            Err(Error::AmbiguousOrigin)?;
+        }
+
+        for target_name in target_names {
+            match self.restore(target_name, options) {
+                Err(Error::MissingTargetPredicate) => missing.push(target_name.to_string()),
+                result => result?
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::MissingTargets(missing))
+        }
+    }
+
+    ///
+    /// Restore a target files version to it's original location, or into the
+    /// destination directory under it's original name.
+    /// When items named `target_name` were trashed from several origins and
+    /// neither an origin nor `all_origins` was given, one is chosen on a
+    /// terminal before anything is restored, unless `newest_only` is set, in
+    /// which case only the newest version among them is restored, or
+    /// `interactive` is set, in which case each of them is confirmed.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.restore("Bilbo.txt", &RestoreOptions::default())?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when no items have the name `target_name`, when the destination
+    /// directory does not exist and can not be created, and when several
+    /// origins match without a terminal to choose between them.
+    ///
+    pub fn restore(&mut self, target_name: &str, options: &RestoreOptions) -> Result<()> {
+        let mut target_origin: Option<&Path> = options.origin.map(Path::new);
+        let mut origins: Vec<PathBuf> = vec![];
+        let found: Vec<&Entry> = self.cache.find((target_name, target_origin));
+
+        //
+        // Missing targets are reported before anything is asked or moved.
+        //
+        if found.is_empty() {
+            Err(Error::MissingTargetPredicate)?;
+        }
+
+        //
+        // Only the newest version among every origin is restored, leaving the
+        // rest in the trash, so any other choice of versions is dropped.
+        //
+        if options.newest_only {
+            let newest: Uuid = *found.iter()
+                .max_by_key(|entry| entry.history().last().map(|version| *version.timestamp()))
+                .unwrap()
+                .uuid();
+            let options: RestoreOptions = RestoreOptions {
+                version: None,
+                transaction: None,
+                ..options.clone()
+            };
+
+            return self.restore_where(|entry| entry.uuid() == &newest, &options).map(|_| ());
+        }
+
+        for entry in found {
+            if !origins.iter().any(|origin| origin == entry.key().origin()) {
+                origins.push(entry.key().origin().to_path_buf());
+            }
+        }
+
+        if target_origin.is_none() && !options.all_origins && !options.interactive && origins.len() > 1 {
+            let origin_style = Style::new().dim().italic();
+
+            self.stdout.write_line(format!("'{}' was trashed from several origins:", target_name).as_str())?;
+
+            for (index, origin) in origins.iter().enumerate() {
+                self.stdout.write_line(format!("  {}. {}", index + 1, self.color(&origin.to_string_lossy(), &origin_style)).as_str())?;
+            }
+
+            if !self.stdout.is_term() {
+                Err(Error::AmbiguousName(target_name.to_string(), origins.iter().map(|origin| origin.display().to_string()).collect()))?;
+            }
+
+            match self.choose("Restore which origin?", origins.len())? {
+                Some(index) => target_origin = Some(&origins[index - 1]),
+                None => return Ok(())
+            }
+        }
+
+        self.restore_where(
+            |entry| {
+                if let Some(target_origin) = target_origin {
+                    entry.key().is_named(target_name) && entry.key().origin() == target_origin
+                } else {
+                    entry.key().is_named(target_name)
+                }
+            },
+            options
+        ).map(|_| ())
+    }
+
+    ///
+    /// Restore the item with the UUID `target_uuid`, ignoring it's name and
+    /// origin.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.restore_uuid("56f188aa-1122-41a1-b2f2-e2f0008db54a", &RestoreOptions::default())?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when `target_uuid` is not a valid UUID and when no item has the
+    /// UUID `target_uuid`.
+    ///
+    pub fn restore_uuid(&mut self, target_uuid: &str, options: &RestoreOptions) -> Result<()> {
+        let uuid: Uuid = Uuid::parse_str(target_uuid)
+            .map_err(|_| Error::InvalidUuid(target_uuid.to_string()))?;
+
+        self.restore_where(|entry| entry.uuid() == &uuid, options)
+            .map(|_| ())
+            .map_err(|error| match error {
+                Error::MissingTargetPredicate => Error::MissingTarget(target_uuid.to_string()),
+                error => error
+            })
+    }
+
+    ///
+    /// Reverse the deletes and restores made by the last run which made any,
+    /// newest first, restoring what it deleted to where it was trashed from
+    /// and trashing again what it restored.
+    /// As the undo is itself logged, undoing twice redoes the run.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.undo()?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when nothing has been logged, and at the first step which can not
+    /// be reversed, such as a version since purged or a restored path since
+    /// moved.
+    ///
+    pub fn undo(&mut self) -> Result<()> {
+        let record: Record = last(&self.paths.log)?.ok_or(Error::NothingToUndo)?;
+        let mut deletes: usize = 0;
+        let mut restores: usize = 0;
+
+        for step in record.steps().iter().rev() {
+            match step {
+                Step::Delete { uuid, version, .. } => {
+                    self.restore_where(|entry| entry.uuid() == uuid, &RestoreOptions {
+                        version: Some(version),
+                        ..RestoreOptions::default()
+                    })?;
+                    deletes += 1;
+                },
+                //
+                // The path was restored on purpose, so it is trashed again
+                // without asking, even when it is a directory.
+                //
+                Step::Restore { destination, .. } => {
+                    self.delete(destination, &DeleteOptions {
+                        yes: true,
+                        ..DeleteOptions::default()
+                    })?;
+                    restores += 1;
+                }
+            }
+        }
+
+        self.inform(format!(
+            "undid {} deletes and {} restores made at {}",
+            self.formatter().number(deletes as u64),
+            self.formatter().number(restores as u64),
+            record.at()
+        ).as_str())
+    }
+
+    ///
+    /// Restore every version trashed in the transaction `target_transaction`,
+    /// or in the last transaction left in the trash if it is `None`.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.restore_transaction(None, &RestoreOptions::default())?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when `target_transaction` is not a valid UUID and when no version
+    /// in the trash was trashed in the transaction.
+    ///
+    pub fn restore_transaction(&mut self, target_transaction: Option<&str>, options: &RestoreOptions) -> Result<()> {
+        let transaction: Uuid = match target_transaction {
+            Some(target_transaction) => Uuid::parse_str(target_transaction)
+                .map_err(|_| Error::InvalidUuid(target_transaction.to_string()))?,
+            None => match self.recorded_transactions().last() {
+                Some((transaction, _, _)) => *transaction,
+                None => Err(Error::MissingTargetPredicate)?
+            }
+        };
+
+        self.restore_where(
+            |entry| entry.history().iter().any(|version| version.metadata().transaction() == Some(&transaction)),
+            &RestoreOptions {
+                transaction: Some(transaction),
+                ..options.clone()
+            }
+        )
+            .map(|_| ())
+            .map_err(|error| match error {
+                Error::MissingTargetPredicate => Error::MissingTarget(transaction.to_string()),
+                error => error
+            })
+    }
+
+    ///
+    /// Restore the items of the types `types` whose names match `pattern`.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.restore_matching(Regex::from_str(r"\.txt$")?, &TypeFilter::All, false, &RestoreOptions::default())?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when `pattern` is empty, unless `all` is set, so that every item
+    /// is not restored by mistake.
+    ///
+    pub fn restore_matching(&mut self, pattern: Regex, types: &TypeFilter, all: bool, options: &RestoreOptions) -> Result<()> {
+        if pattern.as_str().is_empty() && !all {
+            Err(Error::EmptyPattern)?;
+        }
+
+        self.restore_where(|entry| types.matches(entry) && pattern.is_match(&entry.key().name().to_string_lossy()), options)
+            .map(|_| ())
+    }
+
+    ///
+    /// Restore every item, reporting and skipping those whose versions are
+    /// missing from the trash, then summarise how many were restored.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.restore_all(&RestoreOptions::default())?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when any item could not be restored, once every other item has
+    /// been.
+    ///
+    pub fn restore_all(&mut self, options: &RestoreOptions) -> Result<()> {
+        let mut restored: usize = 0;
+        let mut failed: usize = 0;
+        let targets: Vec<(Uuid, String, bool)> = self.cache.entries()
+            .iter()
+            .map(|entry| {
+                let location: PathBuf = self.paths.data.join(entry.uuid().to_string());
+
+                (*entry.uuid(), entry.key().name().to_string_lossy().to_string(), entry.history().iter().all(|version| symlink_metadata(location.join(version.display())).is_ok()))
+            })
+            .collect();
+
+        let total: usize = targets.len();
+
+        for (uuid, name, intact) in targets {
+            if !intact {
+                self.stdout.write_line(format!("skipped '{}', it's versions are missing from the trash", name).as_str())?;
+                failed += 1;
+                continue;
+            }
+
+            match self.restore_where(|entry| entry.uuid() == &uuid, options) {
+                Ok(count) => restored += count,
+                result => {
+                    failed += 1;
+                    finish(result);
+                }
+            }
+        }
+
+        self.inform(format!("restored {} items, {} failed", self.formatter().number(restored as u64), self.formatter().number(failed as u64)).as_str())?;
+
+        if failed > 0 {
+            Err(Error::RestoreFailed(failed, total))?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Restore the items satisfying `entry_predicate`, returning how many were
+    /// restored.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let restored: usize = app.restore_where(|entry| entry.key().is_named("Bilbo.txt"), &RestoreOptions::default())?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when no items satisfy `entry_predicate` and when the destination
+    /// is not a directory or does not exist and can not be created.
+    ///
+    fn restore_where<EP>(&mut self, entry_predicate: EP, options: &RestoreOptions) -> Result<usize>
+    where
+        EP: Fn(&Entry) -> bool
+    {
+        let mut location: PathBuf = PathBuf::default();
+        #[allow(unused_assignments)]
+        let mut destination: PathBuf = PathBuf::default();
+        let mut selected: Vec<(Uuid, Option<String>)> = vec![];
+        let mut entries: Vec<Entry> = vec![];
+
+        if let Some(target_destination) = options.destination {
+            if PathBuf::from(target_destination).exists() && !PathBuf::from(target_destination).is_dir() {
+                Err(Error::NotADirectory(target_destination.to_string()))?;
+            }
+
+            create_dir_all(target_destination)
+                .map_err(|_| Error::InvalidDestination(target_destination.to_string()))?;
+        }
+
+        //
+        // A destination which resolves into the trash, or to a stored version
+        // itself, would have the version moved onto itself and then removed
+        // with it's directory, so it must be refused before anything is
+        // popped.
+        //
+        for entry in self.cache.entries().iter().filter(|entry| entry_predicate(entry)) {
+            self.check_destination(entry, &self.target(entry, options))?;
+        }
+
+        //
+        // Every matching item must be confirmed before anything is moved, so
+        // that declined items are left untouched in the cache.
+        // Versions are only removed from the cache once they have been moved,
+        // so that a failed move leaves the version in the trash.
+        // Items with several versions offer a choice of version, unless one
+        // was given.
+        //
+        if options.interactive {
+            for entry in self.cache.entries().iter().filter(|entry| entry_predicate(entry)) {
+                if options.version.is_none() && options.transaction.is_none() && entry.history().len() > 1 {
+                    self.write_entry(entry, &ListOptions { verbose: true, indices: true, ..ListOptions::default() })?;
+
+                    if let Some(index) = self.choose("Restore which version?", entry.history().len())? {
+                        selected.push((*entry.uuid(), Some(entry.history()[entry.history().len() - index].display())));
+                    }
+                } else {
+                    self.write_entry(entry, &ListOptions { verbose: true, ..ListOptions::default() })?;
+
+                    if self.confirm("Restore this item?")? {
+                        selected.push((*entry.uuid(), None));
+                    }
+                }
+            }
+
+            if selected.is_empty() && self.cache.entries().iter().any(&entry_predicate) {
+                return Ok(0);
+            }
+
+            for (uuid, version) in selected.iter() {
+                entries.append(&mut self.cache.select(
+                    |entry| entry.uuid() == uuid,
+                    match version {
+                        Some(version) => VersionPredicate::Specific(version),
+                        None => options.version_predicate()?
+                    }
+                )?);
+            }
+        } else {
+            entries = self.cache.select(entry_predicate, options.version_predicate()?)?;
+        }
+
+        //
+        // Items whose destination lies on another filesystem than the trash
+        // are copied, then removed from the trash once the copy is verified,
+        // which is slow enough to be interrupted, so they are restored after
+        // every item which can simply be renamed.
+        // A symbolic link leading to another filesystem is named, since
+        // nothing else about the destination says that it will be copied.
+        //
+        let mut plan: Vec<(bool, Entry, PathBuf)> = entries.into_iter()
+            .map(|entry| {
+                let target: PathBuf = self.target(&entry, options);
+
+                (!same_device(&self.paths.data, &target), entry, target)
+            })
+            .collect();
+        plan.sort_by_key(|(copied, _, _)| *copied);
+
+        for (_, _, target) in plan.iter().filter(|(copied, _, _)| *copied) {
+            if let Some((link, link_target)) = linked_ancestor(target) {
+                if !self.warned_links.contains(&link) {
+                    self.inform(format!("warning: {} is a symbolic link to {} on another filesystem, so items restored through it are copied", link.display(), link_target.display()).as_str())?;
+                    self.warned_links.push(link);
+                }
+            }
+        }
+
+        let mut restored: usize = 0;
+
+        for (_, entry, target) in plan {
+            let mut empty: bool = false;
+            let mut moved: bool = false;
+
+            for version in entry.history() {
+                location = self.paths.data.clone();
+                // Ensure unique names by appending the verssion timestamp to
+                // the destination file name, when more than one versions are
+                // being restored, precisely if two were trashed in the same
+                // second.
+                destination = if entry.history().len() > 1 {
+                    let precise: bool = entry.history().iter().filter(|other| other.suffix(false) == version.suffix(false)).count() > 1;
+
+                    PathBuf::from(format!("{}_{}", target.display(), version.suffix(precise)))
+                } else {
+                    target.clone()
+                };
+
+                location.push(entry.uuid().to_string());
+                location.push(version.display());
+
+                if symlink_metadata(&location).is_err() {
+                    Err(Error::MissingTarget(version.display()))?;
+                }
+
+                self.note_recent_restore(entry.uuid(), version)?;
+
+                //
+                // A skipped version is left in the trash, and the cache, so
+                // that it can be restored once the path is free.
+                //
+                destination = match self.settle_conflict(destination, options.on_conflict)? {
+                    Some(destination) => destination,
+                    None => continue
+                };
+
+                move_path(&location, &destination).context("moving", &location)?;
+                moved = true;
+
+                self.cache.record(|candidate| candidate.uuid() == entry.uuid(), Event::new(Utc::now(), *version.timestamp(), destination.clone()));
+                self.journal.push(Step::Restore {
+                    uuid: *entry.uuid(),
+                    version: version.display(),
+                    destination: destination.clone()
+                });
+
+                empty = self.cache.pop_where(|candidate| candidate.uuid() == entry.uuid(), VersionPredicate::Specific(&version.display()))?
+                    .iter()
+                    .any(|(empty, _)| *empty);
+
+                //
+                // Both incremental directories and files deduplicated with
+                // `--dedup` may share their data with other versions.
+                //
+                if version.metadata().manifest().is_some() || version.metadata().kind() == Some(Kind::File) {
+                    separate(&destination).context("separating the links of", &destination)?;
+                }
+
+                //
+                // A special file would have to be opened to set it's
+                // modification time, which blocks for a named pipe.
+                // The time is set before the permissions, which may not let
+                // the file be opened.
+                //
+                if let (Some(modified), false) = (version.metadata().modified(), version.metadata().kind().is_some_and(|kind| kind.is_special())) {
+                    set_modified(&destination, SystemTime::from(*modified)).context("setting the modification time of", &destination)?;
+                }
+
+                if let Some(mode) = version.metadata().mode() {
+                    set_mode(&destination, mode).context("setting the permissions of", &destination)?;
+                }
+
+                for executable in executables(&destination).context("reading", &destination)? {
+                    if options.strip_exec {
+                        strip_exec(&executable).context("setting the permissions of", &executable)?;
+                        self.inform(format!("cleared execute permission of {}", executable.display()).as_str())?;
+                    } else {
+                        self.inform(format!("restored executable {}", executable.display()).as_str())?;
+                    }
+                }
+
+                if let Some(link_target) = version.metadata().link_target() {
+                    if !destination.exists() {
+                        self.inform(format!("restored symlink points to missing {}", link_target).as_str())?;
+                    }
+                }
+            }
+
+            // Remove the directory if all entries are restored.
+            if empty {
+                location.pop();
+                remove_dir_all(&location).context("removing", &location)?;
+            }
+
+            if moved {
+                restored += 1;
+            }
+        }
+
+        Ok(restored)
+    }
+
+    ///
+    /// Copy the file or directory tree at the relative path `inner` out of a
+    /// stored directory version of the item named `target_name`, to the same
+    /// path below it's origin, or into the destination directory, leaving the
+    /// version in the trash.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.restore_inner("project", "src/main.rs", &RestoreOptions::default())?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when `inner` is not a plain relative path, when the item or the
+    /// path inside it can not be located, and when the name was trashed from
+    /// several origins and none was given.
+    ///
+    pub fn restore_inner(&mut self, target_name: &str, inner: &str, options: &RestoreOptions) -> Result<()> {
+        let inner_path: &Path = Path::new(inner);
+
+        if inner_path.components().any(|component| !matches!(component, Component::Normal(_) | Component::CurDir)) || inner_path.file_name().is_none() {
+            Err(Error::InvalidInnerPath(inner.to_string()))?;
+        }
+
+        let found: Vec<&Entry> = self.cache.find((target_name, options.origin.map(Path::new)));
+
+        match found.len() {
+            0 => Err(Error::MissingTarget(target_name.to_string()))?,
+            1 => (),
+            _ => Err(Error::AmbiguousName(target_name.to_string(), found.iter().map(|entry| entry.key().origin().display().to_string()).collect()))?
+        }
+
+        let uuid: Uuid = *found[0].uuid();
+        let entries: Vec<Entry> = self.cache.select(|entry| entry.uuid() == &uuid, VersionPredicate::try_from(options.version)?)?;
+
+        for entry in entries {
+            let target: PathBuf = match options.destination {
+                Some(target_destination) => {
+                    create_dir_all(target_destination)
+                        .map_err(|_| Error::InvalidDestination(target_destination.to_string()))?;
+
+                    PathBuf::from(target_destination).join(inner_path.file_name().unwrap())
+                },
+                None => entry.key().origin().join(inner_path)
+            };
+
+            self.check_destination(&entry, &target)?;
+
+            for version in entry.history() {
+                let stored: PathBuf = self.paths.data.join(entry.uuid().to_string()).join(version.display());
+                let source: PathBuf = stored.join(inner_path);
+                let destination: PathBuf = if entry.history().len() > 1 {
+                    PathBuf::from(format!("{}_{}", target.display(), version.suffix(true)))
+                } else {
+                    target.clone()
+                };
+
+                //
+                // Symbolic links inside the stored version must not lead the
+                // path out of it.
+                //
+                if symlink_metadata(&source).is_err() || !resolve(&source)?.starts_with(resolve(&stored)?) {
+                    Err(Error::MissingTarget(format!("{}/{}", target_name, inner)))?;
+                }
+
+                if let Some(parent) = destination.parent() {
+                    create_dir_all(parent)
+                        .map_err(|_| Error::InvalidDestination(parent.display().to_string()))?;
+                }
+
+                if let Some(destination) = self.settle_conflict(destination, options.on_conflict)? {
+                    copy_path(&source, &destination)?;
+                    self.inform(format!("extracted {}", destination.display()).as_str())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Decide where an item is restored when `destination` already exists,
+    /// according to `on_conflict`, returning nothing when it is skipped.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let destination: Option<PathBuf> = app.settle_conflict(PathBuf::from("./Bilbo.txt"), OnConflict::Rename)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when an existing path can not be overwritten.
+    ///
+    fn settle_conflict(&self, destination: PathBuf, on_conflict: OnConflict) -> Result<Option<PathBuf>> {
+        if symlink_metadata(&destination).is_err() {
+            return Ok(Some(destination));
+        }
+
+        match on_conflict {
+            OnConflict::Skip => {
+                self.inform(format!("skipped '{}', it already exists", destination.display()).as_str())?;
+
+                Ok(None)
+            },
+            OnConflict::Overwrite => {
+                remove_path(&destination)?;
+
+                Ok(Some(destination))
+            },
+            OnConflict::Rename => Ok(Some(available_path(&destination)))
+        }
+    }
+
+    ///
+    /// Write a note when a version next to `version` in the history of the
+    /// item with the UUID `uuid` was restored within the last week.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.note_recent_restore(entry.uuid(), &version)?;
+    /// ```
+    ///
+    fn note_recent_restore(&self, uuid: &Uuid, version: &Version) -> Result<()> {
+        let recent: DateTime<Utc> = Utc::now() - chrono::Duration::weeks(1);
+
+        if let Some(entry) = self.cache.entry(uuid) {
+            for event in entry.events().iter().rev().filter(|event| *event.restored_at() > recent) {
+                let (low, high) = if event.version() < version.timestamp() {
+                    (event.version(), version.timestamp())
+                } else {
+                    (version.timestamp(), event.version())
+                };
+
+                //
+                // Versions are adjacent when no remaining version was trashed
+                // between them.
+                //
+                if !entry.history().iter().any(|other| other.timestamp() > low && other.timestamp() < high) {
+                    self.inform(format!("note: the neighbouring version {} was already restored to {} at {}", event.version(), event.destination().display(), event.restored_at()).as_str())?;
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Get the path an item will be restored to, ignoring version suffixes.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let target: PathBuf = app.target(&entry, &RestoreOptions::default());
+    /// ```
+    ///
+    fn target(&self, entry: &Entry, options: &RestoreOptions) -> PathBuf {
+        match options.destination {
+            Some(target_destination) => PathBuf::from(target_destination).join(entry.key().name()),
+            None => entry.key().origin().to_path_buf()
+        }
+    }
+
+    ///
+    /// Check that restoring the versions of an item to `target` can not move
+    /// them onto themselves or into the trash.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.check_destination(&entry, Path::new("/home/Bilbo/Bilbo.txt"))?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when `target`, once resolved, lies inside the trash or is one of
+    /// the item's stored versions.
+    ///
+    fn check_destination(&self, entry: &Entry, target: &Path) -> Result<()> {
+        let data_path: PathBuf = resolve(&self.paths.data)?;
+        let location: PathBuf = self.paths.data.join(entry.uuid().to_string());
+        let unsafe_destination = || Error::UnsafeDestination(target.display().to_string());
+
+        if resolve(target)?.starts_with(&data_path) {
+            Err(unsafe_destination())?;
+        }
+
+        for version in entry.history() {
+            if same_file(&location.join(version.display()), target) {
+                Err(unsafe_destination())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// List the contents of the trash as a JSON array, without any unicode or
+    /// colors.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.list_json(&Regex::from_str("")?, &ListOptions::default())?;
+    /// ```
+    ///
+    pub fn list_json(&self, pattern: &Regex, options: &ListOptions) -> Result<()> {
+        let entries: Vec<JsonEntry> = self.listed(self.cache.entries(), pattern, options)
+            .into_iter()
+            .map(|entry| JsonEntry {
+                name: entry.key().name().to_string_lossy(),
+                origin: entry.key().origin().to_string_lossy(),
+                uuid: entry.uuid(),
+                display_name: entry.display_name(),
+                tags: entry.tags(),
+                history: entry.history(),
+                events: None
+            })
+            .collect();
+
+        self.stdout.write_line(to_string(&entries)?.as_str())?;
+
+        Ok(())
+    }
+
+    ///
+    /// Collect the `entries` which should be listed, given `pattern` and
+    /// `options`, in the order `options` asks for.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let entries: Vec<&Entry> = app.listed(cache.entries(), &Regex::from_str("")?, &ListOptions::default());
+    /// ```
+    ///
+    fn listed<'a>(&self, entries: &'a [Entry], pattern: &Regex, options: &ListOptions) -> Vec<&'a Entry> {
+        let mut listed: Vec<&Entry> = entries.iter().filter(|entry| self.is_listed(entry, pattern, options)).collect();
+
+        //
+        // Sorts are stable, so items which tie stay in the order they were
+        // first trashed in.
+        //
+        match options.sort {
+            Some(SortKey::Name) => listed.sort_by_key(|entry| entry.key().name().to_os_string()),
+            Some(SortKey::Origin) => listed.sort_by_key(|entry| entry.key().origin().to_path_buf()),
+            Some(SortKey::Date) => listed.sort_by_key(|entry| entry.history().last().map(|version| *version.timestamp())),
+            Some(SortKey::Size) => listed.sort_by_cached_key(|entry| Reverse(self.entry_size(entry))),
+            None => ()
+        }
+
+        if options.reverse {
+            listed.reverse();
+        }
+
+        listed
+    }
+
+    ///
+    /// Check whether an entry should be listed, given `pattern` and `options`.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let listed: bool = app.is_listed(&entry, &Regex::from_str("")?, &ListOptions::default());
+    /// ```
+    ///
+    fn is_listed(&self, entry: &Entry, pattern: &Regex, options: &ListOptions) -> bool {
+        if !options.types.matches(entry) || !entry.has_tags(&options.tags) {
+            return false;
+        }
+
+        if options.missing && !entry.history().iter().any(|version| version.metadata().link_target().is_some_and(|link_target| !Path::new(link_target).exists())) {
+            return false;
+        }
+
+        pattern.is_match(&entry.key().name().to_string_lossy())
+            || (options.match_display && entry.display_name().is_some_and(|display_name| pattern.is_match(display_name)))
+            || (options.search_reasons && entry.history().iter().any(|version| version.metadata().reason().is_some_and(|reason| pattern.is_match(reason))))
+    }
+
+    ///
+    /// List the contents of the trash, re-listing them whenever the cache
+    /// changes if `watch` is set.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.list(Regex::from_str("")?, &ListOptions::default())?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails without listing anything when `quiet_match` is set and no items
+    /// would be listed.
+    ///
+    pub fn list(&self, pattern: Regex, options: &ListOptions) -> Result<()> {
+        if options.quiet_match {
+            if self.cache.entries().iter().any(|entry| self.is_listed(entry, &pattern, options)) {
+                Ok(())
+            } else {
+                Err(Error::NoMatch)
+            }
+        } else if options.json {
+            self.list_json(&pattern, options)
+        } else {
+            //
+            // An invalid format is warned about here, rather than for every
+            // timestamp, or every refresh of a watched listing.
+            //
+            if let Some(format) = self.settings.timestamp_format().filter(|format| !is_valid_format(format)) {
+                self.inform(format!("warning: '{}' is not a valid timestamp_format, so timestamps are shown in full", format).as_str())?;
+            }
+
+            if options.watch {
+                self.watch(&pattern, options)
+            } else {
+                self.render_list(self.cache.entries(), &pattern, options)
+            }
+        }
+    }
+
+    ///
+    /// Re-list a fresh snapshot of the cache each time it's file is modified,
+    /// until the user presses `q` or Ctrl-C.
+    /// Without a terminal, watching stops at a line reading `q` or at the end
+    /// of the input, so that it never outlives whatever is driving it.
+    /// The cache file is polled once every second and is only ever read,
+    /// under a shared lock, so watching never gets in the way of other
+    /// invocations.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.watch(&Regex::from_str("")?, &ListOptions::default())?;
+    /// ```
+    ///
+    pub fn watch(&self, pattern: &Regex, options: &ListOptions) -> Result<()> {
+        let (sender, receiver): (Sender<()>, Receiver<()>) = channel();
+        let mut modified: Option<SystemTime> = None;
+
+        //
+        // Keys are read on their own thread so that they can interrupt the
+        // wait between polls.
+        //
+        if self.stdout.is_term() {
+            spawn(move || {
+                let stdout: Term = Term::stdout();
+
+                loop {
+                    match stdout.read_char() {
+                        Ok('q') | Ok('Q') | Err(_) => {
+                            sender.send(()).unwrap_or_default();
+                            break;
+                        },
+                        Ok(_) => ()
+                    }
+                }
+            });
+        } else {
+            spawn(move || {
+                for line in stdin().lock().lines() {
+                    match line {
+                        Ok(line) if !line.trim().eq_ignore_ascii_case("q") => (),
+                        _ => break
+                    }
+                }
+
+                sender.send(()).unwrap_or_default();
+            });
+        }
+
+        loop {
+            let current: Option<SystemTime> = metadata(&self.paths.cache).and_then(|metadata| metadata.modified()).ok();
+
+            if modified.is_none() || current != modified {
+                modified = current;
+
+                self.stdout.clear_screen()?;
+                self.render_list(&Cache::snapshot(&self.paths.cache)?, pattern, options)?;
+            }
+
+            match receiver.recv_timeout(Duration::from_secs(1)) {
+                Err(RecvTimeoutError::Timeout) => (),
+                _ => break
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Write the listing of `entries` satisfying `pattern`.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.render_list(cache.entries(), &Regex::from_str("")?, &ListOptions::default())?;
+    /// ```
+    ///
+    pub fn render_list(&self, entries: &[Entry], pattern: &Regex, options: &ListOptions) -> Result<()> {
+        let mut empty: bool = true;
+        let mut stored: u64 = 0;
+        let show_all: bool = pattern.as_str().is_empty();
+
+        if !options.simple {
+            if show_all {
+                self.inform("Showing results in trash.")?;
+            } else {
+                self.inform(format!("Showing results for '{}' in trash.", pattern.as_str()).as_str())?;
+            }
+        }
+
+        for entry in self.listed(entries, pattern, options) {
+            if options.simple && options.null {
+                self.stdout.write_str(format!("{}\0", entry.key().name().to_string_lossy()).as_str())?;
+            } else if options.simple && options.size {
+                self.stdout.write_line(format!("{}\t{}", self.listed_size(self.entry_size(entry), options), entry.key().name().to_string_lossy()).as_str())?;
+            } else if options.simple {
+                self.stdout.write_line(format!("{}", entry.key().name().to_string_lossy()).as_str())?;
+            } else {
+                self.write_entry(entry, options)?;
+
+                if options.size {
+                    stored += self.entry_size(entry);
+                } else if options.verbose {
+                    stored += entry.history().iter().filter_map(|version| self.stored_size(entry, version)).sum::<u64>();
+                }
+
+                empty = false;
+            }
+        }
+
+        if !options.simple {
+            if (options.verbose || options.size) && !empty {
+                self.stdout.write_line(format!("{} on disk in total", self.listed_size(stored, options)).as_str())?;
+            }
+
+            if empty && show_all {
+                self.inform("Your trash is empty!")?;
+            } else if empty {
+                self.inform(format!("No results for '{}'.", pattern.as_str()).as_str())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Permenantly delete a target files version from the trash, shredding it
+    /// if `shred` is set, as `remove_stored` does.
+    /// The item's directory is only removed once it has no remaining versions.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.purge("Bilbo.txt", Some("all"), false, false)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when no items have the name `target_name`.
+    ///
+    pub fn purge(&mut self, target_name: &str, target_version: Option<&str>, shred: bool, verbose: bool) -> Result<()> {
+        let entries = self.cache.pop(|key| key.is_named(target_name), VersionPredicate::try_from(target_version)?)
+            .map_err(|_| Error::MissingTarget(target_name.to_string()))?;
+
+        for (empty, entry) in entries {
+            self.remove_popped(&entry, empty, shred, verbose)?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Delete everything of the types `types` with every one of `tags` in the
+    /// trash, once confirmed as `confirm_empty` asks, shredding it if `shred` is
+    /// set, as `remove_stored` does, then report how much was freed.
+    /// When `dry_run` is set, only what would be freed is reported.
+    /// Stored files which can not be read are noted and not counted.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.empty(&TypeFilter::All, &[], true, false, false, false)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when confirmation is needed without a terminal to ask for it.
+    ///
+    pub fn empty(&mut self, types: &TypeFilter, tags: &[String], force: bool, shred: bool, verbose: bool, dry_run: bool) -> Result<()> {
+        let mut location: PathBuf;
+        let mut skipped: Vec<PathBuf> = vec![];
+        let selected: Vec<&Entry> = self.cache.entries().iter().filter(|entry| types.matches(entry) && entry.has_tags(tags)).collect();
+        let count: usize = selected.len();
+        let versions: usize = selected.iter().map(|entry| entry.history().len()).sum();
+        let freed: u64 = selected.iter()
+            .map(|entry| measure(&self.paths.data.join(entry.uuid().to_string()), &mut skipped))
+            .sum();
+        let formatter: Formatter = self.formatter();
+        let summary: String = format!(
+            "{} entries, {} versions, {} freed",
+            formatter.number(count as u64),
+            formatter.number(versions as u64),
+            self.color(&formatter.size(freed), &Style::new().bold())
+        );
+
+        for path in &skipped {
+            self.inform(format!("warning: could not read '{}', so it is not counted", path.display()).as_str())?;
+        }
+
+        if dry_run {
+            return self.inform(format!("Would empty {}", summary).as_str());
+        }
+
+        let question: String = format!("Permanently delete {} entries ({})?", formatter.number(count as u64), formatter.size(freed));
+
+        if count > 0 && !self.confirm_empty(question.as_str(), count, force)? {
+            return Ok(());
+        }
+
+        for (_, entry) in self.cache.pop_where(|entry| types.matches(entry) && entry.has_tags(tags), VersionPredicate::All)? {
+            location = PathBuf::from(&self.paths.data);
+            location.push(entry.uuid().to_string());
+
+            self.remove_stored(&location, shred, verbose)?;
+        }
+
+        self.inform(format!("Emptied {}", summary).as_str())?;
+
+        Ok(())
+    }
+
+    ///
+    /// Permanently delete the versions chosen by `version`, or every version
+    /// when it is not given, of the items satisfying `entry_predicate`, once
+    /// confirmed as `confirm_empty` asks, shredding them if `shred` is set, as
+    /// `remove_stored` does.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.empty_matching(|entry| entry.key().is_named("old_video.mp4"), Some("oldest"), true, false, false)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when no items satisfy `entry_predicate`, or none of their
+    /// versions satisfy `version`, and when confirmation is needed without a
+    /// terminal to ask for it.
+    ///
+    pub fn empty_matching<EP>(&mut self, entry_predicate: EP, version: Option<&str>, force: bool, shred: bool, verbose: bool) -> Result<()>
+    where
+        EP: Fn(&Entry) -> bool
+    {
+        let version: Option<&str> = version.or(Some("all"));
+        let selected: Vec<Entry> = self.cache.select(&entry_predicate, VersionPredicate::try_from(version)?)?;
+        let count: usize = selected.iter().map(|entry| entry.history().len()).sum();
+        let question: String = format!(
+            "This will permanently delete {} versions of {} items, continue?",
+            self.formatter().number(count as u64),
+            self.formatter().number(selected.len() as u64)
+        );
+
+        if !self.confirm_empty(question.as_str(), selected.len(), force)? {
+            return Ok(());
+        }
+
+        for (empty, entry) in self.cache.pop_where(entry_predicate, VersionPredicate::try_from(version)?)? {
+            self.remove_popped(&entry, empty, shred, verbose)?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Permanently delete all but the `kept` newest versions of the items
+    /// satisfying `entry_predicate`, reporting how many were deleted from
+    /// each, shredding them if `shred` is set, as `remove_stored` does.
+    /// Items with no more than `kept` versions are left alone, and those
+    /// left without versions are removed.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.prune(|_| true, 3, false, false)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails if any version can not be removed.
+    ///
+    pub fn prune<EP>(&mut self, entry_predicate: EP, kept: usize, shred: bool, verbose: bool) -> Result<()>
+    where
+        EP: Fn(&Entry) -> bool
+    {
+        //
+        // Having nothing beyond the newest versions is not an error.
+        //
+        let entries: Vec<(bool, Entry)> = match self.cache.pop_where(entry_predicate, VersionPredicate::AllButNewest(kept)) {
+            Err(Error::MissingTarget(_)) | Err(Error::MissingTargetPredicate) => vec![],
+            result => result?
+        };
+
+        for (empty, entry) in entries {
+            self.remove_popped(&entry, empty, shred, verbose)?;
+            self.inform(format!(
+                "pruned {} versions of '{}'",
+                self.formatter().number(entry.history().len() as u64),
+                entry.key().name().to_string_lossy()
+            ).as_str())?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Permanently delete the versions of every item of the types `types` with
+    /// every one of `tags` which were trashed more than `duration` ago,
+    /// reporting how many were deleted and how much space they took, shredding
+    /// them if `shred` is set, as `remove_stored` does.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.expire("30d", &TypeFilter::All, &[], false, false)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails if `duration` is not a number followed by `s`, `m`, `h`, `d` or
+    /// `w`.
+    ///
+    pub fn expire(&mut self, duration: &str, types: &TypeFilter, tags: &[String], shred: bool, verbose: bool) -> Result<()> {
+        let mut location: PathBuf;
+        let mut purged: usize = 0;
+        let mut freed: u64 = 0;
+        let time: DateTime<Utc> = Utc::now()
+            .checked_sub_signed(parse_duration(duration)?)
+            .ok_or_else(|| Error::InvalidDuration(duration.to_string()))?;
+
+        //
+        // Having nothing old enough to delete is not an error.
+        //
+        let entries: Vec<(bool, Entry)> = match self.cache.pop_where(|entry| types.matches(entry) && entry.has_tags(tags), VersionPredicate::OlderThan(time)) {
+            Err(Error::MissingTarget(_)) | Err(Error::MissingTargetPredicate) => vec![],
+            result => result?
+        };
+
+        for (empty, entry) in entries {
+            location = PathBuf::from(&self.paths.data);
+            location.push(entry.uuid().to_string());
+            purged += entry.history().len();
+            freed += entry.history().iter()
+                .filter_map(|version| size(&location.join(version.display())).ok())
+                .sum::<u64>();
+
+            self.remove_popped(&entry, empty, shred, verbose)?;
+        }
+
+        self.inform(format!("purged {} versions older than {}, freeing {}", self.formatter().number(purged as u64), duration, self.formatter().size(freed)).as_str())?;
+
+        Ok(())
+    }
+
+    ///
+    /// Permanently delete the versions trashed more than `retention_days`
+    /// ago, if the setting is enabled, noting how many were deleted, and
+    /// commit the cache at once, as their data is already gone whether or not
+    /// the action goes on to commit it.
+    /// Versions which can not be removed are noted and kept in the trash.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.retain()?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails if the notes can not be written or the cache can not be
+    /// committed.
+    ///
+    fn retain(&mut self) -> Result<()> {
+        let days: u64 = self.settings.retention_days();
+
+        if days == 0 {
+            return Ok(());
+        }
+
+        //
+        // A retention too long to subtract from now keeps everything.
+        //
+        let time: DateTime<Utc> = match parse_duration(&format!("{}d", days)).ok().and_then(|duration| Utc::now().checked_sub_signed(duration)) {
+            Some(time) => time,
+            None => return Ok(())
+        };
+        let mut purged: usize = 0;
+
+        for entry in self.cache.select(|_| true, VersionPredicate::OlderThan(time)).unwrap_or_default() {
+            let uuid: Uuid = *entry.uuid();
+            let location: PathBuf = self.paths.data.join(uuid.to_string());
+
+            for version in entry.history() {
+                let stored: PathBuf = location.join(version.display());
+
+                //
+                // A version only leaves the cache once it's data is gone, or
+                // was already missing, so that one which can not be removed
+                // is still listed rather than orphaned.
+                //
+                if symlink_metadata(&stored).is_ok() {
+                    if let Err(error) = self.remove_stored(&stored, false, false) {
+                        self.inform(format!("warning: could not auto-purge '{}', {}", entry.key().name().to_string_lossy(), error).as_str())?;
+
+                        continue;
+                    }
+                }
+
+                let popped: Vec<(bool, Entry)> = self.cache.pop_where(|candidate| candidate.uuid() == &uuid, VersionPredicate::Specific(&version.display()))?;
+
+                purged += 1;
+
+                if popped.iter().any(|(empty, _)| *empty) {
+                    remove_path(&location).unwrap_or_default();
+                }
+            }
+        }
+
+        if purged > 0 {
+            self.cache.end()?;
+            self.inform(format!("auto-purged {} old versions", self.formatter().number(purged as u64)).as_str())?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Permanently remove the stored versions of `entry`, which were popped
+    /// from the cache, or it's whole directory if `empty` is set because
+    /// no versions are left, as `remove_stored` does.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.remove_popped(&entry, true, false, false)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails if any version can not be removed.
+    ///
+    fn remove_popped(&self, entry: &Entry, empty: bool, shred: bool, verbose: bool) -> Result<()> {
+        let location: PathBuf = self.paths.data.join(entry.uuid().to_string());
+
+        if empty {
+            return self.remove_stored(&location, shred, verbose);
+        }
+
+        for version in entry.history() {
+            self.remove_stored(&location.join(version.display()), shred, verbose)?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Permanently remove the stored copy at `location`, or overwrite it's
+    /// files before removing them if `shred` is set, naming each file
+    /// overwritten if `verbose` is set.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.remove_stored(Path::new("./data/56f188aa-1122-41a1-b2f2-e2f0008db54a"), true, false)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails if the copy can not be overwritten or removed.
+    ///
+    fn remove_stored(&self, location: &Path, shred_files: bool, verbose: bool) -> Result<()> {
+        if !shred_files {
+            return remove_path(location).context("removing", location);
+        }
+
+        shred(location, |file, size| if verbose {
+            self.inform(format!("shredded '{}', {}", file.display(), self.formatter().size(size)).as_str())
+        } else {
+            Ok(())
+        })
+    }
+
+    ///
+    /// Show the summary recorded in the cache's header, without counting the
+    /// entries.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.status()?;
+    /// ```
+    ///
+    pub fn status(&self) -> Result<()> {
+        let header: &Header = self.cache.header();
+        let formatter: Formatter = self.formatter();
+        let unknown = || String::from("unknown");
+
+        self.stdout.write_line(format!("entries:   {}", header.entries().map(|entries| formatter.number(entries as u64)).unwrap_or_else(unknown)).as_str())?;
+        self.stdout.write_line(format!("versions:  {}", header.versions().map(|versions| formatter.number(versions as u64)).unwrap_or_else(unknown)).as_str())?;
+        self.stdout.write_line(format!("size:      {}", header.bytes().map(|bytes| formatter.size(bytes)).unwrap_or_else(unknown)).as_str())?;
+        self.stdout.write_line(format!("created:   {}", header.created_at().map(|time| time.to_string()).unwrap_or_else(unknown)).as_str())?;
+        self.stdout.write_line(format!("compacted: {}", header.compacted_at().map(|time| time.to_string()).unwrap_or_else(unknown)).as_str())?;
+        self.stdout.write_line(format!("platform:  {}", header.platform().cloned().unwrap_or_else(unknown)).as_str())?;
+
+        Ok(())
+    }
+
+    ///
+    /// Summarise the trash by counting it's entries and versions and measuring
+    /// them in the data, along with it's oldest and newest versions and the
+    /// item taking the most space.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.stats()?;
+    /// ```
+    ///
+    pub fn stats(&self) -> Result<()> {
+        let label_style = Style::new().bold();
+        let name_style = Style::new().bold();
+        let origin_style = Style::new().dim().italic();
+        let formatter: Formatter = self.formatter();
+        let entries: &[Entry] = self.cache.entries();
+        let versions: Vec<&Version> = entries.iter().flat_map(|entry| entry.history().iter()).collect();
+        let sizes: Vec<u64> = entries.iter()
+            .map(|entry| entry.history().iter().filter_map(|version| self.stored_size(entry, version)).sum())
+            .collect();
+        let oldest: Option<&DateTime<Utc>> = versions.iter().map(|version| version.timestamp()).min();
+        let newest: Option<&DateTime<Utc>> = versions.iter().map(|version| version.timestamp()).max();
+        let largest: Option<(&Entry, u64)> = entries.iter().zip(sizes.iter().cloned()).max_by_key(|(_, size)| *size);
+        let none = || String::from("none");
+
+        for (label, value) in [
+            ("entries: ", formatter.number(entries.len() as u64)),
+            ("versions:", formatter.number(versions.len() as u64)),
+            ("on disk: ", formatter.size(sizes.iter().sum())),
+            ("oldest:  ", oldest.map(|time| time.to_string()).unwrap_or_else(none)),
+            ("newest:  ", newest.map(|time| time.to_string()).unwrap_or_else(none)),
+            ("largest: ", largest.map(|(entry, size)| format!(
+                "{} {} {} ({})",
+                self.color(&entry.key().name().to_string_lossy(), &name_style),
+                self.unicode("\u{2190}", "<-"),
+                self.color(&entry.key().origin().to_string_lossy(), &origin_style),
+                formatter.size(size)
+            )).unwrap_or_else(none))
+        ] {
+            self.stdout.write_line(format!("{} {}", self.color(label, &label_style), value).as_str())?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Check the counts recorded in the cache's header against it's entries,
+    /// reporting each that has drifted.
+    /// The header is corrected when the cache is written.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.doctor()?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails if any count has drifted.
+    ///
+    pub fn doctor(&self) -> Result<()> {
+        let recorded: &Header = self.cache.header();
+        let actual: Header = Header::describe(self.cache.entries(), recorded);
+        let mut drifted: Vec<String> = vec![];
+
+        if recorded.format().is_none() {
+            self.stdout.write_line("cache has no header, one will be written")?;
+            return Ok(());
+        }
+
+        for (count, recorded, actual) in [
+            ("entries", recorded.entries().map(|entries| entries as u64), actual.entries().map(|entries| entries as u64)),
+            ("versions", recorded.versions().map(|versions| versions as u64), actual.versions().map(|versions| versions as u64)),
+            ("bytes", recorded.bytes(), actual.bytes())
+        ] {
+            match (recorded, actual) {
+                (Some(recorded), Some(actual)) if recorded != actual => {
+                    self.stdout.write_line(format!("{}: header records {}, found {}", count, recorded, actual).as_str())?;
+                    drifted.push(count.to_string());
+                },
+                (None, _) => self.stdout.write_line(format!("{}: not recorded", count).as_str())?,
+                _ => ()
+            }
+        }
+
+        if drifted.is_empty() {
+            self.stdout.write_line("cache is consistent")?;
+            Ok(())
+        } else {
+            Err(Error::InconsistentCache(drifted))
+        }
+    }
+
+    ///
+    /// Check the trash as `Cache::validate` does, without changing it,
+    /// reporting each issue found.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.check()?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails if any issue is found.
+    ///
+    pub fn check(&self) -> Result<()> {
+        let issues: Vec<Issue> = self.cache.validate(&self.paths.data);
+
+        for issue in issues.iter() {
+            self.stdout.write_line(issue.describe().as_str())?;
+        }
+
+        if issues.is_empty() {
+            self.stdout.write_line("the trash is intact")?;
+            Ok(())
+        } else {
+            Err(Error::FailedCheck(issues.len()))
+        }
+    }
+
+    ///
+    /// Move the trash, with it's cache, settings and data, to `directory`,
+    /// creating it if it is missing, and record the new location in the
+    /// settings of the home directory.
+    /// Moving to another filesystem copies the trash and then removes the
+    /// original.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.move_trash(Path::new("/mnt/big/trash"))?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when `directory` already holds a trash, when it lies inside the
+    /// trash, or the trash inside it's data, and if any part of the trash
+    /// can not be moved.
+    ///
+    pub fn move_trash(&mut self, directory: &Path) -> Result<()> {
+        let home: PathBuf = resolve(&home_directory())?;
+        let destination: Paths = Paths::new(resolve(directory)?);
+
+        if destination.root == self.paths.root {
+            return self.inform(format!("the trash is already in '{}'", destination.root.display()).as_str());
+        }
+
+        if destination.root.starts_with(&self.paths.root) {
+            Err(Error::InvalidConfiguration(destination.root.display().to_string(), self.paths.root.display().to_string()))?;
+        }
+
+        if self.paths.root.starts_with(&destination.data) {
+            Err(Error::InvalidConfiguration(self.paths.root.display().to_string(), destination.data.display().to_string()))?;
+        }
+
+        destination.check(&home)?;
+
+        //
+        // The settings in the home directory only record the location of a
+        // trash kept elsewhere, so do not make it a trash of it's own.
+        //
+        if destination.stores().iter().any(|store| symlink_metadata(store).is_ok() && !(destination.root == home && *store == &destination.settings)) {
+            Err(Error::ExistingTrash(destination.root.display().to_string()))?;
+        }
+
+        create_dir_all(&destination.root).context("creating", &destination.root)?;
+
+        //
+        // The cache is written before it is moved, and the data, which is the
+        // most likely to fail, is moved first so that a failure leaves the
+        // trash where it was.
+        // The location left in the home directory is only replaced once
+        // everything else has moved there.
+        //
+        self.cache.end()?;
+
+        move_path(&self.paths.data, &destination.data).context("moving", &self.paths.data)?;
+        move_path(&self.paths.cache, &destination.cache).context("moving", &self.paths.cache)?;
+
+        if destination.root == home && symlink_metadata(&destination.settings).is_ok() {
+            remove_path(&destination.settings).context("removing", &destination.settings)?;
+        }
+
+        move_path(&self.paths.settings, &destination.settings).context("moving", &self.paths.settings)?;
+
+        if symlink_metadata(&self.paths.log).is_ok() {
+            move_path(&self.paths.log, &destination.log).context("moving", &self.paths.log)?;
+        }
+
+        if destination.root != home {
+            let mut pointer: Settings = Settings::default();
+
+            pointer.set_location(Some(destination.root.clone()));
+            create_dir_all(&home).context("creating", &home)?;
+            pointer.save(&home.join("settings.json")).context("writing", &home)?;
+        }
+
+        if self.paths.root != home {
+            remove_dir(&self.paths.root).unwrap_or_default();
+        }
+
+        self.cache = Cache::new(&destination.cache)?;
+        self.paths = destination;
+
+        self.inform(format!("moved the trash to '{}'", self.paths.root.display()).as_str())
+    }
+
+    ///
+    /// Find what the cache and the data disagree on, reporting each
+    /// disagreement: paths in the data which no version is stored in, and
+    /// versions whose stored copies are missing.
+    /// Then, after asking unless `yes` is set, remove the paths and forget the
+    /// versions, along with any item left without versions, or only count
+    /// them when `dry_run` is set.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.gc(false, false)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails if the data can not be read, or an orphaned path removed, and
+    /// when there is no terminal to ask and `yes` is not set.
+    ///
+    pub fn gc(&mut self, yes: bool, dry_run: bool) -> Result<()> {
+        let mut orphans: Vec<PathBuf> = vec![];
+        let mut missing: Vec<(Uuid, String)> = vec![];
+
+        //
+        // Anything in the data which is not named by the UUID of an entry, or
+        // by a version of that entry, is stored for nothing.
+        // UUIDs are only accepted in the form their directories are named in.
+        //
+        for child in read_dir(&self.paths.data).context("reading", &self.paths.data)? {
+            let path: PathBuf = child.context("reading", &self.paths.data)?.path();
+            let entry: Option<&Entry> = path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| Uuid::parse_str(name).ok().filter(|uuid| uuid.to_string() == name))
+                .and_then(|uuid| self.cache.entry(&uuid));
+
+            match entry {
+                Some(entry) if path.is_dir() => {
+                    for version in read_dir(&path).context("reading", &path)? {
+                        let version: PathBuf = version.context("reading", &path)?.path();
+
+                        if !entry.history().iter().any(|known| version.file_name() == Some(OsStr::new(&known.display()))) {
+                            orphans.push(version);
+                        }
+                    }
+                },
+                _ => orphans.push(path)
+            }
+        }
+
+        for entry in self.cache.entries() {
+            for version in entry.history() {
+                if self.stored_size(entry, version).is_none() {
+                    missing.push((*entry.uuid(), version.display()));
+                }
+            }
+        }
+
+        for orphan in orphans.iter() {
+            self.stdout.write_line(format!("orphaned: '{}' is not referenced by the cache", orphan.display()).as_str())?;
+        }
+
+        for (uuid, version) in missing.iter() {
+            if let Some(entry) = self.cache.entry(uuid) {
+                self.stdout.write_line(format!("missing:  {} {} {}, version {}, has no stored copy", entry.key().name().to_string_lossy(), self.unicode("\u{2190}", "<-"), entry.key().origin().display(), version).as_str())?;
+            }
+        }
+
+        if orphans.is_empty() && missing.is_empty() {
+            return self.inform("the cache and the data agree");
+        }
+
+        if dry_run {
+            return self.inform(format!("would remove {} orphaned paths and forget {} missing versions", self.formatter().number(orphans.len() as u64), self.formatter().number(missing.len() as u64)).as_str());
+        }
+
+        if !orphans.is_empty() && (yes || self.confirm(format!("Remove {} orphaned paths from the data?", self.formatter().number(orphans.len() as u64)).as_str())?) {
+            for orphan in orphans.iter() {
+                remove_path(orphan).context("removing", orphan)?;
+            }
+
+            self.inform(format!("removed {} orphaned paths", self.formatter().number(orphans.len() as u64)).as_str())?;
+        }
+
+        if !missing.is_empty() && (yes || self.confirm(format!("Forget {} versions without stored copies?", self.formatter().number(missing.len() as u64)).as_str())?) {
+            for (uuid, version) in missing.iter() {
+                let emptied: bool = self.cache.pop_where(|entry| entry.uuid() == uuid, VersionPredicate::Specific(version))?
+                    .iter()
+                    .any(|(empty, _)| *empty);
+
+                //
+                // An item's directory may remain, empty, after it's last
+                // version went missing.
+                //
+                if emptied {
+                    remove_dir(self.paths.data.join(uuid.to_string())).unwrap_or_default();
+                }
+            }
+
+            self.inform(format!("forgot {} missing versions", self.formatter().number(missing.len() as u64)).as_str())?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Store each version of a file which is identical to the version before
+    /// it as a hard link to that version, reporting how much space was
+    /// reclaimed.
+    /// Restoring either version gives it a copy of it's own again.
+    /// Directories are stored incrementally as they are trashed instead, when
+    /// the `dedup_dirs` setting is enabled.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.dedup()?;
+    /// ```
+    ///
+    pub fn dedup(&self) -> Result<()> {
+        let mut linked: usize = 0;
+        let mut reclaimed: u64 = 0;
+
+        //
+        // Hard links can only be told apart before restoring on Unix, so
+        // versions are never linked elsewhere.
+        //
+        if cfg!(unix) {
+            for entry in self.cache.entries() {
+                let directory: PathBuf = self.paths.data.join(entry.uuid().to_string());
+
+                for pair in entry.history().windows(2) {
+                    if pair.iter().any(|version| version.metadata().kind() != Some(Kind::File)) {
+                        continue;
+                    }
+
+                    let saved: u64 = link_identical(&directory.join(pair[0].display()), &directory.join(pair[1].display()));
+
+                    if saved > 0 {
+                        linked += 1;
+                        reclaimed += saved;
+                    }
+                }
+            }
+        }
+
+        self.inform(format!("linked {} identical versions, reclaiming {}", self.formatter().number(linked as u64), self.formatter().size(reclaimed)).as_str())
+    }
+
+    ///
+    /// Set the setting `key` to `value`, as `Settings::set` reads it, and save
+    /// the settings.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.configure("use_unicode", "true")?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when there is no setting `key`, when `value` is not valid for it
+    /// and when the settings can not be saved.
+    ///
+    pub fn configure(&mut self, key: &str, value: &str) -> Result<()> {
+        self.settings.set(key, value)?;
+        self.settings.save(&self.paths.settings).context("writing", &self.paths.settings)
+    }
+
+    ///
+    /// Write every setting and it's value, as JSON, one per line.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.write_settings()?;
+    /// ```
+    ///
+    pub fn write_settings(&self) -> Result<()> {
+        for (key, value) in self.settings.values()? {
+            self.stdout.write_line(format!("{} = {}", key, value).as_str())?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Set the name shown in place of the name of every item whose name or
+    /// UUID is `target`.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.set_display_name("REPORT~1.DOC", "Report.doc")?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when no item has the name or UUID `target`.
+    ///
+    pub fn set_display_name(&mut self, target: &str, display_name: &str) -> Result<()> {
+        self.cache.set_display_name(
+            |entry| entry.key().is_named(target) || entry.uuid().to_string() == target,
+            display_name.to_string()
+        ).map_err(|_| Error::MissingTarget(target.to_string()))
+    }
+
+    ///
+    /// Show the timeline of the items with the name or UUID `target` -- when
+    /// each of it's versions was trashed and restored, including items which
+    /// have since been emptied, as JSON if `json` is set.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.show("Bilbo.txt", false)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when no items, current or emptied, have the name or UUID
+    /// `target`.
+    ///
+    pub fn show(&self, target: &str, json: bool) -> Result<()> {
+        let is_target = |entry: &&Entry| entry.key().is_named(target) || entry.uuid().to_string() == target;
+        let entries: Vec<&Entry> = self.cache.entries().iter().filter(is_target)
+            .chain(self.cache.retired().iter().filter(is_target))
+            .collect();
+
+        if entries.is_empty() {
+            Err(Error::MissingTarget(target.to_string()))?;
+        }
+
+        if json {
+            let entries: Vec<JsonEntry> = entries.iter()
+                .map(|entry| JsonEntry {
+                    name: entry.key().name().to_string_lossy(),
+                    origin: entry.key().origin().to_string_lossy(),
+                    uuid: entry.uuid(),
+                    display_name: entry.display_name(),
+                    tags: entry.tags(),
+                    history: entry.history(),
+                    events: Some(entry.events())
+                })
+                .collect();
+
+            self.stdout.write_line(to_string(&entries)?.as_str())?;
+
+            return Ok(());
+        }
+
+        let event_style = Style::new().dim();
+
+        for entry in entries {
+            let mut timeline: Vec<(DateTime<Utc>, String)> = vec![];
+
+            //
+            // Restored versions are no longer in the history, but they were
+            // still trashed at the time of their timestamp.
+            //
+            for version in entry.history() {
+                timeline.push((*version.timestamp(), match version.metadata().link_target() {
+                    Some(link_target) => format!("trashed, linking to {}", link_target),
+                    None => String::from("trashed")
+                }));
+            }
+
+            for event in entry.events() {
+                if !entry.history().iter().any(|version| version.timestamp() == event.version()) {
+                    timeline.push((*event.version(), String::from("trashed")));
+                }
+
+                timeline.push((*event.restored_at(), format!("restored {} to {}", event.version(), event.destination().display())));
+            }
+
+            timeline.sort_by_key(|(time, _)| *time);
+            timeline.dedup();
+
+            self.write_header(entry, &ListOptions::default())?;
+
+            for (time, description) in timeline {
+                self.stdout.write_line(format!("    {} {}", time, self.color(description.as_str(), &event_style)).as_str())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Write the detailed representation of an entry -- it's name, origin and
+    /// versions (newest first), as configured by `options`.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.write_entry(&entry, &ListOptions::default())?;
+    /// ```
+    ///
+    pub fn write_entry(&self, entry: &Entry, options: &ListOptions) -> Result<()> {
+        let origin_style = Style::new().dim().italic();
+        let version_style = Style::new();
+        let details_style = Style::new().dim();
+        let mut prefix: String;
+
+        self.write_header(entry, options)?;
+
+        for (index, version) in entry.history().iter().rev().enumerate() {
+            let time: String = self.settings.timestamp_format()
+                .and_then(|format| version.format(format))
+                .unwrap_or_else(|| version.display());
+
+            prefix = if options.indices {
+                format!("{} {}", index + 1, self.unicode("\u{2192}", "->"))
+            } else {
+                String::from(self.unicode("\u{2192}", "->"))
+            };
+
+            if let Some(link_target) = version.metadata().link_target() {
+                self.stdout.write_line(format!("    {} {} {} {}", prefix, self.color(&time, &version_style), self.unicode("\u{21aa}", "@"), self.color(link_target, &origin_style)).as_str())?;
+            } else {
+                self.stdout.write_line(format!("    {} {}", prefix, self.color(&time, &version_style)).as_str())?;
+            }
+
+            if let Some(reason) = version.metadata().reason() {
+                self.stdout.write_line(format!("      {}", self.color(reason, &details_style)).as_str())?;
+            }
+
+            //
+            // Versions trashed before their type, size and mode were recorded
+            // have nothing to show for them, and a version missing from the
+            // data has no size on disk.
+            //
+            if options.verbose {
+                let mut details: Vec<String> = vec![];
+
+                if let Some(kind) = version.metadata().kind() {
+                    details.push(kind.name().to_string());
+                }
+
+                if let Some(size) = version.metadata().size() {
+                    details.push(self.listed_size(size, options));
+                }
+
+                details.push(format!("{} on disk", self.stored_size(entry, version).map_or(String::from("?"), |size| self.listed_size(size, options))));
+
+                if let Some(mode) = version.metadata().mode() {
+                    details.push(format!("mode {:04o}", mode & 0o7777));
+                }
+
+                if let Some(transaction) = version.metadata().transaction() {
+                    details.push(format!("transaction {}", transaction));
+                }
+
+                if !details.is_empty() {
+                    self.stdout.write_line(format!("      {}", self.color(details.join(", ").as_str(), &details_style)).as_str())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// List the transactions with versions left in the trash, oldest first,
+    /// with when they began and how many of their versions are left.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.transactions()?;
+    /// ```
+    ///
+    pub fn transactions(&self) -> Result<()> {
+        let transaction_style = Style::new().dim();
+        let transactions: Vec<(Uuid, DateTime<Utc>, usize)> = self.recorded_transactions();
+
+        if transactions.is_empty() {
+            self.inform("No transactions are left in the trash!")?;
+        }
+
+        for (transaction, began, count) in transactions {
+            self.stdout.write_line(format!(
+                "  * {} {} ({} versions)",
+                began.format("%Y-%m-%d %H:%M:%S UTC"),
+                self.color(&transaction.to_string(), &transaction_style),
+                self.formatter().number(count as u64)
+            ).as_str())?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Collect the transactions recorded by the versions in the trash, with
+    /// the time of their first version and how many versions they have,
+    /// oldest first.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let transactions: Vec<(Uuid, DateTime<Utc>, usize)> = app.recorded_transactions();
+    /// ```
+    ///
+    fn recorded_transactions(&self) -> Vec<(Uuid, DateTime<Utc>, usize)> {
+        let mut transactions: Vec<(Uuid, DateTime<Utc>, usize)> = vec![];
+
+        for version in self.cache.entries().iter().flat_map(|entry| entry.history()) {
+            if let Some(transaction) = version.metadata().transaction() {
+                match transactions.iter_mut().find(|(other, _, _)| other == transaction) {
+                    Some((_, began, count)) => {
+                        *began = (*began).min(*version.timestamp());
+                        *count += 1;
+                    },
+                    None => transactions.push((*transaction, *version.timestamp(), 1))
+                }
+            }
+        }
+
+        //
+        // Ties are broken by UUID so that the order is always the same.
+        //
+        transactions.sort_by_key(|(transaction, began, _)| (*began, *transaction));
+
+        transactions
+    }
+
+    ///
+    /// Measure the stored copy of a version of `entry` in the data, which is
+    /// `None` if it is missing.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let stored: Option<u64> = app.stored_size(&entry, &version);
+    /// ```
+    ///
+    pub fn stored_size(&self, entry: &Entry, version: &Version) -> Option<u64> {
+        size(&self.paths.data.join(entry.uuid().to_string()).join(version.display())).ok()
+    }
+
+    ///
+    /// Get the size on disk of all versions of `entry`, using the sizes
+    /// recorded as they were trashed, which are those of their stored copies,
+    /// and only measuring versions trashed before sizes were recorded.
+    /// Versions which can not be measured count as empty.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let size: u64 = app.entry_size(&entry);
+    /// ```
+    ///
+    pub fn entry_size(&self, entry: &Entry) -> u64 {
+        entry.history().iter()
+            .filter_map(|version| version.metadata().size().or_else(|| self.stored_size(entry, version)))
+            .sum()
+    }
+
+    ///
+    /// Format a size for a listing, as a number of bytes when `options` asks
+    /// for them.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let size: String = app.listed_size(1024, &ListOptions::default());
+    /// ```
+    ///
+    fn listed_size(&self, size: u64, options: &ListOptions) -> String {
+        if options.bytes {
+            size.to_string()
+        } else {
+            self.formatter().size(size)
+        }
+    }
+
+    ///
+    /// Write the first lines of the detailed representation of an entry --
+    /// it's name and origin, along with it's UUID and notes about it's type, as
+    /// configured by `options`.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.write_header(&entry, &ListOptions::default())?;
+    /// ```
+    ///
+    pub fn write_header(&self, entry: &Entry, options: &ListOptions) -> Result<()> {
+        let name_style = Style::new().bold();
+        let raw_name_style = Style::new().dim();
+        let origin_style = Style::new().dim().italic();
+        let size: String = if options.size {
+            format!(" {}", self.color(format!("({})", self.listed_size(self.entry_size(entry), options)).as_str(), &raw_name_style))
+        } else {
+            String::new()
+        };
+
+        if let Some(display_name) = entry.display_name() {
+            self.stdout.write_line(format!("  {} {} ({}) {} {}{}", self.unicode("\u{2022}", "*"), self.color(display_name, &name_style), self.color(&entry.key().name().to_string_lossy(), &raw_name_style), self.unicode("\u{2190}", "<-"), self.color(&entry.key().origin().to_string_lossy(), &origin_style), size).as_str())?;
+        } else {
+            self.stdout.write_line(format!("  {} {} {} {}{}", self.unicode("\u{2022}", "*"), self.color(&entry.key().name().to_string_lossy(), &name_style), self.unicode("\u{2190}", "<-"), self.color(&entry.key().origin().to_string_lossy(), &origin_style), size).as_str())?;
+        }
+
+        if options.verbose {
+            self.stdout.write_line(format!("    {}", self.color(entry.uuid().to_string().as_str(), &raw_name_style)).as_str())?;
+        }
+
+        if !entry.tags().is_empty() {
+            self.stdout.write_line(format!("    {}", self.color(format!("tags: {}", entry.tags().join(", ")).as_str(), &raw_name_style)).as_str())?;
+        }
+
+        if entry.has_mixed_kinds() {
+            self.stdout.write_line(format!("    {}", self.color("mixed types", &raw_name_style)).as_str())?;
+        } else if let Some(kind) = entry.kind().filter(Kind::is_special) {
+            self.stdout.write_line(format!("    {}", self.color(kind.name(), &raw_name_style)).as_str())?;
+        } else if entry.kind().is_none() && !options.types.is_all() {
+            self.stdout.write_line(format!("    {}", self.color("type unknown", &raw_name_style)).as_str())?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Ask the user a yes or no question, defaulting to no.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let answer: bool = app.confirm("Restore this item?")?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when there is no terminal to ask.
+    ///
+    pub fn confirm(&self, question: &str) -> Result<bool> {
+        if !self.stdout.is_term() {
+            Err(Error::NotATerminal)?;
+        }
+
+        self.stdout.write_str(format!("{} [y/N] ", question).as_str())?;
+
+        let answer: String = self.stdout.read_line()?;
+
+        Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+    }
+
+    ///
+    /// Ask the user `question` before permanently deleting `count` items,
+    /// unless `force` is set or the `confirm_empty` setting is disabled.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let answer: bool = app.confirm_empty("Permanently delete 3 entries (1.2 MB)?", 3, false)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when there is no terminal to ask, rather than waiting for an
+    /// answer which can not come.
+    ///
+    fn confirm_empty(&self, question: &str, count: usize, force: bool) -> Result<bool> {
+        if force || !self.settings.confirm_empty() {
+            return Ok(true);
+        }
+
+        if !self.stdout.is_term() {
+            Err(Error::UnconfirmedEmpty(count))?;
+        }
+
+        self.confirm(question)
+    }
+
+    ///
+    /// Ask the user to choose one of `count` numbered options, asking again
+    /// until they choose a valid option or choose nothing.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let choice: Option<usize> = app.choose("Restore which version?", 3)?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when there is no terminal to ask.
+    ///
+    pub fn choose(&self, question: &str, count: usize) -> Result<Option<usize>> {
+        if !self.stdout.is_term() {
+            Err(Error::NotATerminal)?;
+        }
+
+        loop {
+            self.stdout.write_str(format!("{} [1-{}, none] ", question, count).as_str())?;
+
+            let answer: String = self.stdout.read_line()?;
+
+            match answer.trim().parse::<usize>() {
+                Ok(choice) if choice >= 1 && choice <= count => return Ok(Some(choice)),
+                _ if answer.trim().is_empty() || answer.trim() == "none" => return Ok(None),
+                _ => ()
+            }
+        }
+    }
+
+    ///
+    /// Get a formatter for numbers and sizes, following the `size_units` and
+    /// `number_locale` settings.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let size: String = app.formatter().size(1024);
+    /// ```
+    ///
+    pub fn formatter(&self) -> Formatter {
+        Formatter::new(self.settings.size_units(), self.settings.number_locale())
+    }
+
+    ///
+    /// Write a line of information, such as a summary or a note, unless
+    /// `--quiet` was given, to stderr if `--porcelain` was given.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.inform("restored 2 items, 0 failed")?;
+    /// ```
+    ///
+    pub fn inform(&self, line: &str) -> Result<()> {
+        if !self.quiet {
+            self.chatter().write_line(line)?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Get the terminal for information, which is stderr when `--porcelain`
+    /// keeps stdout for records.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// app.chatter().write_line("3 trashed")?;
+    /// ```
+    ///
+    fn chatter(&self) -> Term {
+        if self.porcelain {
+            Term::stderr()
+        } else {
+            Term::stdout()
+        }
+    }
+
+    ///
+    /// Insert a unicode character if `use_unicode` is enabled, else use a
+    /// default ASCII character.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let output: &'static str = app.unicode("\u{2022}", "*");
+    /// ```
+    ///
+    pub fn unicode<'a>(&self, unicode: &'a str, ascii: &'a str) -> &'a str {
+        if self.settings.use_unicode() {
+            unicode
+        } else {
+            ascii
+        }
+    }
+
+    ///
+    /// Format text with ANSI styles if `--color always` was given, or if
+    /// `--color never` was not, `NO_COLOR` is unset or empty, the `use_colors`
+    /// setting is enabled and stdout is a terminal.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let output: StyledObject<&'static str> = app.color("Bold Face", Style::new().bold());
     /// ```
-    /// let output: StyledObject<&'static str> = app.color("Bold Face", Style::new().bold());
-    /// ```
     ///
     pub fn color<'a>(&self, text: &'a str, color: &Style) -> StyledObject<&'a str> {
-        if self.settings.use_colors() {
-            color.apply_to(text)
+        let enabled: bool = match self.colors {
+            Some(enabled) => enabled,
+            None => {
+                var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && self.settings.use_colors()
+                    && self.stdout.is_term()
+            }
+        };
+
+        if enabled {
+            color.apply_to(text).force_styling(true)
         } else {
             Style::new().apply_to(text)
         }
     }
 }
+
+///
+/// Get the directory in which the trash is kept, unless it was moved, which
+/// is `TMAN_HOME` when it is set and `.tman` inside the home directory
+/// otherwise.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let directory: PathBuf = home_directory();
+/// ```
+///
+fn home_directory() -> PathBuf {
+    match var_os("TMAN_HOME") {
+        Some(directory) if !directory.is_empty() => PathBuf::from(directory),
+        _ => home_dir().unwrap_or_default().join(".tman")
+    }
+}
+
+///
+/// Parse a duration written as a number followed by a unit -- `s`, `m`, `h`,
+/// `d` or `w`.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let duration: chrono::Duration = parse_duration("2w")?;
+/// ```
+/// 
+/// # Errors
+/// 
+/// Fails if the duration is malformed.
+///
+fn parse_duration(duration: &str) -> Result<chrono::Duration> {
+    let invalid = || Error::InvalidDuration(duration.to_string());
+    let split: usize = duration.find(|character: char| !character.is_ascii_digit()).ok_or_else(invalid)?;
+    let count: i64 = duration[..split].parse().map_err(|_| invalid())?;
+    let unit: i64 = match &duration[split..] {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => Err(invalid())?
+    };
+
+    count.checked_mul(unit)
+        .and_then(|seconds| seconds.checked_mul(1000))
+        .map(chrono::Duration::milliseconds)
+        .ok_or_else(invalid)
+}
+
+///
+/// Build the command line interface.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let matches: ArgMatches = app().get_matches();
+/// ```
+///
+fn app() -> App<'static, 'static> {
+    let max_argument_values: u64 = u64::MAX;
+
+    App::new("TMan")
+        .name("tman")
+        .version("1.0.0")
+        .author("Kove Salter <kove.w.o.salter@gmail.com>")
+        .about("Safely manage your trash")
+        .setting(AppSettings::ArgRequiredElseHelp)
+        .setting(AppSettings::AllowInvalidUtf8)
+        .help(
+r#"USAGE:
+    tman <ACTION>
+
+ACTIONS:
+    --delete             -D    <FILE_1>...    Delete specified files, or
+                                              files matching globs (*, ?, [])
+        --verbose        -V                   Explain evicted versions
+        --no-evict                            Fail rather than evict old
+                                              versions to stay under the
+                                              max_trash_size quota
+        --allow-protected                     Delete protected paths, such as
+                                              / and the home directory
+        --allow-special                       Delete sockets, named pipes and
+                                              devices
+        --keep-going                          Delete the rest after a failure,
+                                              then summarise
+        --files-from           <FILE|->       Delete the files listed in FILE,
+                                              one per line, as with
+                                              --keep-going
+            --null       -0                   Separate the files with NULs
+        --pattern        -p    <REGEX>        Delete the files under a
+                                              directory with names matching
+                                              REGEX, as with --keep-going
+            --under            <DIR>          Set the directory (default .)
+            --max-depth        <DEPTH>        Only search DEPTH levels deep
+            --include-dirs                    Match directories too
+        --reason               <TEXT>         Record why the files were
+                                              deleted, shown by --list (also
+                                              --note)
+        --tag                  <TAG>          Tag the files, repeatable
+        --copy                                Store copies of the files,
+                                              leaving them in place
+        --if-older-than        <DURATION>     Skip files modified within
+                                              DURATION (e.g. 30d, 24h, 2w)
+        --porcelain                           Print a JSON line per trashed
+                                              file for scripts:
+                                              {"origin", "uuid", "version"}
+        --yes            -y                   Do not ask before deleting
+                                              directories or many matches of
+                                              --pattern, as is required
+                                              without a terminal
+    --restore            -R    <FILE_1>...    Restore specified files
+        --match          -m    <REGEX>        Restore items matching REGEX
+        --pattern        -p    <REGEX>        Restore items matching REGEX,
+                                              like --match
+            --all                             Allow an empty REGEX
+            --only-dirs                       Only restore directories
+            --only-files                      Only restore files
+            --strict-types                    Skip items of unknown type
+        --last                                Restore what the last delete
+                                              trashed
+        --transaction          <ID>           Restore what a delete trashed,
+                                              from --list --transactions
+    --uuid               -u    <UUID>         Restore the specified item
+    --restore-all                             Restore every item
+        --origin         -o    <PATH>         Set the origin (single file only)
+        --all-origins                         Restore a name from every origin
+        --newest-only                         Restore only the newest version
+                                              of a name from any origin
+        --to             -t    <DIR>          Set the destination directory
+        --version        -v                   Set the revision
+            <VERSION>                         Use a specific version
+            latest                            Use the newest version (default)
+            oldest                            Use the oldest version
+            <N>                               Use the Nth newest version
+            <START>..<END>                    Use the versions trashed from
+                                              START until END (dates or
+                                              timestamps, either optional)
+            all                               Use all versions
+        --interactive    -i                   Confirm each item, or choose
+                                              it's version
+        --no-exec                             Clear execute permissions
+        --inner                <PATH>         Copy PATH out of a directory,
+                                              keeping it in the trash
+        --on-conflict          <POLICY>       Handle existing paths
+            rename                            Add a numeric suffix (default)
+            skip                              Keep the path and the item
+            overwrite                         Replace the path
+    --undo                                    Reverse the last run's deletes
+                                              and restores
+    --list               -L                   List items in the trash
+        --pattern        -p    <REGEX>        Set the search pattern
+        --simple         -p                   Set the simple mode
+            --null       -0                   End the names with NUL
+                                              characters, for xargs -0
+        --verbose        -V                   Show the UUIDs, types, sizes,
+                                              modes and transactions, with
+                                              sizes on disk and a total
+        --watch          -w                   Re-list on changes (q to quit)
+        --missing                             Only show links to missing paths
+        --match-display                       Match display names too
+        --search-reasons                      Match the reasons for deleting
+        --tag                  <TAG>          Only show items tagged TAG
+        --indices                             Number the versions
+        --sort                 <KEY>          Sort by name, origin, date or
+                                              size (largest first)
+        --reverse                             Reverse the order
+        --size                                Show each item's size on disk,
+                                              and the total
+            --bytes                           Show sizes in bytes
+        --json                                Print JSON for scripts
+        --transactions                        List the deletes whose files are
+                                              in the trash instead
+        --quiet-match                         Print nothing, exit 1 if nothing
+                                              matches
+        --only-dirs                           Only show directories
+        --only-files                          Only show files
+        --strict-types                        Hide items of unknown type
+    --set-display-name         <FILE|UUID>    Set the name shown by list
+                               <NAME>
+    --show                     <FILE|UUID>    Show when an item was trashed and
+                                              restored
+        --json                                Print JSON for scripts
+    --purge              -P    <FILE_1>...    Permenantly delete specified files
+        --version        -v    <VERSION>      Set the revision
+        --shred                               Overwrite the files first
+            --verbose    -V                   Name each file overwritten
+    --empty              -E    [FILE_1]...    Permenantly delete trash content,
+                                              or only the named items
+        --pattern        -p    <PATTERN>      Only delete items matching
+                                              PATTERN
+        --version        -v    <VERSION>      Only delete this revision of the
+                                              named or matching items
+        --force          -f                   Do not ask for confirmation,
+                                              like --yes
+        --dry-run                             Only report what would be freed
+        --older-than           <DURATION>     Only delete versions older than
+                                              DURATION (e.g. 30d, 24h, 2w)
+        --keep-latest          <N>            Only delete all but the N newest
+                                              versions of each item
+        --tag                  <TAG>          Only delete items tagged TAG
+        --only-dirs                           Only delete directories
+        --only-files                          Only delete files
+        --strict-types                        Keep items of unknown type
+        --shred                               Overwrite the files first
+            --verbose    -V                   Name each file overwritten
+    --status                                  Show the cache's summary
+    --doctor                                  Check the cache's summary
+    --check                                   Check the cache and the data
+                                              for problems, read-only
+    --stats              -S                   Summarise the trash's usage
+    --gc                                      Clean up what the cache and the
+                                              data disagree on
+        --yes            -y                   Do not ask for confirmation
+        --dry-run                             Only report what would be
+                                              cleaned up
+    --dedup                                   Store identical consecutive
+                                              versions of files only once
+    --batch                    <FILE>         Run the commands in FILE
+        --stop-on-error                       Stop at the first failure
+        --dry-run                             Only check the commands
+    --move-trash               <DIR>          Move the trash, with it's
+                                              settings, to DIR
+    --config                   <KEY> <VALUE>  Set a setting, such as
+                                              use_unicode or use_colors
+        --list           -L                   List the settings instead
+    --completions              <SHELL>        Print a completion script for
+                                              bash, zsh, fish, powershell or
+                                              elvish
+
+OPTIONS:
+    --quiet              -q                   Only print errors and results
+    --color                    <WHEN>         Style output always, never or
+                                              auto, when stdout is a terminal
+
+ENVIRONMENT:
+    TMAN_HOME                                 Keep the trash in this directory
+                                              rather than ~/.tman
+    NO_COLOR                                  Never style output, unless
+                                              --color always is given
+
+EXIT STATUS:
+    0                                         Success
+    1                                         Nothing matched --quiet-match
+    2                                         Invalid arguments or usage
+    3                                         A target could not be located
+    4                                         A filesystem operation failed
+    5                                         The cache, settings or log are invalid
+    6                                         Refused to act without --yes,
+                                              confirmation or a safe target
+    7                                         Some deletes, restores or batch
+                                              commands failed
+    8                                         Unknown error"#
+        )
+        .arg(Arg::with_name("delete")
+            .long("delete")
+            .short("D")
+            .help("Delete an item, storing it in the trash")
+            .takes_value(true)
+            .value_name("FILES")
+            .min_values(0)
+            .max_values(max_argument_values)
+            .conflicts_with_all(&[ "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "simple", "empty", "purge" ]))
+        .arg(Arg::with_name("under")
+            .long("under")
+            .help("Set the directory to delete matches of --pattern under, for --delete")
+            .takes_value(true)
+            .value_name("DIR")
+            .requires_all(&[ "delete", "pattern" ]))
+        .arg(Arg::with_name("max-depth")
+            .long("max-depth")
+            .help("Only delete matches of --pattern this many directories deep, for --delete")
+            .takes_value(true)
+            .value_name("DEPTH")
+            .requires_all(&[ "delete", "pattern" ]))
+        .arg(Arg::with_name("include-dirs")
+            .long("include-dirs")
+            .help("Delete directories matching --pattern as well as files, for --delete")
+            .requires_all(&[ "delete", "pattern" ]))
+        .arg(Arg::with_name("files-from")
+            .long("files-from")
+            .help("Read the files to delete from a file, or standard input if it is -, for --delete")
+            .takes_value(true)
+            .value_name("FILE")
+            .requires("delete"))
+        .arg(Arg::with_name("null")
+            .long("null")
+            .short("0")
+            .help("Separate the files read by --files-from, or listed by --list --simple, with NUL characters")
+            .conflicts_with_all(&[ "restore", "uuid", "restore-all", "watch", "json", "set-display-name", "show", "purge", "empty", "status", "doctor", "stats", "gc", "dedup", "batch", "move-trash", "config", "completions" ]))
+        .arg(Arg::with_name("reason")
+            .long("reason")
+            .alias("note")
+            .help("Record why the files were deleted, for --delete")
+            .takes_value(true)
+            .value_name("TEXT")
+            .requires("delete"))
+        .arg(Arg::with_name("tag")
+            .long("tag")
+            .help("Tag the deleted files, or only operate on items with the tag for --list and --empty")
+            .takes_value(true)
+            .value_name("TAG")
+            .multiple(true)
+            .number_of_values(1)
+            .conflicts_with_all(&[ "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "set-display-name", "show", "purge", "status", "doctor", "stats", "gc", "batch", "move-trash" ]))
+        .arg(Arg::with_name("copy")
+            .long("copy")
+            .help("Store a copy of each file as a new version, leaving the file in place, for --delete")
+            .requires("delete"))
+        .arg(Arg::with_name("porcelain")
+            .long("porcelain")
+            .help("Print a JSON line with the origin, UUID and version of each trashed file, and nothing else, for --delete")
+            .requires("delete"))
+        .arg(Arg::with_name("yes")
+            .long("yes")
+            .short("y")
+            .help("Do not ask for confirmation, for --delete, --empty and --gc"))
+        .arg(Arg::with_name("if-older-than")
+            .long("if-older-than")
+            .help("Only delete files last modified longer ago than a duration for --delete")
+            .takes_value(true)
+            .value_name("DURATION")
+            .requires("delete"))
+        .arg(Arg::with_name("no-evict")
+            .long("no-evict")
+            .help("Refuse to outgrow the quota rather than evicting old versions, for --delete")
+            .requires("delete"))
+        .arg(Arg::with_name("allow-special")
+            .long("allow-special")
+            .help("Allow deleting special files, such as sockets, named pipes and devices, for --delete")
+            .requires("delete"))
+        .arg(Arg::with_name("allow-protected")
+            .long("allow-protected")
+            .help("Allow deleting protected paths, such as / and the home directory, for --delete")
+            .requires("delete"))
+        .arg(Arg::with_name("keep-going")
+            .long("keep-going")
+            .help("Attempt every file after a failure, then summarise, for --delete")
+            .requires("delete"))
+        .arg(Arg::with_name("restore")
+            .long("restore")
+            .short("R")
+            .help("Restore files from the trash")
+            .takes_value(true)
+            .value_name("FILES")
+            .min_values(0)
+            .max_values(max_argument_values)
+            .conflicts_with_all(&[ "delete", "list", "simple", "empty" ]))
+        .arg(Arg::with_name("uuid")
+            .long("uuid")
+            .short("u")
+            .help("Restore the item with a UUID from the trash")
+            .takes_value(true)
+            .value_name("UUID")
+            .conflicts_with_all(&[ "delete", "list", "simple", "empty" ]))
+        .arg(Arg::with_name("match")
+            .long("match")
+            .short("m")
+            .help("Restore the items whose names match a pattern")
+            .takes_value(true)
+            .value_name("REGEX")
+            .requires("restore")
+            .conflicts_with_all(&[ "delete", "uuid", "restore-all", "origin", "all-origins", "newest-only", "list", "pattern", "simple", "empty" ]))
+        .arg(Arg::with_name("all")
+            .long("all")
+            .help("Allow an empty pattern for --match or --pattern")
+            .requires("restore")
+            .conflicts_with_all(&[ "delete", "uuid", "restore-all", "origin", "all-origins", "newest-only", "list", "simple", "empty" ]))
+        .arg(Arg::with_name("restore-all")
+            .long("restore-all")
+            .help("Restore every item in the trash")
+            .conflicts_with_all(&[ "delete", "list", "simple", "empty" ]))
+        .arg(Arg::with_name("origin")
+            .long("origin")
+            .short("o")
+            .help("Set the origin for restore")
+            .takes_value(true)
+            .value_name("PATH")
+            .requires("restore")
+            .conflicts_with_all(&[ "delete", "list", "pattern", "simple", "empty" ]))
+        .arg(Arg::with_name("all-origins")
+            .long("all-origins")
+            .help("Restore the items of every origin which have a name")
+            .requires("restore")
+            .conflicts_with_all(&[ "delete", "match", "all", "origin", "list", "pattern", "simple", "empty" ]))
+        .arg(Arg::with_name("newest-only")
+            .long("newest-only")
+            .help("Restore only the newest version of a name among every origin")
+            .requires("restore")
+            .conflicts_with_all(&[ "delete", "match", "all", "origin", "all-origins", "version", "interactive", "list", "pattern", "simple", "empty" ]))
+        .arg(Arg::with_name("to")
+            .long("to")
+            .short("t")
+            .help("Set the destination directory for restore")
+            .takes_value(true)
+            .value_name("DIR")
+            .requires("restore-target")
+            .conflicts_with_all(&[ "delete", "list", "simple", "empty" ]))
+        .arg(Arg::with_name("version")
+            .long("version")
+            .short("v")
+            .help("Set the version for restore, purge or a targeted empty")
+            .takes_value(true)
+            .value_name("VERSION")
+            .conflicts_with_all(&[ "delete", "list", "simple", "older-than", "set-display-name", "show", "status", "doctor", "stats", "gc", "dedup", "batch", "move-trash", "config", "completions" ]))
+        .arg(Arg::with_name("interactive")
+            .long("interactive")
+            .short("i")
+            .help("Confirm each item before restoring it")
+            .requires("restore-target")
+            .conflicts_with_all(&[ "delete", "list", "simple", "empty" ]))
+        .arg(Arg::with_name("no-exec")
+            .long("no-exec")
+            .help("Clear the execute permissions of restored files")
+            .requires("restore-target")
+            .conflicts_with_all(&[ "delete", "list", "simple", "empty" ]))
+        .arg(Arg::with_name("last")
+            .long("last")
+            .help("Restore the versions trashed by the last delete for --restore")
+            .requires("restore")
+            .conflicts_with_all(&[ "delete", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "version", "inner", "transaction", "list", "pattern", "simple", "empty" ]))
+        .arg(Arg::with_name("transaction")
+            .long("transaction")
+            .help("Restore the versions trashed in a transaction for --restore")
+            .takes_value(true)
+            .value_name("ID")
+            .requires("restore")
+            .conflicts_with_all(&[ "delete", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "version", "inner", "list", "pattern", "simple", "empty" ]))
+        .arg(Arg::with_name("inner")
+            .long("inner")
+            .help("Copy a path out of a trashed directory for --restore")
+            .takes_value(true)
+            .value_name("PATH")
+            .requires("restore")
+            .conflicts_with_all(&[ "delete", "match", "all", "all-origins", "newest-only", "interactive", "no-exec", "list", "pattern", "simple", "empty" ]))
+        .arg(Arg::with_name("on-conflict")
+            .long("on-conflict")
+            .help("Set what happens when a restored item's path exists")
+            .takes_value(true)
+            .value_name("POLICY")
+            .possible_values(&[ "skip", "overwrite", "rename" ])
+            .requires("restore-target")
+            .conflicts_with_all(&[ "delete", "list", "simple", "empty" ]))
+        .arg(Arg::with_name("list")
+            .long("list")
+            .short("L")
+            .help("List items in the trash")
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "empty", "purge" ]))
+        .arg(Arg::with_name("pattern")
+            .long("pattern")
+            .short("p")
+            .help("Set a pattern for --list, --restore or --empty, or the names to delete under a directory for --delete")
+            .takes_value(true)
+            .value_name("PATTERN")
+            .conflicts_with_all(&[ "uuid", "restore-all", "match", "origin", "all-origins", "newest-only", "inner", "last", "transaction", "purge" ]))
+        .arg(Arg::with_name("transactions")
+            .long("transactions")
+            .help("List the transactions left in the trash for --list")
+            .requires("list")
+            .conflicts_with_all(&[ "pattern", "simple", "watch", "missing", "match-display", "indices", "json", "quiet-match", "search-reasons", "tag" ]))
+        .arg(Arg::with_name("simple")
+            .long("simple")
+            .short("s")
+            .help("Use simple list format for --list")
+            .requires("list")
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "empty", "purge" ]))
+        .arg(Arg::with_name("verbose")
+            .long("verbose")
+            .short("V")
+            .help("Show the UUIDs, sizes and modes for --list, explain evictions for --delete, or name shredded files for --shred")
+            .conflicts_with_all(&[ "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "interactive", "no-exec", "on-conflict", "simple" ]))
+        .arg(Arg::with_name("watch")
+            .long("watch")
+            .short("w")
+            .help("Re-list the items whenever they change for --list")
+            .requires("list")
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "empty", "purge" ]))
+        .arg(Arg::with_name("missing")
+            .long("missing")
+            .help("Only list symbolic links to missing targets for --list")
+            .requires("list")
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "empty", "purge" ]))
+        .arg(Arg::with_name("match-display")
+            .long("match-display")
+            .help("Match display names as well as names for --list")
+            .requires("list")
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "empty", "purge" ]))
+        .arg(Arg::with_name("search-reasons")
+            .long("search-reasons")
+            .help("Match the reasons items were deleted for as well as names for --list")
+            .requires("list")
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "empty", "purge" ]))
+        .arg(Arg::with_name("indices")
+            .long("indices")
+            .help("Number the versions, as accepted by --version, for --list")
+            .requires("list")
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "simple", "empty", "purge" ]))
+        .arg(Arg::with_name("sort")
+            .long("sort")
+            .help("Sort the items by name, origin, date of the newest version or size on disk, largest first, for --list")
+            .takes_value(true)
+            .value_name("KEY")
+            .possible_values(&[ "name", "origin", "date", "size" ])
+            .requires("list")
+            .conflicts_with_all(&[ "transactions", "quiet-match" ]))
+        .arg(Arg::with_name("size")
+            .long("size")
+            .help("Show the size of each item on disk, and the total, for --list")
+            .requires("list")
+            .conflicts_with_all(&[ "null", "json", "transactions", "quiet-match" ]))
+        .arg(Arg::with_name("bytes")
+            .long("bytes")
+            .help("Show sizes as numbers of bytes, for --list")
+            .requires("list")
+            .conflicts_with_all(&[ "json", "transactions", "quiet-match" ]))
+        .arg(Arg::with_name("reverse")
+            .long("reverse")
+            .help("List the items in the opposite order, for --list")
+            .requires("list")
+            .conflicts_with_all(&[ "transactions", "quiet-match" ]))
+        .arg(Arg::with_name("json")
+            .long("json")
+            .help("Print JSON for --list or --show")
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "simple", "verbose", "watch", "indices", "empty", "purge" ]))
+        .arg(Arg::with_name("quiet-match")
+            .long("quiet-match")
+            .help("List nothing, exiting with 1 when no items match, for --list")
+            .requires("list")
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "simple", "verbose", "watch", "indices", "json", "empty", "purge" ]))
+        .arg(Arg::with_name("set-display-name")
+            .long("set-display-name")
+            .help("Set the name shown in place of an item's name")
+            .takes_value(true)
+            .number_of_values(2)
+            .value_names(&[ "TARGET", "NAME" ])
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "match-display", "indices", "empty", "purge" ]))
+        .arg(Arg::with_name("show")
+            .long("show")
+            .help("Show when an item was trashed and restored")
+            .takes_value(true)
+            .value_name("TARGET")
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "verbose", "watch", "missing", "match-display", "indices", "quiet-match", "set-display-name", "purge", "empty", "status", "doctor" ]))
+        .arg(Arg::with_name("purge")
+            .long("purge")
+            .short("P")
+            .help("Permenantly delete items in the trash")
+            .takes_value(true)
+            .value_name("FILES")
+            .max_values(max_argument_values)
+            .conflicts_with_all(&[ "delete", "list", "pattern", "simple", "watch", "missing", "match-display", "indices", "set-display-name", "empty" ]))
+        .arg(Arg::with_name("empty")
+            .long("empty")
+            .short("E")
+            .help("Permenantly delete all trash items, or only those named or matching --pattern")
+            .takes_value(true)
+            .value_name("FILES")
+            .min_values(0)
+            .max_values(max_argument_values)
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "interactive", "no-exec", "on-conflict", "list", "simple", "purge" ]))
+        .arg(Arg::with_name("shred")
+            .long("shred")
+            .help("Overwrite files before removing them for --empty or --purge")
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "interactive", "no-exec", "on-conflict", "list", "simple", "set-display-name", "show", "status", "doctor", "stats", "gc", "dedup", "batch", "move-trash", "completions" ]))
+        .arg(Arg::with_name("force")
+            .long("force")
+            .short("f")
+            .help("Do not ask for confirmation for --empty")
+            .requires("empty")
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "interactive", "no-exec", "on-conflict", "list", "simple", "purge" ]))
+        .arg(Arg::with_name("older-than")
+            .long("older-than")
+            .help("Only delete versions older than a duration for --empty")
+            .takes_value(true)
+            .value_name("DURATION")
+            .requires("empty")
+            .conflicts_with_all(&[ "delete", "restore", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "purge" ]))
+        .arg(Arg::with_name("keep-latest")
+            .long("keep-latest")
+            .help("Only delete all but the newest versions of each item for --empty")
+            .takes_value(true)
+            .value_name("N")
+            .requires("empty")
+            .conflicts_with_all(&[ "version", "older-than" ]))
+        .arg(Arg::with_name("only-dirs")
+            .long("only-dirs")
+            .help("Only operate on directories for --list, --empty and restores of --match or --pattern")
+            .conflicts_with_all(&[ "delete", "uuid", "restore-all", "origin", "all-origins", "newest-only", "set-display-name", "purge", "status", "doctor" ]))
+        .arg(Arg::with_name("only-files")
+            .long("only-files")
+            .help("Only operate on files for --list, --empty and restores of --match or --pattern")
+            .conflicts_with_all(&[ "delete", "uuid", "restore-all", "origin", "all-origins", "newest-only", "set-display-name", "purge", "status", "doctor" ]))
+        .arg(Arg::with_name("strict-types")
+            .long("strict-types")
+            .help("Exclude items of unknown type for --only-dirs and --only-files")
+            .requires("type-filter")
+            .conflicts_with_all(&[ "delete", "uuid", "restore-all", "origin", "all-origins", "newest-only", "set-display-name", "purge", "status", "doctor" ]))
+        .arg(Arg::with_name("status")
+            .long("status")
+            .help("Show the counts recorded in the cache's header")
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "verbose", "watch", "missing", "match-display", "indices", "set-display-name", "purge", "empty", "older-than", "only-dirs", "only-files", "strict-types", "doctor" ]))
+        .arg(Arg::with_name("stats")
+            .long("stats")
+            .short("S")
+            .help("Summarise the entries, versions and size of the trash")
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "verbose", "watch", "missing", "match-display", "indices", "set-display-name", "purge", "empty", "older-than", "only-dirs", "only-files", "strict-types", "status", "doctor" ]))
+        .arg(Arg::with_name("gc")
+            .long("gc")
+            .help("Remove data which the cache does not reference, and forget versions whose data is missing")
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "verbose", "watch", "missing", "match-display", "indices", "set-display-name", "purge", "empty", "older-than", "only-dirs", "only-files", "strict-types", "status", "doctor", "stats" ]))
+        .arg(Arg::with_name("dedup")
+            .long("dedup")
+            .help("Store identical consecutive versions of files only once")
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "verbose", "watch", "missing", "match-display", "indices", "set-display-name", "purge", "empty", "older-than", "only-dirs", "only-files", "strict-types", "status", "doctor", "stats", "gc" ]))
+        .arg(Arg::with_name("undo")
+            .long("undo")
+            .help("Reverse the deletes and restores of the last run which made any")
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "watch", "missing", "match-display", "indices", "json", "set-display-name", "show", "purge", "empty", "older-than", "only-dirs", "only-files", "strict-types", "status", "doctor", "stats", "gc", "dedup", "batch", "move-trash", "config", "completions", "dry-run" ]))
+        .arg(Arg::with_name("check")
+            .long("check")
+            .help("Check the cache and the data for problems, without changing either")
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "verbose", "watch", "missing", "match-display", "indices", "json", "set-display-name", "show", "purge", "empty", "older-than", "only-dirs", "only-files", "strict-types", "status", "doctor", "stats", "gc", "dedup", "undo", "batch", "move-trash", "config", "completions", "dry-run" ]))
+        .arg(Arg::with_name("doctor")
+            .long("doctor")
+            .help("Check the counts recorded in the cache's header against it's entries")
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "verbose", "watch", "missing", "match-display", "indices", "set-display-name", "purge", "empty", "older-than", "only-dirs", "only-files", "strict-types", "status" ]))
+        .arg(Arg::with_name("batch")
+            .long("batch")
+            .help("Run the commands in a file, one per line")
+            .takes_value(true)
+            .value_name("FILE")
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "verbose", "watch", "missing", "match-display", "indices", "json", "set-display-name", "purge", "empty", "older-than", "only-dirs", "only-files", "strict-types", "status", "doctor" ]))
+        .arg(Arg::with_name("move-trash")
+            .long("move-trash")
+            .help("Move the trash, with it's cache, settings and data, to a directory")
+            .takes_value(true)
+            .value_name("DIR")
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "verbose", "watch", "missing", "match-display", "indices", "json", "set-display-name", "purge", "empty", "older-than", "only-dirs", "only-files", "strict-types", "status", "doctor", "batch" ]))
+        .arg(Arg::with_name("config")
+            .long("config")
+            .help("Set a setting, or list the settings with --list")
+            .takes_value(true)
+            .value_name("KEY VALUE")
+            .min_values(0)
+            .max_values(2)
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "pattern", "simple", "verbose", "watch", "missing", "match-display", "indices", "json", "set-display-name", "show", "purge", "empty", "status", "doctor", "stats", "gc", "dedup", "batch", "move-trash", "transactions" ]))
+        .arg(Arg::with_name("completions")
+            .long("completions")
+            .help("Print a completion script for a shell")
+            .hidden(true)
+            .takes_value(true)
+            .value_name("SHELL")
+            .possible_values(&Shell::variants())
+            .case_insensitive(true)
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "match", "all", "origin", "all-origins", "newest-only", "to", "version", "interactive", "no-exec", "on-conflict", "list", "pattern", "simple", "verbose", "watch", "missing", "match-display", "indices", "json", "set-display-name", "purge", "empty", "older-than", "only-dirs", "only-files", "strict-types", "status", "doctor", "stats", "gc", "batch", "move-trash" ]))
+        .arg(Arg::with_name("stop-on-error")
+            .long("stop-on-error")
+            .help("Stop at the first failed command for --batch")
+            .requires("batch"))
+        .arg(Arg::with_name("dry-run")
+            .long("dry-run")
+            .help("Only check the commands for --batch, or report what --empty would free or --gc clean up")
+            .conflicts_with_all(&[ "delete", "restore", "uuid", "restore-all", "list", "purge", "set-display-name", "show", "status", "doctor", "stats", "dedup", "move-trash", "config", "completions" ]))
+        .arg(Arg::with_name("quiet")
+            .long("quiet")
+            .short("q")
+            .help("Print only errors and results, such as listings"))
+        .arg(Arg::with_name("color")
+            .long("color")
+            .help("Style output always, never or only when stdout is a terminal")
+            .takes_value(true)
+            .value_name("WHEN")
+            .possible_values(&[ "auto", "always", "never" ]))
+        //
+        // Any conflict of a group's member is a conflict of the whole group,
+        // so the members must share their conflicts.
+        //
+        .group(ArgGroup::with_name("restore-target")
+            .args(&[ "restore", "uuid", "restore-all" ]))
+        .group(ArgGroup::with_name("type-filter")
+            .args(&[ "only-dirs", "only-files" ]))
+}
+
+///
+/// Find the first of `path` followed by `_1`, `_2` and so on, which does not
+/// exist.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let path: PathBuf = available_path(Path::new("./Bilbo.txt"));
+/// ```
+///
+fn available_path(path: &Path) -> PathBuf {
+    (1..)
+        .map(|number| PathBuf::from(format!("{}_{}", path.display(), number)))
+        .find(|candidate| symlink_metadata(candidate).is_err())
+        .unwrap()
+}
+
+///
+/// Convert the raw bytes of a path, read from a file, into a path, which is
+/// exact on Unix and lossy elsewhere.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let path: OsString = os_string_from_bytes(b"./Bilbo.txt");
+/// ```
+///
+#[cfg(unix)]
+fn os_string_from_bytes(bytes: &[u8]) -> OsString {
+    use std::os::unix::ffi::{ OsStrExt };
+
+    OsStr::from_bytes(bytes).to_os_string()
+}
+
+#[cfg(not(unix))]
+fn os_string_from_bytes(bytes: &[u8]) -> OsString {
+    OsString::from(String::from_utf8_lossy(bytes).to_string())
+}
+
+///
+/// Split a batch command into arguments at whitespace, as a shell would,
+/// keeping quoted text together and honouring backslash escapes.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let arguments: Vec<String> = split_command("delete 'Bilbo Baggins.txt'")?;
+/// ```
+/// 
+/// # Errors
+/// 
+/// Fails if a quote is not closed.
+///
+fn split_command(command: &str) -> Result<Vec<String>> {
+    let mut arguments: Vec<String> = vec![];
+    let mut argument: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut characters = command.chars();
+
+    while let Some(character) = characters.next() {
+        match (quote, character) {
+            (Some(open), _) if character == open => quote = None,
+            (None, '\'') | (None, '"') => {
+                quote = Some(character);
+                argument.get_or_insert_with(String::new);
+            },
+            (Some('\''), _) => argument.get_or_insert_with(String::new).push(character),
+            (_, '\\') => {
+                if let Some(escaped) = characters.next() {
+                    argument.get_or_insert_with(String::new).push(escaped);
+                }
+            },
+            (None, _) if character.is_whitespace() => arguments.extend(argument.take()),
+            _ => argument.get_or_insert_with(String::new).push(character)
+        }
+    }
+
+    if quote.is_some() {
+        Err(Error::InvalidCommand(String::from("unclosed quote")))?;
+    }
+
+    arguments.extend(argument);
+
+    Ok(arguments)
+}
@@ -0,0 +1,289 @@
+use serde::{ Serialize, Deserialize };
+use std::convert::{ TryFrom };
+
+///
+/// The units in which sizes are shown.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let units: SizeUnits = SizeUnits::Binary;
+/// ```
+///
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeUnits {
+    /// Powers of 1000, such as `kB` and `MB`.
+    #[default]
+    Si,
+    /// Powers of 1024, such as `KiB` and `MiB`.
+    Binary
+}
+
+///
+/// Formats numbers and sizes for people, following the conventions of a
+/// locale.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let formatter: Formatter = Formatter::new(SizeUnits::Si, "de");
+/// let size: String = formatter.size(2_300_000_000);
+/// ```
+///
+pub struct Formatter {
+    /// The units of sizes.
+    units: SizeUnits,
+    /// The separator between groups of three digits.
+    grouping: &'static str,
+    /// The separator before the fraction.
+    decimal: &'static str
+}
+
+///
+/// The separators of the known locales, by language, as the grouping and
+/// decimal separators.
+///
+const LOCALES: [(&str, &str, &str); 14] = [
+    ("en", ",", "."),
+    ("ja", ",", "."),
+    ("zh", ",", "."),
+    ("de", ".", ","),
+    ("es", ".", ","),
+    ("it", ".", ","),
+    ("nl", ".", ","),
+    ("pt", ".", ","),
+    ("da", ".", ","),
+    ("fr", "\u{202f}", ","),
+    ("ru", "\u{a0}", ","),
+    ("pl", "\u{a0}", ","),
+    ("sv", "\u{a0}", ","),
+    ("fi", "\u{a0}", ",")
+];
+
+impl Formatter {
+    ///
+    /// Create a formatter for the language of `locale`, such as `de` or
+    /// `de_DE.UTF-8`, where `auto` uses the locale of the environment.
+    /// Unknown locales are formatted like English.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let formatter: Formatter = Formatter::new(SizeUnits::Binary, "auto");
+    /// ```
+    ///
+    pub fn new(units: SizeUnits, locale: &str) -> Formatter {
+        let locale: String = if locale == "auto" {
+            ["LC_ALL", "LC_NUMERIC", "LANG"].iter()
+                .filter_map(|variable| std::env::var(variable).ok())
+                .find(|value| !value.is_empty())
+                .unwrap_or_default()
+        } else {
+            locale.to_string()
+        };
+        let language: String = locale.chars().take_while(|character| character.is_ascii_alphabetic()).collect::<String>().to_lowercase();
+        let (_, grouping, decimal): (&str, &'static str, &'static str) = LOCALES.iter()
+            .find(|(name, _, _)| *name == language)
+            .cloned()
+            .unwrap_or(LOCALES[0]);
+
+        Formatter {
+            units,
+            grouping,
+            decimal
+        }
+    }
+
+    ///
+    /// Format a count, grouping it's digits.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let count: String = formatter.number(1234);
+    /// ```
+    ///
+    pub fn number(&self, value: u64) -> String {
+        let digits: String = value.to_string();
+        let mut grouped: String = String::new();
+
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                grouped.push_str(self.grouping);
+            }
+
+            grouped.push(digit);
+        }
+
+        grouped
+    }
+
+    ///
+    /// Format a size in bytes with the largest unit it fills, to one decimal
+    /// place, such as `2.3 GB`.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let size: String = formatter.size(1024);
+    /// ```
+    ///
+    pub fn size(&self, bytes: u64) -> String {
+        let (base, units): (u64, [&str; 7]) = match self.units {
+            SizeUnits::Si => (1000, [ "B", "kB", "MB", "GB", "TB", "PB", "EB" ]),
+            SizeUnits::Binary => (1024, [ "B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB" ])
+        };
+        let mut scale: u64 = 1;
+        let mut unit: usize = 0;
+
+        while unit + 1 < units.len() && bytes / scale >= base {
+            scale *= base;
+            unit += 1;
+        }
+
+        if unit == 0 {
+            return format!("{} {}", self.number(bytes), units[0]);
+        }
+
+        //
+        // Tenths are rounded in integers, so that sizes near the largest
+        // `u64` are still exact, and a size rounding up to the next unit is
+        // shown in it.
+        //
+        let mut tenths: u128 = (u128::from(bytes) * 10 + u128::from(scale) / 2) / u128::from(scale);
+
+        if tenths >= u128::from(base) * 10 && unit + 1 < units.len() {
+            scale *= base;
+            unit += 1;
+            tenths = (u128::from(bytes) * 10 + u128::from(scale) / 2) / u128::from(scale);
+        }
+
+        format!("{}{}{} {}", self.number((tenths / 10) as u64), self.decimal, tenths % 10, units[unit])
+    }
+}
+
+///
+/// Parse a size written as a number of bytes, optionally with a fraction and
+/// a unit, such as `512`, `10GiB` or `1.5 MB`, where units ending in `iB`
+/// are powers of 1024 and the others powers of 1000, in any case.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let bytes: Option<u64> = parse_size("10GiB");
+/// ```
+///
+pub fn parse_size(text: &str) -> Option<u64> {
+    let text: &str = text.trim();
+    let split: usize = text.find(|character: char| !character.is_ascii_digit() && character != '.').unwrap_or(text.len());
+    let unit: String = text[split..].trim().to_lowercase();
+    let scale: u128 = match unit.as_str() {
+        "" | "b" => 1,
+        unit => {
+            let mut characters = unit.chars();
+            let exponent: u32 = "kmgtpe".find(characters.next()?)? as u32 + 1;
+            let base: u128 = match characters.as_str() {
+                "" | "b" => 1000,
+                "ib" => 1024,
+                _ => return None
+            };
+
+            base.pow(exponent)
+        }
+    };
+    let (whole, fraction): (&str, &str) = match text[..split].find('.') {
+        Some(point) => (&text[..point], &text[point + 1..split]),
+        None => (&text[..split], "")
+    };
+
+    //
+    // Beyond the nineteenth digit, a fraction of even an exbibyte is less
+    // than a byte, so the rest are dropped to keep the arithmetic in range.
+    //
+    let fraction: &str = &fraction[..fraction.len().min(19)];
+
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+
+    let whole: u128 = if whole.is_empty() { 0 } else { whole.parse().ok()? };
+    let numerator: u128 = if fraction.is_empty() { 0 } else { fraction.parse().ok()? };
+    let bytes: u128 = whole.checked_mul(scale)? + numerator * scale / 10u128.pow(fraction.len() as u32);
+
+    u64::try_from(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_below_a_unit_are_shown_in_bytes() {
+        let si: Formatter = Formatter::new(SizeUnits::Si, "en");
+        let binary: Formatter = Formatter::new(SizeUnits::Binary, "en");
+
+        assert_eq!(si.size(0), "0 B");
+        assert_eq!(si.size(999), "999 B");
+        assert_eq!(si.size(1000), "1.0 kB");
+        assert_eq!(binary.size(0), "0 B");
+        assert_eq!(binary.size(1023), "1,023 B");
+        assert_eq!(binary.size(1024), "1.0 KiB");
+    }
+
+    #[test]
+    fn sizes_rounding_up_are_shown_in_the_next_unit() {
+        let si: Formatter = Formatter::new(SizeUnits::Si, "en");
+        let binary: Formatter = Formatter::new(SizeUnits::Binary, "en");
+
+        assert_eq!(si.size(999_949), "999.9 kB");
+        assert_eq!(si.size(999_950), "1.0 MB");
+        assert_eq!(binary.size(1024 * 1024 - 1), "1.0 MiB");
+        assert_eq!(binary.size(1024 * 1023 + 972), "1,023.9 KiB");
+    }
+
+    #[test]
+    fn the_largest_size_is_shown_in_the_largest_unit() {
+        assert_eq!(Formatter::new(SizeUnits::Si, "en").size(u64::MAX), "18.4 EB");
+        assert_eq!(Formatter::new(SizeUnits::Binary, "en").size(u64::MAX), "16.0 EiB");
+    }
+
+    #[test]
+    fn separators_follow_the_locale() {
+        assert_eq!(Formatter::new(SizeUnits::Si, "de_DE.UTF-8").size(2_345_000_000), "2,3 GB");
+        assert_eq!(Formatter::new(SizeUnits::Si, "de").number(1_234_567), "1.234.567");
+        assert_eq!(Formatter::new(SizeUnits::Si, "xx").number(1_234_567), "1,234,567");
+    }
+
+    #[test]
+    fn sizes_are_parsed_with_and_without_units() {
+        assert_eq!(parse_size("0"), Some(0));
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("512 B"), Some(512));
+        assert_eq!(parse_size("1.5 MB"), Some(1_500_000));
+        assert_eq!(parse_size("10GiB"), Some(10 << 30));
+        assert_eq!(parse_size("2k"), Some(2000));
+        assert_eq!(parse_size("1KIB"), Some(1024));
+        assert_eq!(parse_size(".5kB"), Some(500));
+        assert_eq!(parse_size("18446744073709551615"), Some(u64::MAX));
+    }
+
+    #[test]
+    fn sizes_out_of_range_or_malformed_are_refused() {
+        assert_eq!(parse_size(""), None);
+        assert_eq!(parse_size("."), None);
+        assert_eq!(parse_size("kB"), None);
+        assert_eq!(parse_size("5 xB"), None);
+        assert_eq!(parse_size("5 kx"), None);
+        assert_eq!(parse_size("5 kibb"), None);
+        assert_eq!(parse_size("1.5.2"), None);
+        assert_eq!(parse_size("18446744073709551616"), None);
+        assert_eq!(parse_size("16EiB"), None);
+    }
+
+    #[test]
+    fn long_fractions_are_truncated() {
+        assert_eq!(parse_size("0.99999999999999999999999 EiB"), Some((1 << 60) - 1));
+        assert_eq!(parse_size("1.00000000000000000000009 B"), Some(1));
+    }
+}
@@ -0,0 +1,176 @@
+use std::path::{ Path, PathBuf };
+
+use super::error::{ Result, Error };
+
+///
+/// The locations of everything the program stores, within it's root
+/// directory.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let paths: Paths = Paths::new(PathBuf::from("/home/Bilbo/.tman"));
+/// ```
+///
+pub struct Paths {
+    /// The directory holding everything else.
+    pub root: PathBuf,
+    /// The cache file.
+    pub cache: PathBuf,
+    /// The settings file.
+    pub settings: PathBuf,
+    /// The directory holding the versions of items.
+    pub data: PathBuf,
+    /// The log of the changes made by each run, for `--undo`.
+    pub log: PathBuf
+}
+
+impl Paths {
+    ///
+    /// Lay out the locations within the directory `root`.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let paths: Paths = Paths::new(PathBuf::from("/home/Bilbo/.tman"));
+    /// ```
+    ///
+    pub fn new(root: PathBuf) -> Paths {
+        Paths {
+            cache: root.join("cache.json"),
+            settings: root.join("settings.json"),
+            data: root.join("data"),
+            log: root.join("log.json"),
+            root
+        }
+    }
+
+    ///
+    /// Check that neither the root nor the home directory `home`, whose
+    /// settings record where the trash is kept when it is moved, lies inside
+    /// any of the other's stores, where the program would move, overwrite or
+    /// collect it as garbage.
+    /// The paths are compared as they are, so should already be resolved.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// Paths::new(PathBuf::from("/mnt/big/trash")).check(Path::new("/home/Bilbo/.tman"))?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when either directory lies inside the cache, settings, data or
+    /// log of the other, naming both paths.
+    ///
+    pub fn check(&self, home: &Path) -> Result<()> {
+        let home: Paths = Paths::new(home.to_path_buf());
+
+        for (inner, outer) in &[ (self, &home), (&home, self) ] {
+            for store in outer.stores().iter() {
+                if inner.root.starts_with(store) {
+                    Err(Error::InvalidConfiguration(inner.root.display().to_string(), store.display().to_string()))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Get the locations of the cache, settings, data and log.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let stores: [&PathBuf; 4] = paths.stores();
+    /// ```
+    ///
+    pub fn stores(&self) -> [&PathBuf; 4] {
+        [ &self.cache, &self.settings, &self.data, &self.log ]
+    }
+
+    ///
+    /// Check that trashing the resolved path `target` would not move the
+    /// root, or anything inside it.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// paths.check_target(Path::new("/home/Bilbo/Bilbo.txt"))?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when `target` contains the root or lies inside it.
+    ///
+    pub fn check_target(&self, target: &Path) -> Result<()> {
+        if self.root.starts_with(target) || target.starts_with(&self.root) {
+            Err(Error::TrashTarget(target.display().to_string()))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///
+    /// Check a trash kept in `root` against the home directory `home`.
+    ///
+    fn check(root: &str, home: &str) -> Result<()> {
+        Paths::new(PathBuf::from(root)).check(Path::new(home))
+    }
+
+    #[test]
+    fn check_accepts_separate_and_shared_directories() {
+        assert!(check("/home/Bilbo/.tman", "/home/Bilbo/.tman").is_ok());
+        assert!(check("/mnt/big/trash", "/home/Bilbo/.tman").is_ok());
+        assert!(check("/home/Bilbo/.tman/trash", "/home/Bilbo/.tman").is_ok());
+        assert!(check("/home/Bilbo", "/home/Bilbo/.tman").is_ok());
+        assert!(check("/home/Bilbo/.tman/data-old", "/home/Bilbo/.tman").is_ok());
+    }
+
+    #[test]
+    fn check_refuses_a_root_inside_any_store_of_the_home() {
+        for store in &[ "cache.json", "settings.json", "data", "log.json" ] {
+            for root in &[ format!("/home/Bilbo/.tman/{}", store), format!("/home/Bilbo/.tman/{}/trash", store) ] {
+                match check(root, "/home/Bilbo/.tman") {
+                    Err(Error::InvalidConfiguration(inner, outer)) => {
+                        assert_eq!(inner, *root);
+                        assert_eq!(outer, format!("/home/Bilbo/.tman/{}", store));
+                    },
+                    _ => panic!("{} was accepted", root)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_refuses_a_home_inside_any_store_of_the_root() {
+        for store in &[ "cache.json", "settings.json", "data", "log.json" ] {
+            for home in &[ format!("/mnt/big/trash/{}", store), format!("/mnt/big/trash/{}/.tman", store) ] {
+                match check("/mnt/big/trash", home) {
+                    Err(Error::InvalidConfiguration(inner, outer)) => {
+                        assert_eq!(inner, *home);
+                        assert_eq!(outer, format!("/mnt/big/trash/{}", store));
+                    },
+                    _ => panic!("{} was accepted", home)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_target_refuses_the_root_and_its_contents() {
+        let paths: Paths = Paths::new(PathBuf::from("/home/Bilbo/.tman"));
+
+        assert!(paths.check_target(Path::new("/home/Bilbo/Bilbo.txt")).is_ok());
+        assert!(paths.check_target(Path::new("/home/Bilbo/.tman-old")).is_ok());
+        assert!(matches!(paths.check_target(Path::new("/home/Bilbo")), Err(Error::TrashTarget(_))));
+        assert!(matches!(paths.check_target(Path::new("/home/Bilbo/.tman")), Err(Error::TrashTarget(_))));
+        assert!(matches!(paths.check_target(Path::new("/home/Bilbo/.tman/data/56f188aa")), Err(Error::TrashTarget(_))));
+    }
+}
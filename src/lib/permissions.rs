@@ -0,0 +1,169 @@
+use std::fs::{ File, metadata, symlink_metadata };
+use std::path::{ Path, PathBuf };
+use std::time::{ SystemTime };
+
+use super::error::{ Result, Error };
+use super::dedup::{ files };
+
+///
+/// Collect the executable regular files at `root`, which may be a single file
+/// or a directory tree, without following symbolic links.
+/// Nothing is executable on platforms without Unix permissions.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let executables: Vec<PathBuf> = executables(Path::new("./build"))?;
+/// ```
+///
+pub fn executables(root: &Path) -> Result<Vec<PathBuf>> {
+    let file_type = symlink_metadata(root)?.file_type();
+    let mut executables: Vec<PathBuf> = vec![];
+
+    let candidates: Vec<PathBuf> = if file_type.is_dir() {
+        files(root)?
+    } else if file_type.is_file() {
+        vec![root.to_path_buf()]
+    } else {
+        vec![]
+    };
+
+    for candidate in candidates {
+        if is_executable(&candidate)? {
+            executables.push(candidate);
+        }
+    }
+
+    Ok(executables)
+}
+
+///
+/// Clear every execute bit of the file `path`.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// strip_exec(Path::new("./build.sh"))?;
+/// ```
+///
+#[cfg(unix)]
+pub fn strip_exec(path: &Path) -> Result<()> {
+    use std::fs::{ set_permissions, Permissions };
+    use std::os::unix::fs::{ PermissionsExt };
+
+    let mode: u32 = symlink_metadata(path)?.permissions().mode();
+
+    set_permissions(path, Permissions::from_mode(mode & !0o111))?;
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn strip_exec(_: &Path) -> Result<()> {
+    Ok(())
+}
+
+///
+/// Get the permission bits of `path`, without following symbolic links.
+/// There are no permission bits on platforms without Unix permissions.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let mode: Option<u32> = mode(Path::new("./build.sh"))?;
+/// ```
+///
+#[cfg(unix)]
+pub fn mode(path: &Path) -> Result<Option<u32>> {
+    use std::os::unix::fs::{ PermissionsExt };
+
+    Ok(Some(symlink_metadata(path)?.permissions().mode() & 0o7777))
+}
+
+#[cfg(not(unix))]
+pub fn mode(_: &Path) -> Result<Option<u32>> {
+    Ok(None)
+}
+
+///
+/// Set the permission bits of `path`, following symbolic links.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// set_mode(Path::new("./build.sh"), 0o755)?;
+/// ```
+///
+#[cfg(unix)]
+pub fn set_mode(path: &Path, mode: u32) -> Result<()> {
+    use std::fs::{ set_permissions, Permissions };
+    use std::os::unix::fs::{ PermissionsExt };
+
+    set_permissions(path, Permissions::from_mode(mode))?;
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn set_mode(_: &Path, _: u32) -> Result<()> {
+    Ok(())
+}
+
+///
+/// Set the modification time of `path`, following symbolic links, even when
+/// it's owner may not read it.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// set_modified(Path::new("./build.sh"), SystemTime::now())?;
+/// ```
+///
+#[cfg(unix)]
+pub fn set_modified(path: &Path, time: SystemTime) -> Result<()> {
+    use std::fs::{ set_permissions, Permissions };
+    use std::os::unix::fs::{ PermissionsExt };
+
+    let mode: u32 = metadata(path)?.permissions().mode() & 0o7777;
+    let unreadable: bool = mode & 0o400 == 0;
+
+    //
+    // The file must be opened for it's time to be set, so an owner without
+    // read permission is given it until the time is set.
+    //
+    if unreadable {
+        set_permissions(path, Permissions::from_mode(mode | 0o400))?;
+    }
+
+    let set: Result<()> = File::open(path)
+        .and_then(|file| file.set_modified(time))
+        .map_err(Error::from);
+
+    if unreadable {
+        set_permissions(path, Permissions::from_mode(mode))?;
+    }
+
+    set
+}
+
+#[cfg(not(unix))]
+pub fn set_modified(path: &Path, time: SystemTime) -> Result<()> {
+    File::open(path)?.set_modified(time)?;
+
+    Ok(())
+}
+
+///
+/// Check whether any execute bit of the file `path` is set.
+///
+#[cfg(unix)]
+fn is_executable(path: &Path) -> Result<bool> {
+    use std::os::unix::fs::{ PermissionsExt };
+
+    Ok(symlink_metadata(path)?.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_: &Path) -> Result<bool> {
+    Ok(false)
+}
@@ -1,28 +1,79 @@
-use serde::{ Serialize, Deserialize };
-use serde_json::{ to_writer_pretty, from_reader };
+use serde::{ Serialize, Deserialize, Deserializer };
+use serde::de::{ Error as DeserializeError };
+use serde_json::{ to_writer_pretty, from_reader, from_str, to_value, from_value, Value, Map };
 use std::io::{ BufWriter, BufReader };
 use std::fs::{ File, OpenOptions };
-use std::path::{ PathBuf };
+use std::path::{ Path, PathBuf };
 use std::convert::{ From };
 
 use super::error::{ Result, Error };
+use super::maintenance::{ QuotaPolicy };
+use super::numbers::{ SizeUnits, parse_size };
 
 ///
 /// A structure holding the state of the programs settings.
 /// 
 /// # Example
 /// 
-/// ```
+/// ```ignore
 /// let settings_file: PathBuf = PathBuf::from("./settings.json");
 /// let settings: Settings = Settings::new(&settings_file);
 /// ```
 ///
 #[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct Settings {
     /// Use unicode characters in the programs output.
     use_unicode: bool,
     /// Use ANSI formatting in the programs output.
-    use_colors: bool
+    use_colors: bool,
+    /// Hard link the files of a trashed directory which are unchanged since
+    /// it's previous version (Unix only).
+    dedup_dirs: bool,
+    /// Clear the execute bits of restored files (Unix only).
+    restore_strip_exec: bool,
+    /// The most bytes the trash may hold before versions are evicted, which
+    /// may be written with a unit, such as `10GiB`.
+    #[serde(alias = "max_size_bytes", deserialize_with = "deserialize_size")]
+    max_trash_size: Option<u64>,
+    /// How to choose the versions evicted to fit `max_trash_size`.
+    quota_policy: QuotaPolicy,
+    /// The most versions kept of each item, beyond which the oldest are
+    /// deleted as it is trashed again.
+    max_versions_per_entry: Option<usize>,
+    /// The days versions are kept before they are purged as tman starts, or
+    /// 0 to keep them until the trash is emptied.
+    retention_days: u64,
+    /// The units in which sizes are shown.
+    size_units: SizeUnits,
+    /// The locale whose separators are used in numbers, such as `de`, or
+    /// `auto` (the default) for the environment's.
+    number_locale: Option<String>,
+    /// The chrono format, such as `%Y-%m-%d %H:%M`, in which listings show
+    /// the timestamps of versions, which are shown in full when unset.
+    timestamp_format: Option<String>,
+    /// Paths which may not be deleted, along with everything inside them.
+    protected_paths: Vec<String>,
+    /// Ask before trashing a directory, which is `true` when unset.
+    confirm_directories: Option<bool>,
+    /// Ask before emptying the trash, which is `true` when unset.
+    confirm_empty: Option<bool>,
+    /// The directory the trash was moved to, which is only read from the
+    /// settings in the home directory.
+    location: Option<PathBuf>
+}
+
+///
+/// The written forms of a size, which is a number of bytes or a string with a
+/// unit, such as `10GiB`.
+///
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SizeText {
+    /// A number of bytes.
+    Bytes(u64),
+    /// A size with a unit.
+    Text(String)
 }
 
 impl Settings {
@@ -31,7 +82,7 @@ impl Settings {
     /// 
     /// # Examples
     /// 
-    /// ```
+    /// ```ignore
     /// let settings_path: PathBuf = PathBuf:from("./settings.json");
     /// let settings: Settings = Settings::new(&settings_path)?;
     /// ```
@@ -45,6 +96,7 @@ impl Settings {
             .read(true)
             .write(true)
             .create(true)
+            .truncate(false)
             .open(PathBuf::from(path))?;
         
         //
@@ -69,7 +121,7 @@ impl Settings {
     /// 
     /// # Example
     /// 
-    /// ```
+    /// ```ignore
     /// settings.use_unicode();
     /// ```
     ///
@@ -82,11 +134,295 @@ impl Settings {
     /// 
     /// # Example
     /// 
-    /// ```
+    /// ```ignore
     /// settings.use_colors();
     /// ```
     ///
     pub fn use_colors(&self) -> bool {
         self.use_colors
     }
-}
\ No newline at end of file
+
+    ///
+    /// Get the `dedup_dirs` setting.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.dedup_dirs();
+    /// ```
+    ///
+    pub fn dedup_dirs(&self) -> bool {
+        self.dedup_dirs
+    }
+
+    ///
+    /// Get the `restore_strip_exec` setting.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.restore_strip_exec();
+    /// ```
+    ///
+    pub fn restore_strip_exec(&self) -> bool {
+        self.restore_strip_exec
+    }
+
+    ///
+    /// Get the `max_trash_size` setting, in bytes.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.max_trash_size();
+    /// ```
+    ///
+    pub fn max_trash_size(&self) -> Option<u64> {
+        self.max_trash_size
+    }
+
+    ///
+    /// Get the `quota_policy` setting.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.quota_policy();
+    /// ```
+    ///
+    pub fn quota_policy(&self) -> QuotaPolicy {
+        self.quota_policy
+    }
+
+    ///
+    /// Get the `max_versions_per_entry` setting.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.max_versions_per_entry();
+    /// ```
+    ///
+    pub fn max_versions_per_entry(&self) -> Option<usize> {
+        self.max_versions_per_entry
+    }
+
+    ///
+    /// Get the `retention_days` setting.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.retention_days();
+    /// ```
+    ///
+    pub fn retention_days(&self) -> u64 {
+        self.retention_days
+    }
+
+    ///
+    /// Get the `size_units` setting.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.size_units();
+    /// ```
+    ///
+    pub fn size_units(&self) -> SizeUnits {
+        self.size_units
+    }
+
+    ///
+    /// Get the `number_locale` setting, which is `auto` when unset.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.number_locale();
+    /// ```
+    ///
+    pub fn number_locale(&self) -> &str {
+        self.number_locale.as_ref().map_or("auto", |locale| locale.as_str())
+    }
+
+    ///
+    /// Get the `timestamp_format` setting.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.timestamp_format();
+    /// ```
+    ///
+    pub fn timestamp_format(&self) -> Option<&str> {
+        self.timestamp_format.as_deref()
+    }
+
+    ///
+    /// Get the `protected_paths` setting.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.protected_paths();
+    /// ```
+    ///
+    pub fn protected_paths(&self) -> &Vec<String> {
+        &self.protected_paths
+    }
+
+    ///
+    /// Get the `confirm_directories` setting, which is `true` when unset.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.confirm_directories();
+    /// ```
+    ///
+    pub fn confirm_directories(&self) -> bool {
+        self.confirm_directories.unwrap_or(true)
+    }
+
+    ///
+    /// Get the `confirm_empty` setting, which is `true` when unset.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.confirm_empty();
+    /// ```
+    ///
+    pub fn confirm_empty(&self) -> bool {
+        self.confirm_empty.unwrap_or(true)
+    }
+
+    ///
+    /// Get the `location` setting.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.location();
+    /// ```
+    ///
+    pub fn location(&self) -> Option<&Path> {
+        self.location.as_deref()
+    }
+
+    ///
+    /// Set the `location` setting.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.set_location(Some(PathBuf::from("/mnt/big/trash")));
+    /// ```
+    ///
+    pub fn set_location(&mut self, location: Option<PathBuf>) {
+        self.location = location;
+    }
+
+    ///
+    /// Write the settings to the JSON file, path, replacing it's contents.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.save(&PathBuf::from("./settings.json"))?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Throughs a errors for IO and JSON.
+    ///
+    pub fn save(&self, path: &PathBuf) -> Result<()> {
+        to_writer_pretty(BufWriter::new(File::create(path)?), self)?;
+
+        Ok(())
+    }
+
+    ///
+    /// Get every setting as it's key and it's value written as JSON, ordered
+    /// by key.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let values: Vec<(String, String)> = settings.values()?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Throughs a errors for JSON.
+    ///
+    pub fn values(&self) -> Result<Vec<(String, String)>> {
+        Ok(self.object()?
+            .into_iter()
+            .map(|(key, value)| (key, value.to_string()))
+            .collect())
+    }
+
+    ///
+    /// Set the setting `key` to `value`, which is read as JSON, or as a
+    /// string if it is not valid JSON, so that `true`, `1024`, `null` and
+    /// `oldest-first` are all accepted.
+    /// The location of the trash is only changed by moving it.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// settings.set("use_unicode", "true")?;
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Fails when there is no setting `key`, and when `value` is not valid
+    /// for it, leaving the settings as they were.
+    ///
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let mut object: Map<String, Value> = self.object()?;
+
+        if key == "location" || !object.contains_key(key) {
+            Err(Error::InvalidSetting(key.to_string()))?;
+        }
+
+        object.insert(key.to_string(), from_str(value).unwrap_or_else(|_| Value::String(value.to_string())));
+        *self = from_value(Value::Object(object)).map_err(|_| Error::InvalidSettingValue(key.to_string(), value.to_string()))?;
+
+        Ok(())
+    }
+
+    ///
+    /// Get the settings as a JSON object, including those which are unset.
+    ///
+    fn object(&self) -> Result<Map<String, Value>> {
+        match to_value(self)? {
+            Value::Object(object) => Ok(object),
+            _ => Err(Error::Unknown)
+        }
+    }
+}
+
+///
+/// Deserialize an optional size in any of the forms described by `SizeText`.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let size: Option<u64> = deserialize_size(deserializer)?;
+/// ```
+///
+fn deserialize_size<'de, D>(deserializer: D) -> std::result::Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>
+{
+    match Option::<SizeText>::deserialize(deserializer)? {
+        Some(SizeText::Bytes(bytes)) => Ok(Some(bytes)),
+        Some(SizeText::Text(text)) => parse_size(&text)
+            .map(Some)
+            .ok_or_else(|| D::Error::custom(format!("'{}' is not a size", text))),
+        None => Ok(None)
+    }
+}
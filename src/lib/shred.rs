@@ -0,0 +1,102 @@
+use std::io::{ Write };
+use std::fs::{ OpenOptions, File, Metadata, symlink_metadata, remove_file };
+use std::path::{ Path, PathBuf };
+
+use super::error::{ Result, Context };
+use super::dedup::{ links };
+use super::transfer::{ remove_path };
+use super::walk::{ walk };
+
+///
+/// The size, in bytes, of the buffer files are overwritten from, which bounds
+/// the memory used however large they are.
+///
+const BUFFER_SIZE: usize = 64 * 1024;
+
+///
+/// Overwrite every regular file in the tree `root`, which may itself be a
+/// regular file, with zeros and truncate it, then remove the tree.
+/// `shredded` is called with the path and size of each file once it is
+/// overwritten.
+/// Files with other hard links, such as versions sharing their data, are only
+/// unlinked, so the last of their links is overwritten in their place.
+/// Symbolic links and directories are only removed.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// shred(Path::new("./data/56f188aa-1122-41a1-b2f2-e2f0008db54a"), |_, _| Ok(()))?;
+/// ```
+/// 
+/// # Errors
+/// 
+/// Fails if any file can not be overwritten, or the tree can not be removed,
+/// leaving the files which were not yet overwritten in place.
+///
+pub fn shred<F>(root: &Path, mut shredded: F) -> Result<()>
+where
+    F: FnMut(&Path, u64) -> Result<()>
+{
+    let paths: Vec<PathBuf> = if symlink_metadata(root).context("reading", root)?.is_dir() {
+        walk(root, None)?
+    } else {
+        vec![ root.to_path_buf() ]
+    };
+
+    for path in paths {
+        let metadata: Metadata = symlink_metadata(&path).context("reading", &path)?;
+
+        if !metadata.is_file() {
+            continue;
+        }
+
+        if links(&path).context("reading", &path)? > 1 {
+            remove_file(&path).context("removing", &path)?;
+
+            continue;
+        }
+
+        overwrite(&path, metadata.len()).context("overwriting", &path)?;
+        shredded(&path, metadata.len())?;
+    }
+
+    //
+    // A root which is itself a linked file was unlinked above.
+    //
+    if symlink_metadata(root).is_err() {
+        return Ok(());
+    }
+
+    remove_path(root).context("removing", root)
+}
+
+///
+/// Overwrite the first `length` bytes of the file `path` with zeros, making
+/// sure they reach the disk, then truncate it.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// overwrite(Path::new("./Bilbo.txt"), 1024)?;
+/// ```
+/// 
+/// # Errors
+/// 
+/// Fails if the file can not be opened, written or truncated.
+///
+fn overwrite(path: &Path, length: u64) -> std::io::Result<()> {
+    let mut file: File = OpenOptions::new().write(true).open(path)?;
+    let buffer: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
+    let mut remaining: u64 = length;
+
+    while remaining > 0 {
+        let chunk: usize = remaining.min(BUFFER_SIZE as u64) as usize;
+
+        file.write_all(&buffer[..chunk])?;
+        remaining -= chunk as u64;
+    }
+
+    file.sync_all()?;
+    file.set_len(0)?;
+    file.sync_all()
+}
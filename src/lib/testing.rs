@@ -0,0 +1,68 @@
+use std::fs::{ create_dir_all, remove_dir_all };
+use std::path::{ Path, PathBuf };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::env::{ temp_dir };
+use std::process::{ id };
+
+///
+/// The number of scratch directories made by this process, which keeps their
+/// names apart while tests run in parallel.
+///
+static SCRATCHES: AtomicUsize = AtomicUsize::new(0);
+
+///
+/// A directory for a single test to work in, which is removed, with
+/// everything in it, when it is dropped.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let scratch: Scratch = Scratch::new();
+/// let cache: Cache = Cache::new(&scratch.join("cache.json"))?;
+/// ```
+///
+pub struct Scratch {
+    /// The directory.
+    path: PathBuf
+}
+
+impl Scratch {
+    ///
+    /// Create an empty scratch directory.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let scratch: Scratch = Scratch::new();
+    /// ```
+    ///
+    pub fn new() -> Scratch {
+        let path: PathBuf = temp_dir().join(format!("tman-test-{}-{}", id(), SCRATCHES.fetch_add(1, Ordering::SeqCst)));
+
+        remove_dir_all(&path).unwrap_or_default();
+        create_dir_all(&path).unwrap();
+
+        Scratch {
+            path
+        }
+    }
+
+    ///
+    /// Get a path inside the scratch directory.
+    /// 
+    /// # Example
+    /// 
+    /// ```ignore
+    /// let path: PathBuf = scratch.join("cache.json");
+    /// ```
+    ///
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.path.join(path)
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        remove_dir_all(&self.path).unwrap_or_default();
+    }
+}
@@ -0,0 +1,396 @@
+use std::io;
+use std::io::{ Read, BufReader };
+use std::fs::{ rename, copy, create_dir, read_dir, read_link, remove_dir_all, remove_file, set_permissions, symlink_metadata, metadata, canonicalize, File, Metadata };
+use std::path::{ Path, PathBuf };
+
+use super::error::{ Result, Error };
+use super::permissions::{ set_modified };
+use super::cache::{ Kind };
+
+///
+/// Move a file, symbolic link or directory tree from `from` to `to`, copying
+/// it and removing the original when the two are on different filesystems.
+/// The original is only removed once the copy is verified to match it.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// move_path(Path::new("/mnt/usb/build"), Path::new("/home/Bilbo/.tman/data/build"))?;
+/// ```
+/// 
+/// # Errors
+/// 
+/// Fails if the path can be neither renamed nor copied, or the copy differs
+/// from the original, in which case any partial copy is removed.
+///
+pub fn move_path(from: &Path, to: &Path) -> Result<()> {
+    match rename(from, to) {
+        Err(ref error) if is_cross_device(error) => {
+            let copied: Result<()> = copy_path(from, to).and_then(|_| if same_tree(from, to)? {
+                Ok(())
+            } else {
+                Err(Error::CopyMismatch(from.display().to_string()))
+            });
+
+            if let Err(error) = copied {
+                if symlink_metadata(to).is_ok() {
+                    remove_path(to)?;
+                }
+
+                Err(error)?;
+            }
+
+            remove_path(from)
+        },
+        result => Ok(result?)
+    }
+}
+
+///
+/// Remove a file, symbolic link or directory tree.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// remove_path(Path::new("./Bilbo.txt"))?;
+/// ```
+///
+pub fn remove_path(path: &Path) -> Result<()> {
+    if symlink_metadata(path)?.is_dir() {
+        remove_dir_all(path)?;
+    } else {
+        remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+///
+/// Resolve every symbolic link in `path`, including those of ancestors, even
+/// if `path` itself does not exist.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let resolved: PathBuf = resolve(Path::new("./missing/Bilbo.txt"))?;
+/// ```
+///
+pub fn resolve(path: &Path) -> Result<PathBuf> {
+    let mut ancestor: &Path = path;
+    let mut rest: Vec<&std::ffi::OsStr> = vec![];
+
+    loop {
+        if let Ok(resolved) = canonicalize(if ancestor.as_os_str().is_empty() { Path::new(".") } else { ancestor }) {
+            return Ok(rest.iter().rev().fold(resolved, |resolved, component| resolved.join(component)));
+        }
+
+        match (ancestor.parent(), ancestor.file_name()) {
+            (Some(parent), Some(file_name)) => {
+                rest.push(file_name);
+                ancestor = parent;
+            },
+            _ => return Ok(canonicalize(path)?)
+        }
+    }
+}
+
+///
+/// Check whether `left` and `right` are the same file, following symbolic
+/// links.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let same: bool = same_file(Path::new("./Bilbo.txt"), Path::new("./Frodo.txt"));
+/// ```
+///
+#[cfg(unix)]
+pub fn same_file(left: &Path, right: &Path) -> bool {
+    use std::os::unix::fs::{ MetadataExt };
+
+    match (metadata(left), metadata(right)) {
+        (Ok(left), Ok(right)) => left.dev() == right.dev() && left.ino() == right.ino(),
+        _ => false
+    }
+}
+
+#[cfg(not(unix))]
+pub fn same_file(left: &Path, right: &Path) -> bool {
+    match (metadata(left), canonicalize(left), canonicalize(right)) {
+        (Ok(_), Ok(left), Ok(right)) => left == right,
+        _ => false
+    }
+}
+
+///
+/// Check whether `left` and `right` are on the same filesystem, judging paths
+/// which do not exist by their nearest existing ancestor, and following
+/// symbolic links.
+/// Where devices can not be compared, they are assumed to be the same.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let renamed: bool = same_device(Path::new("/home/Bilbo/.tman/data"), Path::new("/mnt/usb/Bilbo.txt"));
+/// ```
+///
+#[cfg(unix)]
+pub fn same_device(left: &Path, right: &Path) -> bool {
+    use std::os::unix::fs::{ MetadataExt };
+
+    let device = |path: &Path| path.ancestors()
+        .find_map(|ancestor| metadata(if ancestor.as_os_str().is_empty() { Path::new(".") } else { ancestor }).ok())
+        .map(|metadata| metadata.dev());
+
+    match (device(left), device(right)) {
+        (Some(left), Some(right)) => left == right,
+        _ => true
+    }
+}
+
+#[cfg(not(unix))]
+pub fn same_device(_left: &Path, _right: &Path) -> bool {
+    true
+}
+
+///
+/// Find the nearest ancestor of `path` which is a symbolic link, along with
+/// it's target.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let link: Option<(PathBuf, PathBuf)> = linked_ancestor(Path::new("/home/Bilbo/usb/Bilbo.txt"));
+/// ```
+///
+pub fn linked_ancestor(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    path.ancestors()
+        .skip(1)
+        .filter(|ancestor| symlink_metadata(ancestor).map(|metadata| metadata.file_type().is_symlink()).unwrap_or(false))
+        .find_map(|ancestor| read_link(ancestor).ok().map(|target| (ancestor.to_path_buf(), target)))
+}
+
+///
+/// Check whether the trees at `left` and `right` hold the same files,
+/// directories and symbolic links, with the same contents, without
+/// following symbolic links.
+/// Special files, which could block if opened, are only compared by type.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let verified: bool = same_tree(Path::new("/mnt/usb/build"), Path::new("./build"))?;
+/// ```
+/// 
+/// # Errors
+/// 
+/// Fails if either tree can not be read.
+///
+fn same_tree(left: &Path, right: &Path) -> Result<bool> {
+    let left_metadata: Metadata = symlink_metadata(left)?;
+    let right_metadata: Metadata = symlink_metadata(right)?;
+
+    if left_metadata.file_type().is_symlink() || right_metadata.file_type().is_symlink() {
+        return Ok(right_metadata.file_type().is_symlink() && left_metadata.file_type().is_symlink() && read_link(left)? == read_link(right)?);
+    }
+
+    if left_metadata.is_dir() || right_metadata.is_dir() {
+        if !(left_metadata.is_dir() && right_metadata.is_dir()) || read_dir(left)?.count() != read_dir(right)?.count() {
+            return Ok(false);
+        }
+
+        for child in read_dir(left)? {
+            let child: PathBuf = child?.path();
+
+            if !same_tree(&child, &right.join(child.file_name().unwrap()))? {
+                return Ok(false);
+            }
+        }
+
+        return Ok(true);
+    }
+
+    if !left_metadata.is_file() || !right_metadata.is_file() {
+        return Ok(left_metadata.file_type() == right_metadata.file_type());
+    }
+
+    if left_metadata.len() != right_metadata.len() {
+        return Ok(false);
+    }
+
+    let mut left_reader: BufReader<File> = BufReader::new(File::open(left)?);
+    let mut right_reader: BufReader<File> = BufReader::new(File::open(right)?);
+    let mut left_buffer: [u8; 8192] = [0; 8192];
+    let mut right_buffer: [u8; 8192] = [0; 8192];
+
+    loop {
+        let read: usize = left_reader.read(&mut left_buffer)?;
+
+        if read == 0 {
+            return Ok(true);
+        }
+
+        right_reader.read_exact(&mut right_buffer[..read])?;
+
+        if left_buffer[..read] != right_buffer[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+///
+/// Copy a file, symbolic link or directory tree, without following symbolic
+/// links, keeping permissions and modification times.
+/// Special files, such as named pipes, are refused rather than read, which
+/// could block forever.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// copy_path(Path::new("/mnt/usb/build"), Path::new("./build"))?;
+/// ```
+/// 
+/// # Errors
+/// 
+/// Fails if any file can not be read or written, or is a special file,
+/// leaving a partial copy.
+///
+pub fn copy_path(from: &Path, to: &Path) -> Result<()> {
+    let from_metadata: Metadata = symlink_metadata(from)?;
+    let file_type = from_metadata.file_type();
+
+    if file_type.is_symlink() {
+        return copy_link(from, to);
+    }
+
+    if file_type.is_dir() {
+        create_dir(to)?;
+
+        for child in read_dir(from)? {
+            let child: PathBuf = child?.path();
+
+            copy_path(&child, &to.join(child.file_name().unwrap()))?;
+        }
+    } else if file_type.is_file() {
+        copy(from, to)?;
+    } else {
+        Err(Error::UncopyableFile(from.display().to_string(), Kind::of(&file_type).name()))?;
+    }
+
+    //
+    // A directory's permissions and modification time are copied after it's
+    // children, which could not be created inside a read only directory and
+    // would update it's modification time.
+    // Modification times are kept where the platform allows it.
+    //
+    set_permissions(to, from_metadata.permissions())?;
+
+    if let Ok(modified) = from_metadata.modified() {
+        set_modified(to, modified).unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_link(from: &Path, to: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(read_link(from)?, to)?;
+
+    Ok(())
+}
+
+#[cfg(windows)]
+fn copy_link(from: &Path, to: &Path) -> Result<()> {
+    let target: PathBuf = read_link(from)?;
+
+    if from.parent().map_or(target.clone(), |parent| parent.join(&target)).is_dir() {
+        std::os::windows::fs::symlink_dir(target, to)?;
+    } else {
+        std::os::windows::fs::symlink_file(target, to)?;
+    }
+
+    Ok(())
+}
+
+///
+/// Check whether an error was caused by renaming across filesystems.
+///
+#[cfg(unix)]
+fn is_cross_device(error: &io::Error) -> bool {
+    // EXDEV
+    error.raw_os_error() == Some(18)
+}
+
+#[cfg(windows)]
+fn is_cross_device(error: &io::Error) -> bool {
+    // ERROR_NOT_SAME_DEVICE
+    error.raw_os_error() == Some(17)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use super::super::testing::{ Scratch };
+    use std::fs::{ create_dir_all, write };
+    use std::process::{ Command };
+
+    ///
+    /// Make a named pipe at `path`, which blocks whoever opens it until the
+    /// other end is opened.
+    ///
+    fn mkfifo(path: &Path) {
+        assert!(Command::new("mkfifo").arg(path).status().unwrap().success());
+    }
+
+    #[test]
+    fn copy_path_refuses_a_named_pipe() {
+        let scratch: Scratch = Scratch::new();
+
+        mkfifo(&scratch.join("pipe"));
+
+        assert!(matches!(copy_path(&scratch.join("pipe"), &scratch.join("copy")), Err(Error::UncopyableFile(_, "named pipe"))));
+        assert!(symlink_metadata(scratch.join("copy")).is_err());
+    }
+
+    #[test]
+    fn copy_path_refuses_a_tree_holding_a_named_pipe() {
+        let scratch: Scratch = Scratch::new();
+
+        create_dir_all(scratch.join("build/logs")).unwrap();
+        write(scratch.join("build/Bilbo.txt"), "Bilbo").unwrap();
+        mkfifo(&scratch.join("build/logs/pipe"));
+
+        match copy_path(&scratch.join("build"), &scratch.join("copy")) {
+            Err(Error::UncopyableFile(path, _)) => assert_eq!(path, scratch.join("build/logs/pipe").display().to_string()),
+            _ => panic!("the named pipe was copied")
+        }
+    }
+
+    #[test]
+    fn copy_path_copies_files_directories_and_links() {
+        let scratch: Scratch = Scratch::new();
+
+        create_dir_all(scratch.join("build/logs")).unwrap();
+        write(scratch.join("build/Bilbo.txt"), "Bilbo").unwrap();
+        std::os::unix::fs::symlink("../Bilbo.txt", scratch.join("build/logs/latest")).unwrap();
+
+        copy_path(&scratch.join("build"), &scratch.join("copy")).unwrap();
+
+        assert!(same_tree(&scratch.join("build"), &scratch.join("copy")).unwrap());
+        write(scratch.join("copy/Bilbo.txt"), "Frodo").unwrap();
+        assert!(!same_tree(&scratch.join("build"), &scratch.join("copy")).unwrap());
+    }
+
+    #[test]
+    fn same_tree_compares_named_pipes_without_opening_them() {
+        let scratch: Scratch = Scratch::new();
+
+        mkfifo(&scratch.join("left"));
+        mkfifo(&scratch.join("right"));
+        write(scratch.join("file"), "").unwrap();
+
+        assert!(same_tree(&scratch.join("left"), &scratch.join("right")).unwrap());
+        assert!(!same_tree(&scratch.join("left"), &scratch.join("file")).unwrap());
+        assert!(!same_tree(&scratch.join("file"), &scratch.join("left")).unwrap());
+    }
+}
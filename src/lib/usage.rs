@@ -0,0 +1,71 @@
+use std::fs::{ Metadata, read_dir, symlink_metadata };
+use std::path::{ Path, PathBuf };
+
+use super::error::{ Result };
+use super::dedup::{ files };
+
+///
+/// Measure the apparent size, in bytes, of `root`, which may be a single file
+/// or a directory tree, without following symbolic links.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let size: u64 = size(Path::new("./build"))?;
+/// ```
+///
+pub fn size(root: &Path) -> Result<u64> {
+    let metadata = symlink_metadata(root)?;
+
+    if metadata.is_dir() {
+        let mut size: u64 = 0;
+
+        for file in files(root)? {
+            size += symlink_metadata(&file)?.len();
+        }
+
+        Ok(size)
+    } else {
+        Ok(metadata.len())
+    }
+}
+
+///
+/// Measure the apparent size, in bytes, of `root` like `size`, counting only
+/// what can be read and collecting the paths which could not be into
+/// `skipped`.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let size: u64 = measure(Path::new("./build"), &mut skipped);
+/// ```
+///
+pub fn measure(root: &Path, skipped: &mut Vec<PathBuf>) -> u64 {
+    let metadata: Metadata = match symlink_metadata(root) {
+        Ok(metadata) => metadata,
+        Err(_) => {
+            skipped.push(root.to_path_buf());
+
+            return 0;
+        }
+    };
+
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    let mut size: u64 = 0;
+
+    match read_dir(root) {
+        Ok(children) => for child in children {
+            match child {
+                Ok(child) => size += measure(&child.path(), skipped),
+                Err(_) => skipped.push(root.to_path_buf())
+            }
+        },
+        Err(_) => skipped.push(root.to_path_buf())
+    }
+
+    size
+}
@@ -0,0 +1,64 @@
+use std::fs::{ read_dir, symlink_metadata };
+use std::path::{ Path, PathBuf };
+
+use super::error::{ Result, Context };
+
+///
+/// Collect the paths in the directory tree `root`, excluding `root` itself,
+/// listing each directory before it's contents and siblings in sorted order.
+/// Symbolic links are not followed, and the contents of directories at
+/// `max_depth`, where the children of `root` are at depth 1, are left out.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// let paths: Vec<PathBuf> = walk(Path::new("./build"), Some(2))?;
+/// ```
+/// 
+/// # Errors
+/// 
+/// Fails if any directory in the tree can not be read.
+///
+pub fn walk(root: &Path, max_depth: Option<usize>) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = vec![];
+
+    walk_into(root, 1, max_depth, &mut paths)?;
+
+    Ok(paths)
+}
+
+///
+/// Collect the paths inside `directory`, which is at `depth - 1`, into
+/// `paths`, as described by `walk`.
+/// 
+/// # Example
+/// 
+/// ```ignore
+/// walk_into(Path::new("./build"), 1, None, &mut paths)?;
+/// ```
+///
+fn walk_into(directory: &Path, depth: usize, max_depth: Option<usize>, paths: &mut Vec<PathBuf>) -> Result<()> {
+    if max_depth.is_some_and(|max_depth| depth > max_depth) {
+        return Ok(());
+    }
+
+    let mut children: Vec<PathBuf> = vec![];
+
+    for child in read_dir(directory).context("reading", directory)? {
+        children.push(child.context("reading", directory)?.path());
+    }
+
+    children.sort();
+
+    for child in children {
+        let is_dir: bool = symlink_metadata(&child).context("reading", &child)?.is_dir();
+
+        paths.push(child.clone());
+
+        if is_dir {
+            walk_into(&child, depth + 1, max_depth, paths)?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,11 +1,14 @@
-mod lib;
+use std::process::{ exit };
 
-use lib::{ TMan };
-use lib::error::{ finish };
+use tman::api::{ TMan, Result, finish };
 
 fn main() {
-    match TMan::new() {
-        Ok(mut tman) => finish(tman.main()),
-        error => finish(error),
-    }
-}
\ No newline at end of file
+    let result: Result<()> = TMan::new().and_then(|mut tman| tman.main());
+    let code: i32 = match &result {
+        Ok(_) => 0,
+        Err(error) => error.exit_code()
+    };
+
+    finish(result);
+    exit(code);
+}
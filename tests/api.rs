@@ -0,0 +1,412 @@
+//!
+//! Checks on the stable interface, `tman::api`, which may only change in
+//! major releases.
+//!
+
+use std::env::{ var_os };
+use std::fs::{ read_to_string, write };
+use std::path::{ Path, PathBuf };
+use std::process::{ Command, Output };
+use std::ffi::{ OsStr };
+use regex::{ Regex };
+use serde_json::{ Value, from_str };
+
+use tman::api::{ TMan, DeleteOptions, RestoreOptions, OnConflict, ListOptions, SortKey, TypeFilter, Error, Result, finish };
+
+///
+/// The public interface, as `render` lists it, with one item on each line.
+///
+const SNAPSHOT: &str = "tests/api.txt";
+
+///
+/// The unstable traits which the compiler implements alongside derived ones,
+/// which come and go between nightly toolchains.
+///
+const MARKERS: &[&str] = &[ "StructuralPartialEq", "TrivialClone" ];
+
+///
+/// The crates of the standard library, whose items are named as they are
+/// written.
+///
+const STANDARD: &[&str] = &[ "std", "core", "alloc" ];
+
+///
+/// Build the documentation of the library as JSON, which needs a nightly
+/// toolchain, returning `None` when there is none.
+/// Hidden items are documented too, as the JSON does not inline the items
+/// re-exported from hidden modules.
+///
+/// # Example
+///
+/// ```
+/// let documentation: Option<Value> = documentation();
+/// ```
+///
+fn documentation() -> Option<Value> {
+    let manifest: &Path = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target: PathBuf = manifest.join("target").join("api");
+    let output: Output = Command::new("rustup")
+        .args([ "run", "nightly", "cargo", "rustdoc", "--lib", "--quiet", "--manifest-path" ])
+        .arg(manifest.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target)
+        .args([ "--", "-Z", "unstable-options", "--output-format", "json", "--document-hidden-items" ])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+
+        return None;
+    }
+
+    Some(from_str(&read_to_string(target.join("doc").join("tman.json")).unwrap()).unwrap())
+}
+
+///
+/// List the items re-exported by `tman::api`, with their fields, variants,
+/// methods and trait implementations, sorted.
+///
+/// # Example
+///
+/// ```
+/// let items: Vec<String> = render(&documentation);
+/// ```
+///
+fn render(documentation: &Value) -> Vec<String> {
+    let index: &Value = &documentation["index"];
+    let paths: &Value = &documentation["paths"];
+    let item = |id: &Value| -> &Value { &index[id.to_string()] };
+    let api: &Value = index.as_object().unwrap().values()
+        .find(|candidate| candidate["name"] == "api" && candidate["inner"]["module"].is_object())
+        .unwrap();
+    let mut lines: Vec<String> = vec![];
+
+    for export in api["inner"]["module"]["items"].as_array().unwrap() {
+        let export: &Value = &item(export)["inner"]["use"];
+        let target: &Value = item(&export["id"]);
+        let path: String = format!("tman::api::{}", export["name"].as_str().unwrap());
+        let generics: String = generics(paths, &target["inner"].as_object().unwrap().values().next().unwrap()["generics"]);
+
+        if let Some(function) = target["inner"].get("function") {
+            lines.push(format!("pub fn {}{}", path, signature(paths, function)));
+            continue;
+        }
+
+        if let Some(alias) = target["inner"].get("type_alias") {
+            lines.push(format!("pub type {}{} = {}", path, generics, render_type(paths, &alias["type"])));
+            continue;
+        }
+
+        let (keyword, impls): (&str, &Value) = match target["inner"].as_object().unwrap().iter().next().unwrap() {
+            (kind, inner) if kind == "struct" || kind == "enum" => (kind.as_str(), &inner["impls"]),
+            (kind, _) => panic!("{} is a {}, which is not rendered", path, kind)
+        };
+
+        lines.push(format!("pub {} {}{}", keyword, path, generics));
+
+        let fields: Vec<&Value> = match &target["inner"]["struct"]["kind"]["plain"]["fields"] {
+            Value::Array(fields) => fields.iter().collect(),
+            _ => vec![]
+        };
+
+        for field in fields.into_iter().map(item) {
+            lines.push(format!("pub {}::{}: {}", path, field["name"].as_str().unwrap(), render_type(paths, &field["inner"]["struct_field"])));
+        }
+
+        for variant in target["inner"]["enum"]["variants"].as_array().into_iter().flatten().map(item) {
+            let fields: Vec<String> = variant["inner"]["variant"]["kind"]["struct"]["fields"].as_array().into_iter().flatten()
+                .map(item)
+                .map(|field| format!("{}: {}", field["name"].as_str().unwrap(), render_type(paths, &field["inner"]["struct_field"])))
+                .collect();
+
+            let types: Vec<String> = variant["inner"]["variant"]["kind"]["tuple"].as_array().into_iter().flatten()
+                .map(|field| match field {
+                    Value::Null => String::from("_"),
+                    field => render_type(paths, &item(field)["inner"]["struct_field"])
+                })
+                .collect();
+
+            match (fields.is_empty(), types.is_empty()) {
+                (false, _) => lines.push(format!("{}::{} {{ {} }}", path, variant["name"].as_str().unwrap(), fields.join(", "))),
+                (_, false) => lines.push(format!("{}::{}({})", path, variant["name"].as_str().unwrap(), types.join(", "))),
+                _ => lines.push(format!("{}::{}", path, variant["name"].as_str().unwrap()))
+            }
+        }
+
+        for implementation in impls.as_array().unwrap().iter().map(|id| &item(id)["inner"]["impl"]) {
+            if implementation["is_synthetic"] == true || !implementation["blanket_impl"].is_null() {
+                continue;
+            }
+
+            if MARKERS.contains(&implementation["trait"]["path"].as_str().unwrap_or_default()) {
+                continue;
+            }
+
+            if !implementation["trait"].is_null() {
+                lines.push(format!("impl {} for {}{}", render_path(paths, &implementation["trait"]), path, generics));
+                continue;
+            }
+
+            for method in implementation["items"].as_array().unwrap().iter().map(item) {
+                if method["visibility"] == "public" {
+                    lines.push(format!("pub fn {}::{}{}", path, method["name"].as_str().unwrap(), signature(paths, &method["inner"]["function"])));
+                }
+            }
+        }
+    }
+
+    lines.sort();
+    lines
+}
+
+///
+/// Render the generic parameters of an item, such as `<'a>`.
+///
+/// # Example
+///
+/// ```
+/// let generics: String = generics(paths, &item["inner"]["struct"]["generics"]);
+/// ```
+///
+fn generics(paths: &Value, generics: &Value) -> String {
+    let parameters: Vec<String> = generics["params"].as_array().unwrap().iter()
+        .map(|parameter| {
+            let bounds: Vec<String> = parameter["kind"]["type"]["bounds"].as_array().into_iter().flatten().map(|value| render_bound(paths, value)).collect();
+
+            match bounds.is_empty() {
+                true => parameter["name"].as_str().unwrap().to_string(),
+                false => format!("{}: {}", parameter["name"].as_str().unwrap(), bounds.join(" + "))
+            }
+        })
+        .collect();
+
+    match parameters.is_empty() {
+        true => String::new(),
+        false => format!("<{}>", parameters.join(", "))
+    }
+}
+
+///
+/// Render the generic parameters, inputs, output and bounds of a function.
+///
+/// # Example
+///
+/// ```
+/// let signature: String = signature(paths, &item["inner"]["function"]);
+/// ```
+///
+fn signature(paths: &Value, function: &Value) -> String {
+    let inputs: Vec<String> = function["sig"]["inputs"].as_array().unwrap().iter()
+        .map(|input| match (input[0].as_str().unwrap(), render_type(paths, &input[1]).as_str()) {
+            ("self", "Self") => String::from("self"),
+            ("self", "&Self") => String::from("&self"),
+            ("self", "&mut Self") => String::from("&mut self"),
+            (name, kind) => format!("{}: {}", name, kind)
+        })
+        .collect();
+    let output: String = match &function["sig"]["output"] {
+        Value::Null => String::new(),
+        output => format!(" -> {}", render_type(paths, output))
+    };
+    let predicates: Vec<String> = function["generics"]["where_predicates"].as_array().unwrap().iter()
+        .map(|predicate| &predicate["bound_predicate"])
+        .map(|predicate| format!("{}: {}", render_type(paths, &predicate["type"]), predicate["bounds"].as_array().unwrap().iter().map(|value| render_bound(paths, value)).collect::<Vec<String>>().join(" + ")))
+        .collect();
+    let clause: String = match predicates.is_empty() {
+        true => String::new(),
+        false => format!(" where {}", predicates.join(", "))
+    };
+
+    format!("{}({}){}{}", generics(paths, &function["generics"]), inputs.join(", "), output, clause)
+}
+
+///
+/// Render a bound on a generic parameter.
+///
+/// # Example
+///
+/// ```
+/// let bound: String = render_bound(paths, &bound);
+/// ```
+///
+fn render_bound(paths: &Value, bound: &Value) -> String {
+    match bound.get("trait_bound") {
+        Some(bound) => render_path(paths, &bound["trait"]),
+        None => bound["outlives"].as_str().unwrap().to_string()
+    }
+}
+
+///
+/// Render a path to a type or trait, with it's generic arguments.
+///
+/// # Example
+///
+/// ```
+/// let path: String = render_path(paths, &implementation["trait"]);
+/// ```
+///
+fn render_path(paths: &Value, path: &Value) -> String {
+    let external: &Value = &paths[path["id"].to_string()];
+
+    //
+    // Items from other crates, besides the standard library, are named after
+    // their crate, so that the `std` and `regex` errors, say, are told apart,
+    // but not by the modules they are defined in, which change between
+    // versions of the crate.
+    //
+    let name: String = match (external["crate_id"].as_u64(), external["path"][0].as_str()) {
+        (Some(crate_id), Some(krate)) if crate_id != 0 && !STANDARD.contains(&krate) => format!("{}::{}", krate, external["path"].as_array().unwrap().last().unwrap().as_str().unwrap()),
+        _ => path["path"].as_str().unwrap().to_string()
+    };
+    let arguments: &Value = &path["args"];
+
+    if let Some(parenthesized) = arguments.get("parenthesized") {
+        let inputs: Vec<String> = parenthesized["inputs"].as_array().unwrap().iter().map(|value| render_type(paths, value)).collect();
+
+        return match &parenthesized["output"] {
+            Value::Null => format!("{}({})", name, inputs.join(", ")),
+            output => format!("{}({}) -> {}", name, inputs.join(", "), render_type(paths, output))
+        };
+    }
+
+    let arguments: Vec<String> = arguments["angle_bracketed"]["args"].as_array().into_iter().flatten()
+        .map(|argument| match argument.get("type") {
+            Some(kind) => render_type(paths, kind),
+            None => argument["lifetime"].as_str().unwrap().to_string()
+        })
+        .collect();
+
+    match arguments.is_empty() {
+        true => name.to_string(),
+        false => format!("{}<{}>", name, arguments.join(", "))
+    }
+}
+
+///
+/// Render a type.
+///
+/// # Example
+///
+/// ```
+/// let kind: String = render_type(paths, &field["inner"]["struct_field"]);
+/// ```
+///
+fn render_type(paths: &Value, kind: &Value) -> String {
+    let (variant, inner): (&String, &Value) = kind.as_object().unwrap().iter().next().unwrap();
+
+    match variant.as_str() {
+        "resolved_path" => render_path(paths, inner),
+        "generic" | "primitive" => inner.as_str().unwrap().to_string(),
+        "borrowed_ref" => format!(
+            "&{}{}{}",
+            inner["lifetime"].as_str().map(|lifetime| format!("{} ", lifetime)).unwrap_or_default(),
+            if inner["is_mutable"] == true { "mut " } else { "" },
+            render_type(paths, &inner["type"])
+        ),
+        "tuple" => format!("({})", inner.as_array().unwrap().iter().map(|value| render_type(paths, value)).collect::<Vec<String>>().join(", ")),
+        "slice" => format!("[{}]", render_type(paths, inner)),
+        "array" => format!("[{}; {}]", render_type(paths, &inner["type"]), inner["len"].as_str().unwrap()),
+        "impl_trait" => format!("impl {}", inner.as_array().unwrap().iter().map(|value| render_bound(paths, value)).collect::<Vec<String>>().join(" + ")),
+        variant => panic!("{} types are not rendered", variant)
+    }
+}
+
+#[test]
+fn public_api_matches_the_snapshot() {
+    let documentation: Value = match documentation() {
+        Some(documentation) => documentation,
+        None => {
+            eprintln!("skipped, as the documentation needs a nightly toolchain to build as JSON");
+            return;
+        }
+    };
+    let rendered: String = render(&documentation).join("\n") + "\n";
+    let snapshot: PathBuf = Path::new(env!("CARGO_MANIFEST_DIR")).join(SNAPSHOT);
+
+    //
+    // An intended change is recorded by running the test with
+    // `UPDATE_SNAPSHOTS` set, and reviewing the difference.
+    //
+    if var_os("UPDATE_SNAPSHOTS").is_some() {
+        write(&snapshot, &rendered).unwrap();
+    }
+
+    assert_eq!(rendered, read_to_string(&snapshot).unwrap(), "the public interface changed, see {}", SNAPSHOT);
+}
+
+#[test]
+#[allow(clippy::type_complexity)]
+fn signatures_are_unchanged() {
+    let _: fn() -> Result<TMan> = TMan::new;
+    let _: fn(&mut TMan) -> Result<()> = TMan::main;
+    let _: fn(&mut TMan) -> Result<()> = TMan::commit;
+    let _: fn(&mut TMan, &Path, &DeleteOptions) -> Result<()> = TMan::delete;
+    let _: fn(&mut TMan, Vec<&OsStr>, &DeleteOptions) -> Result<()> = TMan::delete_each;
+    let _: fn(&mut TMan, &str, &RestoreOptions) -> Result<()> = TMan::restore;
+    let _: fn(&mut TMan, Vec<&str>, &RestoreOptions) -> Result<()> = TMan::restore_each;
+    let _: fn(&mut TMan, &RestoreOptions) -> Result<()> = TMan::restore_all;
+    let _: fn(&TMan, Regex, &ListOptions) -> Result<()> = TMan::list;
+    let _: fn(&mut TMan, &str, Option<&str>, bool, bool) -> Result<()> = TMan::purge;
+    let _: fn(&mut TMan, &TypeFilter, &[String], bool, bool, bool, bool) -> Result<()> = TMan::empty;
+    let _: fn(&Error) -> String = Error::message;
+    let _: fn(&Error) -> i32 = Error::exit_code;
+    let _: fn(Result<()>) = finish;
+}
+
+#[test]
+fn options_are_unchanged() {
+    let _: DeleteOptions = DeleteOptions {
+        verbose: false,
+        allow_protected: false,
+        keep_going: false,
+        yes: false,
+        reason: None,
+        tags: vec![],
+        copy: false,
+        allow_special: false,
+        modified_before: None,
+        no_evict: false
+    };
+    let _: RestoreOptions = RestoreOptions {
+        origin: None,
+        all_origins: false,
+        newest_only: false,
+        version: None,
+        destination: None,
+        interactive: false,
+        strip_exec: false,
+        on_conflict: OnConflict::default(),
+        transaction: None
+    };
+    let _: ListOptions = ListOptions {
+        simple: false,
+        null: false,
+        match_display: false,
+        verbose: false,
+        watch: false,
+        missing: false,
+        indices: false,
+        types: TypeFilter::default(),
+        json: false,
+        quiet_match: false,
+        search_reasons: false,
+        tags: vec![],
+        sort: None,
+        reverse: false,
+        size: false,
+        bytes: false
+    };
+
+    match OnConflict::default() {
+        OnConflict::Skip | OnConflict::Overwrite | OnConflict::Rename => ()
+    }
+
+    match TypeFilter::default() {
+        TypeFilter::All | TypeFilter::Files { strict: _ } | TypeFilter::Directories { strict: _ } => ()
+    }
+
+    match SortKey::Name {
+        SortKey::Name | SortKey::Origin | SortKey::Date | SortKey::Size => ()
+    }
+}
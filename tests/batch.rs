@@ -0,0 +1,95 @@
+mod common;
+
+use std::fs::{ metadata, read_to_string };
+use std::path::{ PathBuf };
+use std::process::{ Output };
+use serde_json::{ Value, from_str };
+
+use common::{ Sandbox, code, printed };
+
+///
+/// Read the records of the log, one per line.
+///
+fn records(sandbox: &Sandbox) -> Vec<Value> {
+    read_to_string(sandbox.home().join("log.json")).unwrap_or_default()
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn a_mixed_batch_runs_every_line_and_commits_once() {
+    let sandbox: Sandbox = Sandbox::new();
+    let bilbo: PathBuf = sandbox.write("Bilbo.txt", "Bilbo");
+    let frodo: PathBuf = sandbox.write("Frodo.txt", "Frodo");
+
+    sandbox.write("batch.txt", "# Trash the hobbits\ndelete Bilbo.txt\nrestore Gandalf.txt\n\ndelete Frodo.txt\n");
+
+    let output: Output = sandbox.run(&[ "--batch", "batch.txt" ]);
+
+    assert_eq!(code(&output), 7, "{}", printed(&output));
+    assert!(printed(&output).contains("line 2: ok"), "{}", printed(&output));
+    assert!(printed(&output).contains("line 3: could not locate 'Gandalf.txt'"), "{}", printed(&output));
+    assert!(printed(&output).contains("line 5: ok"), "{}", printed(&output));
+    assert!(!bilbo.exists());
+    assert!(!frodo.exists());
+    assert_eq!(sandbox.cache()["entries"].as_array().unwrap().len(), 2);
+
+    //
+    // Both deletes are committed together, as a single record.
+    //
+    let records: Vec<Value> = records(&sandbox);
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["steps"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn stop_on_error_skips_the_rest_of_a_batch() {
+    let sandbox: Sandbox = Sandbox::new();
+    let frodo: PathBuf = sandbox.write("Frodo.txt", "Frodo");
+
+    sandbox.write("Bilbo.txt", "Bilbo");
+    sandbox.write("batch.txt", "delete Bilbo.txt\nrestore Gandalf.txt\ndelete Frodo.txt\n");
+
+    let output: Output = sandbox.run(&[ "--batch", "batch.txt", "--stop-on-error" ]);
+
+    assert_eq!(code(&output), 7, "{}", printed(&output));
+    assert!(!printed(&output).contains("line 3"), "{}", printed(&output));
+    assert!(frodo.exists());
+    assert_eq!(sandbox.cache()["entries"].as_array().unwrap().len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn a_batch_in_which_every_line_fails_leaves_the_cache() {
+    use std::os::unix::fs::{ MetadataExt };
+
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("Bilbo.txt", "Bilbo");
+    sandbox.ok(&[ "-D", "Bilbo.txt" ]);
+    sandbox.write("batch.txt", "restore Gandalf.txt\nfrobnicate\n");
+
+    let cache: PathBuf = sandbox.home().join("cache.json");
+    let before: u64 = metadata(&cache).unwrap().ino();
+    let output: Output = sandbox.run(&[ "--batch", "batch.txt" ]);
+
+    assert_eq!(code(&output), 7, "{}", printed(&output));
+    assert!(printed(&output).contains("line 2: invalid command"), "{}", printed(&output));
+    assert_eq!(metadata(&cache).unwrap().ino(), before);
+    assert_eq!(records(&sandbox).len(), 1);
+}
+
+#[test]
+fn a_dry_run_batch_changes_nothing() {
+    let sandbox: Sandbox = Sandbox::new();
+    let bilbo: PathBuf = sandbox.write("Bilbo.txt", "Bilbo");
+
+    sandbox.write("batch.txt", "delete Bilbo.txt\n");
+    sandbox.ok(&[ "--batch", "batch.txt", "--dry-run" ]);
+
+    assert!(bilbo.exists());
+    assert!(records(&sandbox).is_empty());
+}
@@ -0,0 +1,175 @@
+//!
+//! Helpers shared by the integration tests, which run the program against a
+//! home directory and a working directory of their own.
+//! Each test binary uses only some of them.
+//!
+#![allow(dead_code)]
+
+use std::fs::{ create_dir_all, remove_dir_all, read_to_string, write };
+use std::path::{ Path, PathBuf };
+use std::process::{ Command, Output, Stdio };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::env::{ temp_dir };
+use serde_json::{ Value, from_str, to_string };
+
+///
+/// The number of sandboxes made by this process, which keeps their names
+/// apart while tests run in parallel.
+///
+static SANDBOXES: AtomicUsize = AtomicUsize::new(0);
+
+///
+/// A home directory, given to the program as `TMAN_HOME`, and a working
+/// directory to trash from, which are removed when it is dropped.
+/// 
+/// # Example
+/// 
+/// ```
+/// let sandbox: Sandbox = Sandbox::new();
+/// let output: Output = sandbox.run(&[ "-D", "Bilbo.txt" ]);
+/// ```
+///
+pub struct Sandbox {
+    /// The directory holding the others.
+    root: PathBuf
+}
+
+impl Sandbox {
+    ///
+    /// Create an empty home directory and working directory.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// let sandbox: Sandbox = Sandbox::new();
+    /// ```
+    ///
+    pub fn new() -> Sandbox {
+        let root: PathBuf = temp_dir().join(format!("tman-cli-{}-{}", std::process::id(), SANDBOXES.fetch_add(1, Ordering::SeqCst)));
+
+        remove_dir_all(&root).unwrap_or_default();
+        create_dir_all(root.join("work")).unwrap();
+
+        Sandbox {
+            root
+        }
+    }
+
+    ///
+    /// Get the home directory, which may not exist until the program runs.
+    ///
+    pub fn home(&self) -> PathBuf {
+        self.root.join("home")
+    }
+
+    ///
+    /// Get the working directory.
+    ///
+    pub fn work(&self) -> PathBuf {
+        self.root.join("work")
+    }
+
+    ///
+    /// Get the directory holding the versions of items.
+    ///
+    pub fn data(&self) -> PathBuf {
+        self.home().join("data")
+    }
+
+    ///
+    /// Get a path inside the working directory.
+    ///
+    pub fn path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.work().join(path)
+    }
+
+    ///
+    /// Write `contents` to the file `name` in the working directory, creating
+    /// it's parents.
+    ///
+    pub fn write<P: AsRef<Path>>(&self, name: P, contents: &str) -> PathBuf {
+        let path: PathBuf = self.path(name);
+
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(&path, contents).unwrap();
+
+        path
+    }
+
+    ///
+    /// Prepare the program to run with `arguments` in the sandbox, reading
+    /// nothing from stdin.
+    ///
+    pub fn command(&self, arguments: &[&str]) -> Command {
+        let mut command: Command = Command::new(env!("CARGO_BIN_EXE_tman"));
+
+        command.args(arguments)
+            .current_dir(self.work())
+            .env("TMAN_HOME", self.home())
+            .env("NO_COLOR", "1")
+            .stdin(Stdio::null());
+
+        command
+    }
+
+    ///
+    /// Run the program with `arguments` in the sandbox.
+    ///
+    pub fn run(&self, arguments: &[&str]) -> Output {
+        self.command(arguments).output().unwrap()
+    }
+
+    ///
+    /// Run the program with `arguments` in the sandbox, failing the test
+    /// unless it succeeds.
+    ///
+    pub fn ok(&self, arguments: &[&str]) -> String {
+        let output: Output = self.run(arguments);
+
+        assert!(output.status.success(), "{:?} failed: {}", arguments, String::from_utf8_lossy(&output.stderr));
+
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    ///
+    /// Read the cache as JSON.
+    ///
+    pub fn cache(&self) -> Value {
+        from_str(&read_to_string(self.home().join("cache.json")).unwrap()).unwrap()
+    }
+
+    ///
+    /// Replace the cache with `cache`.
+    ///
+    pub fn set_cache(&self, cache: &Value) {
+        write(self.home().join("cache.json"), to_string(cache).unwrap()).unwrap();
+    }
+
+    ///
+    /// Write the settings file.
+    ///
+    pub fn set_settings(&self, settings: &str) {
+        create_dir_all(self.home()).unwrap();
+        write(self.home().join("settings.json"), settings).unwrap();
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        remove_dir_all(&self.root).unwrap_or_default();
+    }
+}
+
+///
+/// Get the exit code of a run.
+///
+pub fn code(output: &Output) -> i32 {
+    output.status.code().unwrap_or(-1)
+}
+
+///
+/// Get everything a run printed, on stdout and stderr.
+///
+pub fn printed(output: &Output) -> String {
+    format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr))
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use std::fs::{ read_dir, read_to_string };
+use std::path::{ PathBuf };
+
+use common::{ Sandbox };
+
+///
+/// Trash three versions of the directory `build`, which differ only in
+/// `changed.txt`, with directories stored incrementally.
+///
+fn trash_builds(sandbox: &Sandbox) {
+    sandbox.set_settings(r#"{ "dedup_dirs": true }"#);
+
+    for version in 1..=3 {
+        sandbox.write("build/shared.txt", "Shared");
+        sandbox.write("build/changed.txt", &format!("Version {}", version));
+        sandbox.ok(&[ "-D", "build", "--yes" ]);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn unchanged_files_are_linked_between_versions() {
+    use std::fs::{ metadata };
+    use std::os::unix::fs::{ MetadataExt };
+
+    let sandbox: Sandbox = Sandbox::new();
+
+    trash_builds(&sandbox);
+
+    let directory: PathBuf = sandbox.data().join(sandbox.cache()["entries"][0]["uuid"].as_str().unwrap());
+    let versions: Vec<PathBuf> = read_dir(&directory).unwrap().map(|version| version.unwrap().path()).collect();
+
+    assert_eq!(versions.len(), 3);
+
+    for version in versions {
+        assert_eq!(metadata(version.join("shared.txt")).unwrap().nlink(), 3);
+        assert_eq!(metadata(version.join("changed.txt")).unwrap().nlink(), 1);
+    }
+}
+
+#[test]
+fn purging_the_middle_version_keeps_the_others() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    trash_builds(&sandbox);
+    sandbox.ok(&[ "--purge", "build", "--version", "2" ]);
+    sandbox.ok(&[ "-R", "build", "--version", "all" ]);
+
+    let mut restored: Vec<PathBuf> = read_dir(sandbox.work()).unwrap()
+        .map(|child| child.unwrap().path())
+        .filter(|path| path.file_name().unwrap().to_string_lossy().starts_with("build_"))
+        .collect();
+    restored.sort();
+
+    assert_eq!(restored.len(), 2);
+
+    for (path, version) in restored.iter().zip(&[ 1, 3 ]) {
+        assert_eq!(read_to_string(path.join("shared.txt")).unwrap(), "Shared");
+        assert_eq!(read_to_string(path.join("changed.txt")).unwrap(), format!("Version {}", version));
+    }
+}
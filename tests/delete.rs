@@ -0,0 +1,231 @@
+mod common;
+
+use std::fs::{ create_dir_all, read_dir, read_to_string, remove_dir_all, write };
+use std::path::{ Path, PathBuf };
+use std::process::{ Command, Output };
+use serde_json::{ Value, from_str };
+
+use common::{ Sandbox, code, printed };
+
+#[test]
+fn tman_home_overrides_the_home_directory() {
+    let sandbox: Sandbox = Sandbox::new();
+    let user: PathBuf = sandbox.path("user");
+
+    sandbox.write("Bilbo.txt", "Bilbo");
+    sandbox.write("Frodo.txt", "Frodo");
+
+    let mut command: Command = sandbox.command(&[ "-D", "Bilbo.txt" ]);
+
+    assert!(command.env("HOME", &user).status().unwrap().success());
+    assert!(sandbox.home().join("cache.json").exists());
+    assert!(!user.join(".tman").exists());
+
+    //
+    // An empty `TMAN_HOME` is the same as none.
+    //
+    let mut command: Command = sandbox.command(&[ "-D", "Frodo.txt" ]);
+
+    assert!(command.env("HOME", &user).env("TMAN_HOME", "").status().unwrap().success());
+    assert!(user.join(".tman/cache.json").exists());
+    assert_eq!(sandbox.cache()["entries"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn porcelain_prints_a_record_per_trashed_path() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("Bilbo.txt", "Bilbo");
+    sandbox.write("Frodo.txt", "Frodo");
+
+    let output: Output = sandbox.run(&[ "-D", "Bilbo.txt", "Frodo.txt", "--porcelain" ]);
+    let cache: Value = sandbox.cache();
+    let lines: Vec<String> = String::from_utf8(output.stdout).unwrap().lines().map(String::from).collect();
+
+    assert!(output.status.success());
+    assert_eq!(lines.len(), 2);
+
+    for (line, name) in lines.iter().zip(&[ "Bilbo.txt", "Frodo.txt" ]) {
+        let record: Value = from_str(line).unwrap();
+        let entry: &Value = cache["entries"].as_array().unwrap().iter().find(|entry| entry["key"]["name"] == *name).unwrap();
+
+        assert_eq!(record.as_object().unwrap().keys().collect::<Vec<&String>>(), vec![ "origin", "uuid", "version" ]);
+        assert_eq!(record["origin"], Value::from(sandbox.path(name).to_str().unwrap()));
+        assert_eq!(record["origin"], entry["key"]["origin"]);
+        assert_eq!(record["uuid"], entry["uuid"]);
+        assert!(sandbox.data().join(record["uuid"].as_str().unwrap()).join(record["version"].as_str().unwrap()).exists());
+    }
+}
+
+#[test]
+fn a_failed_store_leaves_no_version_behind() {
+    let sandbox: Sandbox = Sandbox::new();
+    let path: PathBuf = sandbox.write("Frodo.txt", "Frodo");
+
+    sandbox.write("Bilbo.txt", "Bilbo");
+    sandbox.ok(&[ "-D", "Bilbo.txt" ]);
+    remove_dir_all(sandbox.data()).unwrap();
+    write(sandbox.data(), "").unwrap();
+
+    let output: Output = sandbox.run(&[ "-D", "Frodo.txt" ]);
+
+    assert_eq!(code(&output), 4, "{}", printed(&output));
+    assert!(printed(&output).contains("while creating"), "{}", printed(&output));
+    assert!(path.exists());
+    assert_eq!(sandbox.cache()["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(sandbox.cache()["entries"][0]["key"]["name"], "Bilbo.txt");
+}
+
+#[test]
+fn a_vanished_target_is_reported_missing() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("Shire/Bilbo.txt", "Bilbo");
+
+    let output: Output = sandbox.run(&[ "-D", "Shire", "Shire/Bilbo.txt", "--yes" ]);
+
+    assert_eq!(code(&output), 3, "{}", printed(&output));
+    assert!(printed(&output).contains("could not locate 'Shire/Bilbo.txt'"), "{}", printed(&output));
+    assert_eq!(sandbox.cache()["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(sandbox.cache()["entries"][0]["key"]["name"], "Shire");
+}
+
+#[test]
+fn a_missing_target_is_named() {
+    let sandbox: Sandbox = Sandbox::new();
+    let output: Output = sandbox.run(&[ "-D", "Bilbo.txt" ]);
+
+    assert_eq!(code(&output), 3, "{}", printed(&output));
+    assert!(printed(&output).contains("could not locate 'Bilbo.txt'"), "{}", printed(&output));
+}
+
+//
+// Permissions do not bind the superuser, so this is only tried as anyone
+// else.
+//
+#[cfg(unix)]
+#[test]
+fn an_inaccessible_target_is_named() {
+    use std::fs::{ set_permissions, Permissions };
+    use std::os::unix::fs::{ PermissionsExt };
+
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("Shire/Bilbo.txt", "Bilbo");
+    set_permissions(sandbox.path("Shire"), Permissions::from_mode(0o000)).unwrap();
+
+    if read_dir(sandbox.path("Shire")).is_ok() {
+        set_permissions(sandbox.path("Shire"), Permissions::from_mode(0o755)).unwrap();
+        eprintln!("skipped, permissions are not enforced");
+        return;
+    }
+
+    let output: Output = sandbox.run(&[ "-D", "Shire/Bilbo.txt" ]);
+
+    set_permissions(sandbox.path("Shire"), Permissions::from_mode(0o755)).unwrap();
+
+    assert_eq!(code(&output), 4, "{}", printed(&output));
+    assert!(printed(&output).contains("permission denied for 'Shire/Bilbo.txt'"), "{}", printed(&output));
+}
+
+#[test]
+fn globs_are_expanded_unless_a_file_has_the_name() {
+    let sandbox: Sandbox = Sandbox::new();
+    let literal: PathBuf = sandbox.write("report[1].txt", "Literal");
+    let other: PathBuf = sandbox.write("report1.txt", "Other");
+
+    sandbox.ok(&[ "-D", "report[1].txt" ]);
+
+    assert!(!literal.exists());
+    assert!(other.exists());
+
+    let hidden: PathBuf = sandbox.write(".report2.txt", "Hidden");
+
+    sandbox.ok(&[ "-D", "report[!x].txt" ]);
+
+    assert!(!other.exists());
+    assert!(hidden.exists());
+
+    let output: Output = sandbox.run(&[ "-D", "*.md" ]);
+
+    assert_eq!(code(&output), 3, "{}", printed(&output));
+}
+
+///
+/// Write a nested tree under `root`, returning it's files and their contents.
+///
+fn nested_tree(root: &Path) -> Vec<(PathBuf, String)> {
+    let files: Vec<(PathBuf, String)> = [ "README.md", "src/main.rs", "src/lib/mod.rs", "src/lib/deep/er/leaf.rs" ].iter()
+        .map(|name| (PathBuf::from(name), format!("contents of {}", name)))
+        .collect();
+
+    for (name, contents) in &files {
+        create_dir_all(root.join(name).parent().unwrap()).unwrap();
+        write(root.join(name), contents).unwrap();
+    }
+
+    create_dir_all(root.join("empty")).unwrap();
+
+    files
+}
+
+///
+/// Check that the tree under `root` holds exactly `files`, and the empty
+/// directory `empty`.
+///
+fn assert_tree(root: &Path, files: &[(PathBuf, String)]) {
+    for (name, contents) in files {
+        assert_eq!(&read_to_string(root.join(name)).unwrap(), contents);
+    }
+
+    assert!(root.join("empty").is_dir());
+    assert_eq!(read_dir(root).unwrap().count(), 3);
+}
+
+#[test]
+fn nested_directories_are_trashed_and_restored_whole() {
+    let sandbox: Sandbox = Sandbox::new();
+    let files: Vec<(PathBuf, String)> = nested_tree(&sandbox.path("project"));
+
+    sandbox.ok(&[ "-D", "project", "--yes" ]);
+
+    assert!(!sandbox.path("project").exists());
+    assert_eq!(sandbox.cache()["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(sandbox.cache()["entries"][0]["history"].as_array().unwrap().len(), 1);
+
+    sandbox.ok(&[ "-R", "project" ]);
+
+    assert_tree(&sandbox.path("project"), &files);
+}
+
+//
+// Trashing from another filesystem copies the tree, which can only be tried
+// where `/dev/shm` is a separate tmpfs.
+//
+#[cfg(unix)]
+#[test]
+fn nested_directories_are_trashed_from_another_filesystem() {
+    use std::fs::{ metadata };
+    use std::os::unix::fs::{ MetadataExt };
+
+    let sandbox: Sandbox = Sandbox::new();
+    let shared: PathBuf = Path::new("/dev/shm").join(format!("tman-cli-tree-{}", std::process::id()));
+
+    if create_dir_all(&shared).is_err() || metadata(&shared).unwrap().dev() == metadata(sandbox.work()).unwrap().dev() {
+        remove_dir_all(&shared).unwrap_or_default();
+        eprintln!("skipped, /dev/shm is not another filesystem");
+        return;
+    }
+
+    let files: Vec<(PathBuf, String)> = nested_tree(&shared.join("project"));
+    let trashed: Output = sandbox.run(&[ "-D", shared.join("project").to_str().unwrap(), "--yes" ]);
+    let left: bool = shared.join("project").exists();
+    let restored: Output = sandbox.run(&[ "-R", "project", "--to", "out" ]);
+
+    remove_dir_all(&shared).unwrap_or_default();
+
+    assert_eq!(code(&trashed), 0, "{}", printed(&trashed));
+    assert!(!left);
+    assert_eq!(code(&restored), 0, "{}", printed(&restored));
+    assert_tree(&sandbox.path("out/project"), &files);
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use std::process::{ Output };
+
+use common::{ Sandbox, code, printed };
+
+#[test]
+fn quiet_match_exits_like_grep() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("Bilbo.txt", "Bilbo");
+    sandbox.ok(&[ "-D", "Bilbo.txt" ]);
+
+    let matched: Output = sandbox.run(&[ "--list", "--quiet-match", "--pattern", "Bil" ]);
+    let unmatched: Output = sandbox.run(&[ "--list", "--quiet-match", "--pattern", "Frodo" ]);
+    let invalid: Output = sandbox.run(&[ "--list", "--quiet-match", "--pattern", "(" ]);
+
+    assert_eq!(code(&matched), 0, "{}", printed(&matched));
+    assert_eq!(printed(&matched), "");
+    assert_eq!(code(&unmatched), 1, "{}", printed(&unmatched));
+    assert_eq!(printed(&unmatched), "");
+    assert_eq!(code(&invalid), 2, "{}", printed(&invalid));
+    assert!(printed(&invalid).contains("regular expression"), "{}", printed(&invalid));
+
+    //
+    // Without it, an empty listing still succeeds.
+    //
+    assert_eq!(code(&sandbox.run(&[ "--list", "--simple", "--pattern", "Frodo" ])), 0);
+}
+
+#[test]
+fn type_filters_scope_the_listing() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("Bilbo.txt", "Bilbo");
+    sandbox.write("Shire/Frodo.txt", "Frodo");
+    sandbox.ok(&[ "-D", "Bilbo.txt", "Shire", "--yes" ]);
+
+    assert_eq!(sandbox.ok(&[ "--list", "--simple", "--only-dirs" ]), "Shire\n");
+    assert_eq!(sandbox.ok(&[ "--list", "--simple", "--only-files" ]), "Bilbo.txt\n");
+}
+
+#[test]
+fn watching_renders_again_when_the_cache_changes() {
+    use std::io::{ Read };
+    use std::process::{ Child, ChildStdin, ChildStdout, ExitStatus, Stdio };
+    use std::sync::{ Arc, Mutex };
+    use std::thread::{ JoinHandle, sleep, spawn };
+    use std::time::{ Duration, Instant };
+
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("Bilbo.txt", "Bilbo");
+    sandbox.write("Frodo.txt", "Frodo");
+    sandbox.ok(&[ "-D", "Bilbo.txt" ]);
+
+    let mut watcher: Child = sandbox.command(&[ "--list", "--watch" ]).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+    let input: ChildStdin = watcher.stdin.take().unwrap();
+    let rendered: Arc<Mutex<String>> = Arc::new(Mutex::new(String::new()));
+    let mut stdout: ChildStdout = watcher.stdout.take().unwrap();
+    let reader: JoinHandle<()> = {
+        let rendered: Arc<Mutex<String>> = Arc::clone(&rendered);
+
+        spawn(move || {
+            let mut buffer: [u8; 1024] = [0; 1024];
+
+            while let Ok(read) = stdout.read(&mut buffer) {
+                if read == 0 {
+                    break;
+                }
+
+                rendered.lock().unwrap().push_str(&String::from_utf8_lossy(&buffer[..read]));
+            }
+        })
+    };
+
+    //
+    // Wait, for at most ten seconds, until `count` listings are rendered,
+    // the last of them naming each of `names`.
+    //
+    let renders = |count: usize, names: &[&str]| -> Vec<String> {
+        let started: Instant = Instant::now();
+
+        loop {
+            let renders: Vec<String> = rendered.lock().unwrap().split("Showing results in trash.").skip(1).map(String::from).collect();
+
+            if (renders.len() >= count && names.iter().all(|name| renders[count - 1].contains(name))) || started.elapsed() > Duration::from_secs(10) {
+                return renders;
+            }
+
+            sleep(Duration::from_millis(50));
+        }
+    };
+
+    assert_eq!(renders(1, &[ "Bilbo.txt" ]).len(), 1);
+    sandbox.ok(&[ "-D", "Frodo.txt" ]);
+
+    let renders: Vec<String> = renders(2, &[ "Bilbo.txt", "Frodo.txt" ]);
+
+    //
+    // Without a terminal, the end of the input stops watching.
+    //
+    drop(input);
+
+    let started: Instant = Instant::now();
+    let status: Option<ExitStatus> = loop {
+        match watcher.try_wait().unwrap() {
+            None if started.elapsed() < Duration::from_secs(10) => sleep(Duration::from_millis(50)),
+            status => break status
+        }
+    };
+
+    watcher.kill().unwrap_or_default();
+    reader.join().unwrap();
+
+    assert!(status.is_some_and(|status| status.success()), "{:?}", status);
+
+    assert_eq!(renders.len(), 2, "{:?}", renders);
+    assert!(renders[0].contains("Bilbo.txt") && !renders[0].contains("Frodo.txt"), "{:?}", renders);
+    assert!(renders[1].contains("Bilbo.txt") && renders[1].contains("Frodo.txt"), "{:?}", renders);
+}
+
+#[test]
+fn display_names_are_listed_and_matched_when_asked() {
+    use serde_json::{ Value, from_str };
+
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("REPORT~1.DOC", "Report");
+    sandbox.ok(&[ "-D", "REPORT~1.DOC" ]);
+    sandbox.ok(&[ "--set-display-name", "REPORT~1.DOC", "Quarterly report.doc" ]);
+
+    assert!(sandbox.ok(&[ "--list" ]).contains("Quarterly report.doc (REPORT~1.DOC)"));
+    assert!(sandbox.ok(&[ "--show", "REPORT~1.DOC" ]).contains("Quarterly report.doc (REPORT~1.DOC)"));
+
+    //
+    // Patterns match raw names, unless display names are asked for.
+    //
+    assert_eq!(sandbox.ok(&[ "--list", "--simple", "--pattern", "Quarterly" ]), "");
+    assert_eq!(sandbox.ok(&[ "--list", "--simple", "--pattern", "Quarterly", "--match-display" ]), "REPORT~1.DOC\n");
+    assert_eq!(sandbox.ok(&[ "--list", "--simple", "--pattern", "REPORT" ]), "REPORT~1.DOC\n");
+
+    let listed: Value = from_str(&sandbox.ok(&[ "--list", "--json" ])).unwrap();
+
+    assert_eq!(listed[0]["name"], "REPORT~1.DOC");
+    assert_eq!(listed[0]["display_name"], "Quarterly report.doc");
+}
+
+#[test]
+fn display_names_need_a_target_and_a_name() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("REPORT~1.DOC", "Report");
+    sandbox.ok(&[ "-D", "REPORT~1.DOC" ]);
+
+    assert_eq!(code(&sandbox.run(&[ "--set-display-name", "REPORT~1.DOC" ])), 2);
+    assert_eq!(code(&sandbox.run(&[ "--set-display-name", "REPORT~1.DOC", "Quarterly report.doc", "--set-display-name", "REPORT~1.DOC", "Report.doc" ])), 2);
+    assert!(printed(&sandbox.run(&[ "--set-display-name", "Gandalf.txt", "Gandalf" ])).contains("Gandalf.txt"));
+    assert!(sandbox.ok(&[ "--list" ]).contains("REPORT~1.DOC"));
+    assert!(!sandbox.ok(&[ "--list" ]).contains("Quarterly report.doc"));
+}
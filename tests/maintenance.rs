@@ -0,0 +1,104 @@
+mod common;
+
+use std::fs::{ create_dir_all, rename, read_dir };
+use std::path::{ PathBuf };
+use std::process::{ Output };
+use serde_json::{ Value };
+
+use common::{ Sandbox, code, printed };
+
+///
+/// Trash `name` and then backdate it's only version to the start of 2000,
+/// returning the directory of it's entry.
+///
+fn trash_old(sandbox: &Sandbox, name: &str) -> PathBuf {
+    sandbox.write(name, "Bilbo");
+    sandbox.ok(&[ "-D", name ]);
+
+    let mut cache: Value = sandbox.cache();
+    let entry: &mut Value = cache["entries"].as_array_mut().unwrap().iter_mut()
+        .find(|entry| entry["key"]["name"] == name)
+        .unwrap();
+    let directory: PathBuf = sandbox.data().join(entry["uuid"].as_str().unwrap());
+    let version: PathBuf = read_dir(&directory).unwrap().next().unwrap().unwrap().path();
+
+    entry["history"][0]["timestamp"] = Value::from("2000-01-01T00:00:00Z");
+    rename(version, directory.join("2000-01-01 00:00:00 UTC")).unwrap();
+    sandbox.set_cache(&cache);
+
+    directory
+}
+
+#[test]
+fn retention_purges_old_versions_even_when_a_batch_fails() {
+    let sandbox: Sandbox = Sandbox::new();
+    let directory: PathBuf = trash_old(&sandbox, "Bilbo.txt");
+
+    sandbox.write("Frodo.txt", "Frodo");
+    sandbox.ok(&[ "-D", "Frodo.txt" ]);
+    sandbox.set_settings(r#"{ "retention_days": 30 }"#);
+    sandbox.write("batch.txt", "restore Gandalf.txt\n");
+
+    assert_eq!(code(&sandbox.run(&[ "--batch", "batch.txt" ])), 7);
+    assert!(!directory.exists());
+    assert_eq!(sandbox.cache()["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(code(&sandbox.run(&[ "--check" ])), 0);
+}
+
+#[test]
+fn a_trash_located_inside_the_home_data_is_refused() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    create_dir_all(sandbox.data().join("trash")).unwrap();
+    sandbox.set_settings(&format!(r#"{{ "location": {:?} }}"#, sandbox.data().join("trash")));
+
+    assert_eq!(code(&sandbox.run(&[ "--list" ])), 5);
+}
+
+#[test]
+fn doctor_reports_counts_which_drifted_from_the_header() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("Bilbo.txt", "Bilbo");
+    sandbox.ok(&[ "-D", "Bilbo.txt" ]);
+
+    assert!(sandbox.ok(&[ "--doctor" ]).contains("cache is consistent"));
+
+    let mut cache: Value = sandbox.cache();
+
+    cache["header"]["versions"] = Value::from(5);
+    sandbox.set_cache(&cache);
+
+    let output: Output = sandbox.run(&[ "--doctor" ]);
+
+    assert_eq!(code(&output), 5, "{}", printed(&output));
+    assert!(printed(&output).contains("versions: header records 5, found 1"), "{}", printed(&output));
+    assert!(printed(&output).contains("the cache's versions have drifted from it's header"), "{}", printed(&output));
+    assert!(!printed(&output).contains("entries: header records"), "{}", printed(&output));
+}
+
+#[test]
+fn pruning_is_scoped_by_type() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    for _ in 0..2 {
+        sandbox.write("Shire/Frodo.txt", "Frodo");
+        sandbox.write("Bilbo.txt", "Bilbo");
+        sandbox.ok(&[ "-D", "Shire", "Bilbo.txt", "--yes" ]);
+    }
+
+    sandbox.ok(&[ "--empty", "--keep-latest", "1", "--only-files" ]);
+
+    let versions = |name: &str| -> usize {
+        sandbox.cache()["entries"].as_array().unwrap().iter()
+            .find(|entry| entry["key"]["name"] == name)
+            .map_or(0, |entry| entry["history"].as_array().unwrap().len())
+    };
+
+    assert_eq!(versions("Bilbo.txt"), 1);
+    assert_eq!(versions("Shire"), 2);
+
+    sandbox.ok(&[ "--empty", "--keep-latest", "1", "--only-dirs" ]);
+
+    assert_eq!(versions("Shire"), 1);
+}
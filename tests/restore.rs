@@ -0,0 +1,402 @@
+mod common;
+
+use std::fs::{ File, remove_dir_all };
+use std::path::{ PathBuf };
+use std::process::{ Output };
+use serde_json::{ Value, json };
+
+use common::{ Sandbox, code, printed };
+
+#[cfg(unix)]
+#[test]
+fn restore_keeps_modes_and_modification_times() {
+    use std::fs::{ metadata, set_permissions, Permissions };
+    use std::os::unix::fs::{ PermissionsExt };
+    use std::time::{ Duration, SystemTime, UNIX_EPOCH };
+
+    let sandbox: Sandbox = Sandbox::new();
+    let modified: SystemTime = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+
+    for (name, mode) in &[ ("run.sh", 0o755), ("notes.txt", 0o644), ("inbox", 0o200), ("sealed", 0o000) ] {
+        let path: PathBuf = sandbox.write(name, "Bilbo");
+
+        File::open(&path).unwrap().set_modified(modified).unwrap();
+        set_permissions(&path, Permissions::from_mode(*mode)).unwrap();
+        sandbox.ok(&[ "-D", name ]);
+        sandbox.ok(&[ "-R", name ]);
+
+        assert_eq!(metadata(&path).unwrap().permissions().mode() & 0o7777, *mode, "mode of {}", name);
+        assert_eq!(metadata(&path).unwrap().modified().unwrap(), modified, "modification time of {}", name);
+    }
+}
+
+#[test]
+fn restores_are_kept_in_the_timeline_when_retrashed() {
+    let sandbox: Sandbox = Sandbox::new();
+    let path: PathBuf = sandbox.write("Bilbo.txt", "Bilbo");
+
+    sandbox.ok(&[ "-D", "Bilbo.txt" ]);
+    sandbox.ok(&[ "-R", "Bilbo.txt" ]);
+    sandbox.ok(&[ "-D", "Bilbo.txt" ]);
+
+    let shown: String = sandbox.ok(&[ "--show", "Bilbo.txt" ]);
+    let timeline: Vec<&str> = shown.lines().skip(1).collect();
+
+    assert_eq!(timeline.len(), 3, "{}", shown);
+    assert!(timeline[0].ends_with(" trashed"));
+    assert!(timeline[1].ends_with(&format!(" restored {} to {}", timeline[0].trim().trim_end_matches(" trashed"), path.display())), "{}", shown);
+    assert!(timeline[2].ends_with(" trashed"));
+
+    let events: &Value = &sandbox.cache()["entries"][0]["events"];
+
+    assert_eq!(events.as_array().unwrap().len(), 1);
+    assert_eq!(events[0]["destination"], Value::from(path.to_str().unwrap()));
+}
+
+#[test]
+fn newest_only_restores_a_single_version() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    for path in &[ "one/Bilbo.txt", "one/Bilbo.txt", "two/Bilbo.txt" ] {
+        sandbox.write(path, path);
+        sandbox.ok(&[ "-D", path ]);
+    }
+
+    assert_eq!(code(&sandbox.run(&[ "-R", "Bilbo.txt", "--newest-only", "--version", "all" ])), 2);
+
+    sandbox.ok(&[ "-R", "Bilbo.txt", "--newest-only" ]);
+
+    assert!(sandbox.path("two/Bilbo.txt").exists());
+    assert!(!sandbox.path("one/Bilbo.txt").exists());
+    assert_eq!(sandbox.cache()["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(sandbox.cache()["entries"][0]["history"].as_array().unwrap().len(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn names_which_are_not_unicode_are_kept_exactly() {
+    use std::ffi::{ OsStr };
+    use std::fs::{ read_dir };
+    use std::os::unix::ffi::{ OsStrExt };
+    use std::process::{ Command };
+
+    let sandbox: Sandbox = Sandbox::new();
+    let name: &OsStr = OsStr::from_bytes(b"caf\xe9");
+
+    File::create(sandbox.path(name)).unwrap();
+
+    let mut command: Command = sandbox.command(&[ "-D" ]);
+
+    assert!(command.arg(name).status().unwrap().success());
+    assert!(!sandbox.path(name).exists());
+    assert_eq!(sandbox.cache()["entries"][0]["key"]["name"], json!({ "Unix": [ 99, 97, 102, 233 ] }));
+    assert_eq!(sandbox.ok(&[ "--list", "--simple" ]), "caf\u{fffd}\n");
+
+    sandbox.ok(&[ "-R", "caf\u{fffd}" ]);
+
+    let restored: Vec<Vec<u8>> = read_dir(sandbox.work()).unwrap()
+        .map(|child| child.unwrap().file_name().as_bytes().to_vec())
+        .collect();
+
+    assert_eq!(restored, vec![ b"caf\xe9".to_vec() ]);
+}
+
+//
+// Restoring through a symbolic link to another filesystem copies the item,
+// and names the link, which can only be tried where `/dev/shm` is a
+// separate tmpfs.
+//
+#[cfg(unix)]
+#[test]
+fn restores_through_a_link_to_another_filesystem_are_copied() {
+    use std::fs::{ create_dir_all, metadata, read_to_string, remove_dir_all };
+    use std::os::unix::fs::{ symlink, MetadataExt };
+    use std::path::{ Path };
+
+    let sandbox: Sandbox = Sandbox::new();
+    let shared: PathBuf = Path::new("/dev/shm").join(format!("tman-cli-{}", std::process::id()));
+
+    if create_dir_all(&shared).is_err() || metadata(&shared).unwrap().dev() == metadata(sandbox.work()).unwrap().dev() {
+        remove_dir_all(&shared).unwrap_or_default();
+        eprintln!("skipped, /dev/shm is not another filesystem");
+        return;
+    }
+
+    let link: PathBuf = sandbox.path("shared");
+
+    symlink(&shared, &link).unwrap();
+    sandbox.write("Bilbo.txt", "Bilbo");
+    sandbox.ok(&[ "-D", "Bilbo.txt" ]);
+
+    let output: Output = sandbox.run(&[ "-R", "Bilbo.txt", "--to", link.to_str().unwrap() ]);
+    let restored: Option<String> = read_to_string(shared.join("Bilbo.txt")).ok();
+
+    remove_dir_all(&shared).unwrap_or_default();
+
+    assert_eq!(code(&output), 0, "{}", printed(&output));
+    assert!(printed(&output).contains(&format!("{} is a symbolic link to {} on another filesystem", link.display(), shared.display())), "{}", printed(&output));
+    assert_eq!(restored.as_deref(), Some("Bilbo"));
+    assert!(sandbox.cache()["entries"].as_array().unwrap().iter().all(|entry| entry["history"].as_array().unwrap().is_empty()));
+}
+
+#[cfg(unix)]
+#[test]
+fn restores_onto_the_stored_version_are_refused() {
+    use std::fs::{ hard_link, read_dir, read_to_string, remove_file };
+    use std::os::unix::fs::{ symlink };
+
+    let sandbox: Sandbox = Sandbox::new();
+    let path: PathBuf = sandbox.write("Bilbo.txt", "Bilbo");
+
+    sandbox.ok(&[ "-D", "Bilbo.txt" ]);
+
+    let directory: PathBuf = sandbox.data().join(sandbox.cache()["entries"][0]["uuid"].as_str().unwrap());
+    let stored: PathBuf = read_dir(&directory).unwrap().next().unwrap().unwrap().path();
+
+    //
+    // Both a link to the stored version and another name for it would have
+    // it moved onto itself.
+    //
+    for link in &[ symlink, hard_link ] {
+        link(&stored, &path).unwrap();
+
+        let output: Output = sandbox.run(&[ "-R", "Bilbo.txt" ]);
+
+        assert_eq!(code(&output), 6, "{}", printed(&output));
+        assert!(printed(&output).contains("refusing to restore onto"), "{}", printed(&output));
+        assert_eq!(read_to_string(&stored).unwrap(), "Bilbo");
+        assert_eq!(sandbox.cache()["entries"][0]["history"].as_array().unwrap().len(), 1);
+
+        remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn a_failed_move_keeps_the_version_in_the_trash() {
+    use std::fs::{ read_dir, remove_dir, write };
+
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("Shire/Bilbo.txt", "Bilbo");
+    sandbox.ok(&[ "-D", "Shire/Bilbo.txt" ]);
+
+    //
+    // The origin's directory is replaced by a file, which it can not be moved
+    // into.
+    //
+    remove_dir(sandbox.path("Shire")).unwrap();
+    write(sandbox.path("Shire"), "").unwrap();
+
+    let output: Output = sandbox.run(&[ "-R", "Bilbo.txt" ]);
+    let directory: PathBuf = sandbox.data().join(sandbox.cache()["entries"][0]["uuid"].as_str().unwrap());
+
+    assert_eq!(code(&output), 4, "{}", printed(&output));
+    assert_eq!(sandbox.cache()["entries"][0]["history"].as_array().unwrap().len(), 1);
+    assert_eq!(read_dir(&directory).unwrap().count(), 1);
+    assert!(sandbox.cache()["entries"][0]["events"].is_null());
+}
+
+#[test]
+fn an_entry_without_versions_is_reported_missing() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("Bilbo.txt", "Bilbo");
+    sandbox.ok(&[ "-D", "Bilbo.txt" ]);
+
+    let mut cache: Value = sandbox.cache();
+
+    cache["entries"][0]["history"] = json!([]);
+    sandbox.set_cache(&cache);
+
+    for arguments in &[ [ "-R", "Bilbo.txt" ], [ "--purge", "Bilbo.txt" ] ] {
+        let output: Output = sandbox.run(arguments);
+
+        assert_eq!(code(&output), 3, "{}", printed(&output));
+        assert!(printed(&output).contains("could not locate 'Bilbo.txt'"), "{}", printed(&output));
+    }
+}
+
+///
+/// Trash a small project, with a nested file and a nested directory.
+///
+fn trash_project(sandbox: &Sandbox) {
+    sandbox.write("project/src/main.rs", "fn main() {}");
+    sandbox.write("project/docs/guide/intro.md", "# Intro");
+    sandbox.write("project/docs/guide/usage.md", "# Usage");
+    sandbox.ok(&[ "-D", "project", "--yes" ]);
+}
+
+#[test]
+fn inner_paths_are_extracted_leaving_the_version() {
+    use std::fs::{ read_to_string };
+
+    let sandbox: Sandbox = Sandbox::new();
+
+    trash_project(&sandbox);
+    sandbox.ok(&[ "-R", "project", "--inner", "src/main.rs" ]);
+    sandbox.ok(&[ "-R", "project", "--inner", "docs/guide", "--to", "out" ]);
+
+    assert_eq!(read_to_string(sandbox.path("project/src/main.rs")).unwrap(), "fn main() {}");
+    assert!(!sandbox.path("project/docs").exists());
+    assert_eq!(read_to_string(sandbox.path("out/guide/intro.md")).unwrap(), "# Intro");
+    assert_eq!(read_to_string(sandbox.path("out/guide/usage.md")).unwrap(), "# Usage");
+    assert_eq!(sandbox.cache()["entries"][0]["history"].as_array().unwrap().len(), 1);
+
+    //
+    // The version is left whole, so it can still be restored.
+    //
+    sandbox.ok(&[ "-R", "project", "--to", "whole" ]);
+
+    assert_eq!(read_to_string(sandbox.path("whole/project/docs/guide/intro.md")).unwrap(), "# Intro");
+}
+
+#[test]
+fn inner_paths_must_lie_inside_the_item() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    trash_project(&sandbox);
+
+    for inner in &[ "../cache.json", "src/../../cache.json", "/etc/hostname", "" ] {
+        let output: Output = sandbox.run(&[ "-R", "project", "--inner", inner ]);
+
+        assert_eq!(code(&output), 2, "{}", printed(&output));
+        assert!(printed(&output).contains("is not a relative path inside the item"), "{}", printed(&output));
+    }
+
+    let output: Output = sandbox.run(&[ "-R", "project", "--inner", "src/lib.rs" ]);
+
+    assert_eq!(code(&output), 3, "{}", printed(&output));
+    assert!(printed(&output).contains("could not locate 'project/src/lib.rs'"), "{}", printed(&output));
+}
+
+#[cfg(unix)]
+#[test]
+fn inner_paths_do_not_follow_links_out_of_the_item() {
+    use std::os::unix::fs::{ symlink };
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("secret.txt", "Ring");
+    sandbox.write("project/README.md", "# Project");
+    symlink(sandbox.work(), sandbox.path("project/outside")).unwrap();
+    sandbox.ok(&[ "-D", "project", "--yes" ]);
+
+    let output: Output = sandbox.run(&[ "-R", "project", "--inner", "outside/secret.txt", "--to", "out" ]);
+
+    assert_eq!(code(&output), 3, "{}", printed(&output));
+    assert!(!sandbox.path("out/secret.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn execute_bits_are_kept_unless_stripped() {
+    use std::fs::{ metadata, set_permissions, Permissions };
+    use std::os::unix::fs::{ PermissionsExt };
+
+    let sandbox: Sandbox = Sandbox::new();
+    let script: PathBuf = sandbox.write("run.sh", "echo Bilbo");
+    let tool: PathBuf = sandbox.write("bin/tool", "echo Frodo");
+    let mode = |path: &PathBuf| metadata(path).unwrap().permissions().mode() & 0o777;
+
+    set_permissions(&script, Permissions::from_mode(0o755)).unwrap();
+    set_permissions(&tool, Permissions::from_mode(0o750)).unwrap();
+
+    sandbox.ok(&[ "-D", "run.sh" ]);
+
+    let output: Output = sandbox.run(&[ "-R", "run.sh" ]);
+
+    assert!(printed(&output).contains(&format!("restored executable {}", script.display())), "{}", printed(&output));
+    assert_eq!(mode(&script), 0o755);
+
+    sandbox.ok(&[ "-D", "run.sh" ]);
+
+    let output: Output = sandbox.run(&[ "-R", "run.sh", "--no-exec" ]);
+
+    assert!(printed(&output).contains(&format!("cleared execute permission of {}", script.display())), "{}", printed(&output));
+    assert_eq!(mode(&script), 0o644);
+
+    //
+    // Files inside a directory are stripped too, as they are by the setting.
+    //
+    sandbox.ok(&[ "-D", "bin", "--yes" ]);
+    sandbox.set_settings(r#"{ "restore_strip_exec": true }"#);
+    sandbox.ok(&[ "-R", "bin" ]);
+
+    assert_eq!(mode(&tool), 0o640);
+}
+
+#[cfg(unix)]
+#[test]
+fn restored_links_to_missing_targets_are_noted() {
+    use std::fs::{ read_link, remove_file, symlink_metadata };
+    use std::os::unix::fs::{ symlink };
+
+    let sandbox: Sandbox = Sandbox::new();
+    let target: PathBuf = sandbox.write("Bilbo.txt", "Bilbo");
+    let link: PathBuf = sandbox.path("latest");
+
+    symlink(&target, &link).unwrap();
+    sandbox.ok(&[ "-D", "latest" ]);
+
+    assert_eq!(sandbox.cache()["entries"][0]["history"][0]["link_target"], target.to_str().unwrap());
+    assert!(sandbox.ok(&[ "--show", "latest" ]).contains(&format!("trashed, linking to {}", target.display())));
+
+    remove_file(&target).unwrap();
+
+    let output: Output = sandbox.run(&[ "-R", "latest" ]);
+
+    assert_eq!(code(&output), 0, "{}", printed(&output));
+    assert!(printed(&output).contains(&format!("restored symlink points to missing {}", target.display())), "{}", printed(&output));
+    assert!(symlink_metadata(&link).unwrap().file_type().is_symlink());
+    assert_eq!(read_link(&link).unwrap(), target);
+}
+
+#[test]
+fn pattern_restores_are_scoped_by_type() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("Shire/Frodo.txt", "Frodo");
+    sandbox.write("Shire.txt", "Shire");
+    sandbox.ok(&[ "-D", "Shire", "Shire.txt", "--yes" ]);
+
+    sandbox.ok(&[ "-R", "--pattern", "^Shire", "--only-files" ]);
+
+    assert!(sandbox.path("Shire.txt").is_file());
+    assert!(!sandbox.path("Shire").exists());
+
+    sandbox.ok(&[ "-R", "--match", "^Shire", "--only-dirs" ]);
+
+    assert!(sandbox.path("Shire/Frodo.txt").is_file());
+    assert!(sandbox.cache()["entries"].as_array().unwrap().is_empty());
+
+    //
+    // Named items are restored whatever their type.
+    //
+    sandbox.ok(&[ "-D", "Shire", "--yes" ]);
+
+    assert_eq!(code(&sandbox.run(&[ "-R", "Shire", "--only-dirs" ])), 2);
+    assert_eq!(code(&sandbox.run(&[ "-R", "--last", "--only-files" ])), 2);
+    assert!(!sandbox.path("Shire").exists());
+}
+
+#[test]
+fn restoring_everything_fails_when_any_item_fails() {
+    let sandbox: Sandbox = Sandbox::new();
+
+    sandbox.write("Bilbo.txt", "Bilbo");
+    sandbox.write("Frodo.txt", "Frodo");
+    sandbox.ok(&[ "-D", "Bilbo.txt", "Frodo.txt" ]);
+
+    let cache: Value = sandbox.cache();
+    let broken: &Value = cache["entries"].as_array().unwrap().iter().find(|entry| entry["key"]["name"] == "Frodo.txt").unwrap();
+
+    remove_dir_all(sandbox.data().join(broken["uuid"].as_str().unwrap())).unwrap();
+
+    let output: Output = sandbox.run(&[ "--restore-all" ]);
+
+    assert_eq!(code(&output), 7, "{}", printed(&output));
+    assert!(printed(&output).contains("restored 1 items, 1 failed"), "{}", printed(&output));
+    assert!(printed(&output).contains("1 of 2 restores failed"), "{}", printed(&output));
+    assert!(sandbox.path("Bilbo.txt").is_file());
+    assert!(!sandbox.path("Frodo.txt").exists());
+    assert_eq!(sandbox.cache()["entries"].as_array().unwrap().len(), 1);
+}